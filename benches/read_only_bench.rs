@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate criterion;
+extern crate blockstack_lib;
+
+use blockstack_lib::{
+    vm::types::{Value, QualifiedContractIdentifier},
+    vm::database::{MemoryBackingStore},
+    vm::contexts::OwnedEnvironment,
+    vm::costs::LimitedCostTracker,
+    vm::ast,
+};
+
+use criterion::Criterion;
+
+const EVALUATIONS: usize = 1000;
+
+const CONTRACT: &str = "
+    (define-map balances ((owner int)) ((balance int)))
+    (map-set balances ((owner 1)) ((balance 100)))
+    (define-read-only (get-balance (owner int))
+      (default-to 0 (get balance (map-get? balances ((owner owner))))))";
+
+const QUERY: &str = "(+ (get-balance 1) (get-balance 2))";
+
+fn eval_read_only_string(n: usize) {
+    let contract_identifier = QualifiedContractIdentifier::local("balances").unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut owned_env = OwnedEnvironment::new_cost_limited(marf.as_clarity_db(), LimitedCostTracker::new_max_limit());
+    owned_env.initialize_contract(contract_identifier.clone(), CONTRACT).unwrap();
+
+    for _ in 0..n {
+        owned_env.execute_in_env(Value::from(QualifiedContractIdentifier::transient().issuer),
+                                 |env| env.eval_read_only(&contract_identifier, QUERY)).unwrap();
+    }
+}
+
+fn eval_read_only_parsed(n: usize) {
+    let contract_identifier = QualifiedContractIdentifier::local("balances").unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut owned_env = OwnedEnvironment::new_cost_limited(marf.as_clarity_db(), LimitedCostTracker::new_max_limit());
+    owned_env.initialize_contract(contract_identifier.clone(), CONTRACT).unwrap();
+
+    let parsed = ast::build_ast(&contract_identifier, QUERY, &mut ()).unwrap()
+        .expressions;
+    for _ in 0..n {
+        owned_env.eval_read_only_parsed(&contract_identifier, &parsed).unwrap();
+    }
+}
+
+pub fn read_only_benchmark(c: &mut Criterion) {
+    c.bench_function("eval_read_only_string", |b| b.iter(|| eval_read_only_string(EVALUATIONS)));
+    c.bench_function("eval_read_only_parsed", |b| b.iter(|| eval_read_only_parsed(EVALUATIONS)));
+}
+
+criterion_group!(benches, read_only_benchmark);
+criterion_main!(benches);
@@ -222,6 +222,23 @@ pub fn c32_address_decode(c32_address_str: &str) -> Result<(u8, Vec<u8>), Error>
     }
 }
 
+/// Returns the version byte of a well-formed c32 address (an `S`, the version character,
+///   and a 20-byte hash with a valid checksum), or None if the address is malformed.
+pub fn c32_address_version(c32_address_str: &str) -> Option<u8> {
+    // checking for ASCII up front keeps the decoder from splitting a multi-byte character
+    if !c32_address_str.is_ascii() || !(c32_address_str.starts_with('S') || c32_address_str.starts_with('s')) {
+        return None
+    }
+    match c32_address_decode(c32_address_str) {
+        Ok((version, ref data)) if data.len() == 20 => Some(version),
+        _ => None
+    }
+}
+
+pub fn is_valid_c32_address(c32_address_str: &str) -> bool {
+    c32_address_version(c32_address_str).is_some()
+}
+
 pub fn c32_address(version: u8, data: &[u8]) -> Result<String, Error> {
     let c32_string = c32_check_encode(version, data)?;
     Ok(format!("S{}", c32_string))
@@ -373,6 +390,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_address_validation() {
+        let good = [
+            ("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7", 22),
+            ("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G", 20),
+            ("ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ", 26),
+            ("SN2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKP6D2ZK9", 21),
+            ("S02J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE", 0),
+            ("s02j6zy48gvlez5v2v5rb9mp66sw86pykkpvkg2ce", 0)];
+        for (address, version) in good.iter() {
+            assert_eq!(c32_address_version(address), Some(*version));
+            assert!(is_valid_c32_address(address));
+        }
+
+        let bad = [
+            // bad checksum
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8",
+            // wrong length: too short, too long, and a 19-byte hash with a valid checksum
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ70",
+            &c32_address(22, &[1; 19]).unwrap(),
+            // illegal characters
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EU7",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9E-7",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJé",
+            "Sé2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            // not an S address
+            "XP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            "",
+            "S"];
+        for address in bad.iter() {
+            assert_eq!(c32_address_version(address), None, "{}", address);
+            assert!(!is_valid_c32_address(address));
+        }
+    }
+
     #[test]
     fn test_normalize() {
         let addrs = [
@@ -375,6 +375,41 @@ mod test {
         assert!(ContractName::consensus_deserialize(&mut &s_payload[..]).is_err());
     }
 
+    #[test]
+    fn test_clarity_name_validate() {
+        use vm::representations::NameError;
+
+        assert_eq!(ClarityName::validate("hello-world?"), Ok(()));
+        assert_eq!(ClarityName::validate("<="), Ok(()));
+        assert_eq!(ClarityName::validate("*"), Ok(()));
+
+        assert_eq!(ClarityName::validate(""), Err(NameError::Empty));
+        assert_eq!(ClarityName::validate(&"a".repeat(129)), Err(NameError::TooLong(128, 129)));
+        assert_eq!(ClarityName::validate("1abc"), Err(NameError::StartsWithDigit));
+        assert_eq!(ClarityName::validate("-abc"), Err(NameError::IllegalFirstCharacter('-')));
+        assert_eq!(ClarityName::validate("ab.c"), Err(NameError::IllegalCharacter('.')));
+
+        // validate() and try_from() must agree on what is legal
+        for name in ["hello-world?", "<=", "", "1abc", "-abc", "ab.c", "a b"].iter() {
+            assert_eq!(ClarityName::validate(name).is_ok(), ClarityName::try_from(name.to_string()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_contract_name_validate() {
+        use vm::representations::NameError;
+
+        assert_eq!(ContractName::validate("hello_world-1"), Ok(()));
+
+        assert_eq!(ContractName::validate(""), Err(NameError::Empty));
+        assert_eq!(ContractName::validate("abcd"), Err(NameError::TooShort(CONTRACT_MIN_NAME_LENGTH, 4)));
+        assert_eq!(ContractName::validate(&"a".repeat(CONTRACT_MAX_NAME_LENGTH + 1)),
+                   Err(NameError::TooLong(CONTRACT_MAX_NAME_LENGTH, CONTRACT_MAX_NAME_LENGTH + 1)));
+        assert_eq!(ContractName::validate("1hello"), Err(NameError::StartsWithDigit));
+        assert_eq!(ContractName::validate("_hello"), Err(NameError::IllegalFirstCharacter('_')));
+        assert_eq!(ContractName::validate("hello?world"), Err(NameError::IllegalCharacter('?')));
+    }
+
     #[test]
     fn test_url_parse() {
         assert!(UrlString::try_from("asdfjkl;").unwrap().parse_to_block_url().unwrap_err().to_string().find("Invalid URL").is_some());
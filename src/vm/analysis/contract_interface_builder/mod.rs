@@ -1,5 +1,6 @@
 use vm::analysis::types::ContractAnalysis;
 use std::collections::{BTreeMap, BTreeSet};
+use std::{fmt, io};
 use vm::{ClarityName};
 use vm::types::{TypeSignature, FunctionArg, TupleTypeSignature, FunctionType, FixedFunction};
 
@@ -22,6 +23,9 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         type_map: _,
         cost_track: _,
         contract_interface: _,
+        contract_size: _,
+        config: _,
+        pass_costs: _,
     } = contract_analysis;
 
     contract_interface.functions.append(
@@ -307,4 +311,123 @@ impl ContractInterface {
     pub fn serialize(&self) -> String {
         serde_json::to_string(self).expect("Failed to serialize contract interface")
     }
+
+    /// Serialize the interface as pretty-printed JSON, giving up as soon as the
+    ///   output grows past `max_bytes`, rather than rendering the whole interface first.
+    pub fn to_json_bounded(&self, max_bytes: usize) -> Result<String, InterfaceTooLarge> {
+        let mut writer = BoundedWriter { output: Vec::new(), max_bytes };
+        if serde_json::to_writer_pretty(&mut writer, self).is_err() {
+            return Err(InterfaceTooLarge { max_bytes })
+        }
+        Ok(String::from_utf8(writer.output).expect("serde_json produced invalid UTF-8"))
+    }
+
+    /// Compare this (deployed) interface against a `newer` version of the contract's interface.
+    ///   Only the externally visible parts are compared: public and read-only functions
+    ///   (by access, argument types and return type), maps, and data-var types.
+    pub fn diff(&self, newer: &ContractInterface) -> InterfaceDiff {
+        let callable_functions = |interface: &ContractInterface| -> BTreeMap<String, (ContractInterfaceFunctionAccess, Vec<ContractInterfaceAtomType>, ContractInterfaceAtomType)> {
+            interface.functions.iter()
+                .filter(|function| function.access != ContractInterfaceFunctionAccess::private)
+                .map(|function| (function.name.clone(),
+                                 (function.access.clone(),
+                                  function.args.iter().map(|arg| arg.type_f.clone()).collect(),
+                                  function.outputs.type_f.clone())))
+                .collect()
+        };
+        let maps = |interface: &ContractInterface| -> BTreeMap<String, (Vec<ContractInterfaceTupleEntryType>, Vec<ContractInterfaceTupleEntryType>)> {
+            interface.maps.iter()
+                .map(|map| (map.name.clone(), (map.key.clone(), map.value.clone())))
+                .collect()
+        };
+        let data_vars = |interface: &ContractInterface| -> BTreeMap<String, ContractInterfaceAtomType> {
+            interface.variables.iter()
+                .filter(|variable| variable.access == ContractInterfaceVariableAccess::variable)
+                .map(|variable| (variable.name.clone(), variable.type_f.clone()))
+                .collect()
+        };
+
+        InterfaceDiff {
+            functions: DefinitionsDiff::between(&callable_functions(self), &callable_functions(newer)),
+            maps: DefinitionsDiff::between(&maps(self), &maps(newer)),
+            data_vars: DefinitionsDiff::between(&data_vars(self), &data_vars(newer)),
+        }
+    }
+}
+
+/// The names of definitions added, removed, or changed between two versions of a contract.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DefinitionsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl DefinitionsDiff {
+    fn between<T: PartialEq>(older: &BTreeMap<String, T>, newer: &BTreeMap<String, T>) -> DefinitionsDiff {
+        let mut diff = DefinitionsDiff::default();
+        for (name, older_signature) in older.iter() {
+            match newer.get(name) {
+                None => diff.removed.push(name.clone()),
+                Some(newer_signature) if newer_signature != older_signature => diff.changed.push(name.clone()),
+                _ => {}
+            }
+        }
+        diff.added = newer.keys()
+            .filter(|name| !older.contains_key(*name))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InterfaceDiff {
+    pub functions: DefinitionsDiff,
+    pub maps: DefinitionsDiff,
+    pub data_vars: DefinitionsDiff,
+}
+
+impl InterfaceDiff {
+    /// An upgrade is backward compatible if it only adds definitions: removing a definition,
+    ///   or changing its signature, breaks callers of the deployed version.
+    pub fn is_backward_compatible(&self) -> bool {
+        [&self.functions, &self.maps, &self.data_vars].iter()
+            .all(|diff| diff.removed.is_empty() && diff.changed.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceTooLarge {
+    pub max_bytes: usize
+}
+
+impl fmt::Display for InterfaceTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "contract interface exceeds {} bytes", self.max_bytes)
+    }
+}
+
+// Buffers written bytes, failing any write that would exceed max_bytes.
+struct BoundedWriter {
+    output: Vec<u8>,
+    max_bytes: usize
+}
+
+impl io::Write for BoundedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.output.len() + buf.len() > self.max_bytes {
+            return Err(io::Error::new(io::ErrorKind::Other, "contract interface too large"))
+        }
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,50 @@
+use vm::representations::SymbolicExpression;
+use vm::representations::SymbolicExpressionType::{AtomValue, LiteralValue};
+use vm::functions::NativeFunctions;
+use vm::types::Value;
+use vm::diagnostic::Diagnostic;
+use super::native_application;
+
+#[cfg(test)]
+mod tests;
+
+///
+/// Advisory check, like `check_unreachable_code`, for integer division by zero: reports every
+///   divisor of a `/` or `mod` which is a literal `0` or `u0`, as such an application always fails.
+///
+/// Divisors computed at runtime, such as `(- 1 1)` or a bound variable, are never reported.
+pub fn check_division_by_zero(expressions: &[SymbolicExpression]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for expression in expressions.iter() {
+        check_expression(expression, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn check_expression(expression: &SymbolicExpression, diagnostics: &mut Vec<Diagnostic>) {
+    if let Some(list) = expression.match_list() {
+        if let Some((function, args)) = native_application(list) {
+            match function {
+                NativeFunctions::Divide | NativeFunctions::Modulo => {
+                    for divisor in args.iter().skip(1).filter(|arg| is_literal_zero(arg)) {
+                        diagnostics.push(Diagnostic::warning(
+                            "division by a literal zero always fails".to_string(),
+                            vec![divisor.span.clone()]));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        for item in list.iter() {
+            check_expression(item, diagnostics);
+        }
+    }
+}
+
+fn is_literal_zero(expression: &SymbolicExpression) -> bool {
+    match expression.expr {
+        AtomValue(ref value) | LiteralValue(ref value) => *value == Value::Int(0) || *value == Value::UInt(0),
+        _ => false
+    }
+}
@@ -0,0 +1,51 @@
+use vm::ast::parse;
+use vm::analysis::division_by_zero_checker::check_division_by_zero;
+use vm::analysis::mem_type_check;
+use vm::diagnostic::Level;
+use vm::types::QualifiedContractIdentifier;
+
+fn check(contract: &str) -> Vec<(u32, u32)> {
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let expressions = parse(&contract_identifier, contract).unwrap();
+    check_division_by_zero(&expressions).iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.level, Level::Warning);
+            assert_eq!(diagnostic.spans.len(), 1);
+            (diagnostic.spans[0].start_line, diagnostic.spans[0].start_column)
+        })
+        .collect()
+}
+
+#[test]
+fn test_division_by_literal_zero() {
+    let contract = "(define-read-only (f (x int))
+  (/ x 0))";
+
+    // the division type checks, so it is only reported by the advisory check
+    mem_type_check(contract).unwrap();
+
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let diagnostics = check_division_by_zero(&parse(&contract_identifier, contract).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, Level::Warning);
+    assert_eq!(diagnostics[0].message, "division by a literal zero always fails");
+    assert_eq!(diagnostics[0].spans[0].start_line, 2);
+    assert_eq!(diagnostics[0].spans[0].start_column, 8);
+}
+
+#[test]
+fn test_literal_zero_divisors() {
+    assert_eq!(check("(let ((x u10)) (mod x u0))"), vec![(1, 23)]);
+    assert_eq!(check("(/ 10 2 0)"), vec![(1, 9)]);
+    assert_eq!(check("(/ 10 0 0)"), vec![(1, 7), (1, 9)]);
+    assert_eq!(check("(define-data-var bar int (/ 1 0))"), vec![(1, 31)]);
+}
+
+#[test]
+fn test_non_literal_divisors() {
+    assert!(check("(let ((x 10) (y 0)) (/ x y))").is_empty());
+    assert!(check("(let ((x u10) (y u0)) (mod x y))").is_empty());
+    assert!(check("(/ 0 1)").is_empty());
+    assert!(check("(/ 10 (- 1 1))").is_empty());
+    assert!(check("(* 10 0)").is_empty());
+}
@@ -32,6 +32,8 @@ pub enum CheckErrors {
     TypeError(TypeSignature, TypeSignature),
     TypeLiteralError(TypeSignature, TypeSignature),
     TypeValueError(TypeSignature, Value),
+    // an arithmetic operation on a mix of int and uint arguments
+    IntUintMismatch(Box<IntUintMismatch>),
 
     NoSuperType(TypeSignature, TypeSignature),
     InvalidTypeDescription,
@@ -41,6 +43,9 @@ pub enum CheckErrors {
     UnionTypeError(Vec<TypeSignature>, TypeSignature),
     UnionTypeValueError(Vec<TypeSignature>, Value),
 
+    // principal on the wrong network
+    PrincipalNetworkMismatch(String, Value),
+
     ExpectedLiteral,
     ExpectedOptionalType(TypeSignature),
     ExpectedResponseType(TypeSignature),
@@ -77,6 +82,7 @@ pub enum CheckErrors {
     EmptyTuplesNotAllowed,
     BadTupleConstruction,
     TupleExpectsPairs,
+    TupleTooLarge { arity: u64, limit: u64 },
 
     // variables
     NoSuchDataVariable(String),
@@ -128,6 +134,8 @@ pub enum CheckErrors {
     // argument counts
     RequiresAtLeastArguments(usize, usize),
     IncorrectArgumentCount(usize, usize),
+    ArgumentTypeError { index: usize, expected: TypeSignature, found: Value },
+    IncorrectCallbackArity(String, String, usize, usize),
     IfArmsMustMatch(TypeSignature, TypeSignature),
     MatchArmsMustMatch(TypeSignature, TypeSignature),
     DefaultTypesMustMatch(TypeSignature, TypeSignature),
@@ -142,14 +150,25 @@ pub enum CheckErrors {
     ImportTraitBadSignature,
     TraitReferenceNotAllowed,
     BadTraitImplementation(String, String),
+    IncompleteTraitImplementation(String, Vec<String>, Vec<String>),
     DefineTraitBadSignature,
     UnexpectedTraitOrFieldReference,
     TraitBasedContractCallInReadOnly,
 
     WriteAttemptedInReadOnly,
+    ContractCallWriteInReadOnly(String, String),
     AtBlockClosureMustBeReadOnly
 }
 
+/// An arithmetic operation `op` mixing int and uint arguments: `found` is the type of the
+///   argument which doesn't match the `expected` type of the preceding ones.
+#[derive(Debug, PartialEq)]
+pub struct IntUintMismatch {
+    pub op: String,
+    pub expected: TypeSignature,
+    pub found: TypeSignature,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CheckError {
     pub err: CheckErrors,
@@ -157,6 +176,12 @@ pub struct CheckError {
     pub diagnostic: Diagnostic,
 }
 
+impl From<CheckError> for Diagnostic {
+    fn from(e: CheckError) -> Self {
+        e.diagnostic
+    }
+}
+
 impl CheckError {
     pub fn new(err: CheckErrors) -> CheckError {
         let diagnostic = Diagnostic::err(&err);
@@ -182,6 +207,19 @@ impl CheckError {
     }
 }
 
+impl CheckErrors {
+    /// Whether the type checker may continue past this error when collecting errors:
+    ///   exhausted budgets, depth limits and internal failures abort the analysis.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            CheckErrors::CostOverflow | CheckErrors::CostBalanceExceeded(..) |
+            CheckErrors::MemoryBalanceExceeded(..) | CheckErrors::MaxContextDepthReached |
+            CheckErrors::TypeSignatureTooDeep | CheckErrors::TypeAlreadyAnnotatedFailure => false,
+            _ => true
+        }
+    }
+}
+
 impl fmt::Display for CheckErrors {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -295,8 +333,10 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::TypeError(expected_type, found_type) => format!("expecting expression of type '{}', found '{}'", expected_type, found_type),
             CheckErrors::TypeLiteralError(expected_type, found_type) => format!("expecting a literal of type '{}', found '{}'", expected_type, found_type),
             CheckErrors::TypeValueError(expected_type, found_value) => format!("expecting expression of type '{}', found '{}'", expected_type, found_value),
+            CheckErrors::IntUintMismatch(mismatch) => format!("'{}' cannot mix signed and unsigned integers (got '{}' and '{}')", mismatch.op, mismatch.expected, mismatch.found),
             CheckErrors::UnionTypeError(expected_types, found_type) => format!("expecting expression of type {}, found '{}'", formatted_expected_types(expected_types), found_type),
             CheckErrors::UnionTypeValueError(expected_types, found_type) => format!("expecting expression of type {}, found '{}'", formatted_expected_types(expected_types), found_type),
+            CheckErrors::PrincipalNetworkMismatch(expected_network, found_value) => format!("expecting a {} principal, found '{}'", expected_network, found_value),
             CheckErrors::ExpectedOptionalType(found_type) => format!("expecting expression of type 'optional', found '{}'", found_type),
             CheckErrors::ExpectedOptionalOrResponseType(found_type) => format!("expecting expression of type 'optional' or 'response', found '{}'", found_type),
             CheckErrors::ExpectedOptionalOrResponseValue(found_type) =>  format!("expecting expression of type 'optional' or 'response', found '{}'", found_type),
@@ -310,6 +350,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::ExpectedTuple(type_signature) => format!("expecting tuple, found '{}'", type_signature),
             CheckErrors::NoSuchTupleField(field_name, tuple_signature) => format!("cannot find field '{}' in tuple '{}'", field_name, tuple_signature),
             CheckErrors::BadTupleConstruction => format!("invalid tuple syntax, expecting list of pair"),
+            CheckErrors::TupleTooLarge { arity, limit } => format!("tuple has {} fields, more than the limit of {}", arity, limit),
             CheckErrors::TupleExpectsPairs => format!("invalid tuple syntax, expecting pair"),
             CheckErrors::NoSuchDataVariable(var_name) => format!("use of unresolved persisted variable '{}'", var_name),
             CheckErrors::BadTransferSTXArguments => format!("STX transfer expects an int amount, from principal, to principal"),
@@ -344,6 +385,8 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::UndefinedFunction(var_name) => format!("use of unresolved function '{}'", var_name),
             CheckErrors::RequiresAtLeastArguments(expected, found) => format!("expecting >= {} argument, got {}", expected, found),
             CheckErrors::IncorrectArgumentCount(expected_count, found_count) => format!("expecting {} arguments, got {}", expected_count, found_count),
+            CheckErrors::ArgumentTypeError { index, expected, found } => format!("argument {} expects type '{}', found '{}'", index, expected, found),
+            CheckErrors::IncorrectCallbackArity(iterable_name, function_name, expected_count, found_count) => format!("function '{}' passed to '{}' must take {} argument(s), but takes {}", function_name, iterable_name, expected_count, found_count),
             CheckErrors::IfArmsMustMatch(type_1, type_2) => format!("expression types returned by the arms of 'if' must match (got '{}' and '{}')", type_1, type_2),
             CheckErrors::MatchArmsMustMatch(type_1, type_2) => format!("expression types returned by the arms of 'match' must match (got '{}' and '{}')", type_1, type_2),
             CheckErrors::DefaultTypesMustMatch(type_1, type_2) => format!("expression types passed in 'default-to' must match (got '{}' and '{}')", type_1, type_2),
@@ -352,6 +395,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::UnknownFunction(function_name) => format!("use of unresolved function '{}'", function_name),
             CheckErrors::TraitBasedContractCallInReadOnly => format!("use of trait based contract calls are not allowed in read-only context"),
             CheckErrors::WriteAttemptedInReadOnly => format!("expecting read-only statements, detected a writing operation"),
+            CheckErrors::ContractCallWriteInReadOnly(contract, function_name) => format!("expecting read-only statements, detected a call to '{}' in '{}', which is not read-only", function_name, contract),
             CheckErrors::AtBlockClosureMustBeReadOnly => format!("(at-block ...) closures expect read-only statements, but detected a writing operation"),
             CheckErrors::BadTokenName => format!("expecting an token name as an argument"),
             CheckErrors::DefineFTBadSignature => format!("(define-token ...) expects a token name as an argument"),
@@ -362,6 +406,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::TraitMethodUnknown(trait_name, func_name) => format!("method '{}' unspecified in trait <{}>", func_name, trait_name),
             CheckErrors::ImportTraitBadSignature => format!("(use-trait ...) expects a trait name and a trait identifier"),
             CheckErrors::BadTraitImplementation(trait_name, func_name) => format!("invalid signature for method '{}' regarding trait's specification <{}>", func_name, trait_name),
+            CheckErrors::IncompleteTraitImplementation(trait_name, missing, mismatched) => format!("contract does not conform to trait's specification <{}> (missing methods: [{}], invalid signatures for methods: [{}])", trait_name, missing.join(", "), mismatched.join(", ")),
             CheckErrors::ExpectedTraitIdentifier => format!("expecting expression of type trait identifier"),
             CheckErrors::UnexpectedTraitOrFieldReference => format!("unexpected use of trait reference or field"),
             CheckErrors::DefineTraitBadSignature => format!("invalid trait definition"),
@@ -377,7 +422,14 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::BadSyntaxBinding => Some(format!("binding syntax example: ((supply int) (ttl int))")),
             CheckErrors::BadLetSyntax => Some(format!("'let' syntax example: (let ((supply 1000) (ttl 60)) <next-expression>)")),
             CheckErrors::TraitReferenceUnknown(_) => Some(format!("traits should be either defined, with define-trait, or imported, with use-trait.")),
+            CheckErrors::IncorrectCallbackArity(iterable_name, _, _, _) => match iterable_name.as_str() {
+                "map" => Some("'map' applies a one-argument function to each item: (map f list), where (f item) -> result".to_string()),
+                "filter" => Some("'filter' applies a one-argument predicate to each item: (filter f list), where (f item) -> bool".to_string()),
+                "fold" => Some("'fold' applies a two-argument function to each item and the accumulator: (fold f list initial), where (f item accumulator) -> accumulator".to_string()),
+                _ => None
+            },
             CheckErrors::NoSuchBlockInfoProperty(_) => Some(format!("properties available: time, header-hash, burnchain-header-hash, vrf-seed")),
+            CheckErrors::IntUintMismatch(_) => Some("convert the arguments to a common type with to-int or to-uint".to_string()),
             _ => None
         }
     }
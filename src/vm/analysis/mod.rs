@@ -5,12 +5,19 @@ pub mod type_checker;
 pub mod read_only_checker;
 pub mod analysis_db;
 pub mod contract_interface_builder;
+pub mod unreachable_code_checker;
+pub mod division_by_zero_checker;
 
-pub use self::types::{ContractAnalysis, AnalysisPass};
+pub use self::types::{ContractAnalysis, AnalysisPass, AnalysisConfig, PassName};
+pub use self::unreachable_code_checker::check_unreachable_code;
+pub use self::division_by_zero_checker::check_division_by_zero;
 use vm::representations::{SymbolicExpression};
+use vm::functions::NativeFunctions;
 use vm::types::{TypeSignature, QualifiedContractIdentifier};
 use vm::costs::LimitedCostTracker;
-use vm::database::STORE_CONTRACT_SRC_INTERFACE;
+use vm::database::{MemoryBackingStore, STORE_CONTRACT_SRC_INTERFACE};
+use vm::diagnostic::{Diagnostic, DiagnosticSink};
+use vm::ast::build_ast_with_diagnostics;
 
 pub use self::errors::{CheckResult, CheckError, CheckErrors};
 pub use self::analysis_db::{AnalysisDatabase};
@@ -22,14 +29,14 @@ use self::contract_interface_builder::build_contract_interface;
 
 #[cfg(test)]
 pub fn mem_type_check(snippet: &str) -> CheckResult<(Option<TypeSignature>, ContractAnalysis)> {
-    use vm::database::MemoryBackingStore;
     use vm::ast::parse;
     let contract_identifier = QualifiedContractIdentifier::transient();
     let mut contract = parse(&contract_identifier, snippet).unwrap();
     let mut marf = MemoryBackingStore::new();
     let mut analysis_db = marf.as_analysis_db();
     type_check(&QualifiedContractIdentifier::transient(), &mut contract, &mut analysis_db, false)
-        .map(|x| {
+        .map(|mut x| {
+             x.set_contract_size(snippet);
              // return the first type result of the type checker
              let first_type = x.type_map.as_ref().unwrap()
                 .get_type(&x.expressions.last().unwrap()).cloned();
@@ -52,19 +59,23 @@ pub fn run_analysis(contract_identifier: &QualifiedContractIdentifier,
                     analysis_db: &mut AnalysisDatabase, 
                     save_contract: bool,
                     cost_tracker: LimitedCostTracker) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
+    run_analysis_with_config(contract_identifier, expressions, analysis_db, save_contract, cost_tracker, AnalysisConfig::default())
+}
+
+/// Run the analysis passes, enforcing the limits of `config` in addition to the cost limits.
+pub fn run_analysis_with_config(contract_identifier: &QualifiedContractIdentifier,
+                                expressions: &mut [SymbolicExpression],
+                                analysis_db: &mut AnalysisDatabase,
+                                save_contract: bool,
+                                cost_tracker: LimitedCostTracker,
+                                config: AnalysisConfig) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
     let mut contract_analysis = ContractAnalysis::new(contract_identifier.clone(), expressions.to_vec(), cost_tracker);
+    contract_analysis.config = config;
     let result = analysis_db.execute(|db| {
-        ReadOnlyChecker::run_pass(&mut contract_analysis, db)?;
-        TypeChecker::run_pass(&mut contract_analysis, db)?;
-        TraitChecker::run_pass(&mut contract_analysis, db)?;
-        if STORE_CONTRACT_SRC_INTERFACE {
-            let interface = build_contract_interface(&contract_analysis);
-            contract_analysis.contract_interface = Some(interface);
-        }
-        if save_contract {
-            db.insert_contract(&contract_identifier, &contract_analysis)?;
-        }
-        Ok(())
+        contract_analysis.run_costed_pass::<ReadOnlyChecker>(PassName::ReadOnlyChecker, db)?;
+        contract_analysis.run_costed_pass::<TypeChecker>(PassName::TypeChecker, db)?;
+        contract_analysis.run_costed_pass::<TraitChecker>(PassName::TraitChecker, db)?;
+        finish_analysis(contract_identifier, &mut contract_analysis, db, save_contract)
     });
     match result {
         Ok(_) => Ok(contract_analysis),
@@ -72,6 +83,97 @@ pub fn run_analysis(contract_identifier: &QualifiedContractIdentifier,
     }
 }
 
+/// Run the analysis passes without charging for them, the analysis analog of building
+///   the AST with a `()` cost tracker. This is intended for offline tooling such as linters:
+///   the checks performed are the same as `run_analysis`, but there is no budget to exceed.
+///   The analysis' own cost tracker is left uncharged, and no pass costs are recorded.
+pub fn run_analysis_without_costs(contract_identifier: &QualifiedContractIdentifier,
+                                  expressions: &mut [SymbolicExpression],
+                                  analysis_db: &mut AnalysisDatabase,
+                                  save_contract: bool) -> CheckResult<ContractAnalysis> {
+    let mut contract_analysis = ContractAnalysis::new(contract_identifier.clone(), expressions.to_vec(),
+                                                      LimitedCostTracker::new_max_limit());
+    analysis_db.execute(|db| {
+        ReadOnlyChecker::run_pass(&mut contract_analysis, db)?;
+        TypeChecker::run_pass_with_cost_tracker(&mut contract_analysis, db, &mut ())?;
+        TraitChecker::run_pass(&mut contract_analysis, db)?;
+        finish_analysis(contract_identifier, &mut contract_analysis, db, save_contract)
+    })?;
+    Ok(contract_analysis)
+}
+
+fn finish_analysis(contract_identifier: &QualifiedContractIdentifier, contract_analysis: &mut ContractAnalysis,
+                   analysis_db: &mut AnalysisDatabase, save_contract: bool) -> CheckResult<()> {
+    if STORE_CONTRACT_SRC_INTERFACE {
+        let interface = build_contract_interface(contract_analysis);
+        contract_analysis.contract_interface = Some(interface);
+    }
+    if save_contract {
+        analysis_db.insert_contract(contract_identifier, contract_analysis)?;
+    }
+    Ok(())
+}
+
+// The analysis passes may also be run individually, e.g., to only type check a contract
+//   while it is being edited. Each pass runs in its own analysis database transaction,
+//   and passes must be run in the same order as in `run_analysis`:
+//   1. the read-only checker, which rejects writes from read-only contexts. The later
+//      passes do not repeat these checks, so skipping it admits contracts `run_analysis` rejects.
+//   2. the type checker, which records the function, variable and map types on the ContractAnalysis.
+//   3. the trait checker, which checks the implemented traits against the recorded function types,
+//      and so requires the type checker to have run.
+
+pub fn run_read_only_checker(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
+    analysis_db.execute(|db| ReadOnlyChecker::run_pass(contract_analysis, db))
+}
+
+pub fn run_type_checker(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
+    analysis_db.execute(|db| TypeChecker::run_pass(contract_analysis, db))
+}
+
+/// Like `run_type_checker`, but reports every recoverable type error rather than the first.
+pub fn run_type_checker_collecting_errors(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Result<(), Vec<CheckError>> {
+    analysis_db.execute(|db| TypeChecker::run_pass_collecting_errors(contract_analysis, db))
+}
+
+pub fn run_trait_checker(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
+    analysis_db.execute(|db| TraitChecker::run_pass(contract_analysis, db))
+}
+
+/// Parse and analyze a contract in isolation, collecting every diagnostic found along
+///   the way, sorted by source position. The analysis is only returned if the contract
+///   has no problems at all. Because the contract is analyzed against an empty database,
+///   calls to other contracts are reported as unknown contracts.
+pub fn compile_and_diagnose(contract_identifier: &QualifiedContractIdentifier, source_code: &str) -> (Option<ContractAnalysis>, Vec<Diagnostic>) {
+    let mut diagnostics = DiagnosticSink::new();
+    let mut cost_tracker = LimitedCostTracker::new_max_limit();
+    let contract_analysis = build_ast_with_diagnostics(contract_identifier, source_code, &mut cost_tracker, &mut diagnostics)
+        .and_then(|mut contract_ast| {
+            let mut marf = MemoryBackingStore::new();
+            let mut analysis_db = marf.as_analysis_db();
+            let result = run_analysis(contract_identifier, &mut contract_ast.expressions, &mut analysis_db, false, cost_tracker)
+                .map_err(|(e, _)| e)
+                .map(|mut contract_analysis| {
+                    contract_analysis.set_contract_size(source_code);
+                    contract_analysis
+                });
+            diagnostics.capture(result)
+        });
+    if diagnostics.is_empty() {
+        (contract_analysis, vec![])
+    } else {
+        (None, diagnostics.into_sorted())
+    }
+}
+
+// Split an application of a native function into the function and its arguments,
+//   for the advisory checks.
+fn native_application(list: &[SymbolicExpression]) -> Option<(NativeFunctions, &[SymbolicExpression])> {
+    let (function_name, args) = list.split_first()?;
+    let function = NativeFunctions::lookup_by_name(function_name.match_atom()?)?;
+    Some((function, args))
+}
+
 #[cfg(test)]
 mod tests;
 
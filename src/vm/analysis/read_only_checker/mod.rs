@@ -18,7 +18,9 @@ mod tests;
 
 pub struct ReadOnlyChecker <'a, 'b> {
     db: &'a mut AnalysisDatabase<'b>,
-    defined_functions: HashMap<ClarityName, bool>
+    defined_functions: HashMap<ClarityName, bool>,
+    // whether the body of a define-read-only is being checked
+    in_read_only_function: bool
 }
 
 impl <'a, 'b> AnalysisPass for ReadOnlyChecker <'a, 'b> {
@@ -35,7 +37,8 @@ impl <'a, 'b> ReadOnlyChecker <'a, 'b> {
     fn new(db: &'a mut AnalysisDatabase<'b>) -> ReadOnlyChecker<'a, 'b> {
         Self { 
             db, 
-            defined_functions: HashMap::new() 
+            defined_functions: HashMap::new(),
+            in_read_only_function: false
         }
     }
 
@@ -85,7 +88,10 @@ impl <'a, 'b> ReadOnlyChecker <'a, 'b> {
                     self.defined_functions.insert(f_name, is_read_only);
                 },
                 ReadOnlyFunction { signature, body } => {
-                    let (f_name, is_read_only) = self.check_define_function(signature, body)?;
+                    self.in_read_only_function = true;
+                    let result = self.check_define_function(signature, body);
+                    self.in_read_only_function = false;
+                    let (f_name, is_read_only) = result?;
                     if !is_read_only {
                         return Err(CheckErrors::WriteAttemptedInReadOnly.into())
                     } else {
@@ -258,7 +264,16 @@ impl <'a, 'b> ReadOnlyChecker <'a, 'b> {
 
                 let is_function_read_only = match &args[0].expr {
                     SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(ref contract_identifier))) => {
-                        self.db.get_read_only_function_type(&contract_identifier, function_name)?.is_some()
+                        let is_read_only = self.db.get_read_only_function_type(&contract_identifier, function_name)
+                            .map_err(|mut e| { e.set_expression(&args[0]); e })?
+                            .is_some();
+                        if !is_read_only && self.in_read_only_function {
+                            let mut error = CheckError::new(CheckErrors::ContractCallWriteInReadOnly(
+                                contract_identifier.to_string(), function_name.to_string()));
+                            error.set_expression(&args[1]);
+                            return Err(error)
+                        }
+                        is_read_only
                     },
                     SymbolicExpressionType::Atom(_trait_reference) => {
                         // Dynamic dispatch from a readonly-function can only be guaranteed at runtime,
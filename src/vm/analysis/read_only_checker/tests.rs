@@ -169,7 +169,9 @@ fn test_contract_call_read_only_violations() {
     }).unwrap();
 
     let err = db.execute(|db| type_check(&contract_bad_caller_id, &mut bad_caller, db, true)).unwrap_err();
-    assert_eq!(err.err, CheckErrors::WriteAttemptedInReadOnly);
+    assert_eq!(err.err, CheckErrors::ContractCallWriteInReadOnly(contract_1_id.to_string(), "mint".to_string()));
+    assert_eq!(err.diagnostic.spans[0].start_line, 2);
+    assert_eq!(err.diagnostic.spans[0].start_column, 40);
 
     db.execute(|db| type_check(&contract_ok_caller_id, &mut ok_caller, db, false)).unwrap();
 
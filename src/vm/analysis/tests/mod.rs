@@ -1,6 +1,6 @@
 use vm::ast::parse;
 use vm::analysis::{AnalysisDatabase, mem_type_check};
-use vm::analysis::errors::CheckErrors;
+use vm::analysis::errors::{CheckErrors, IntUintMismatch};
 use vm::analysis::{ContractAnalysis, type_check};
 
 mod costs;
@@ -267,3 +267,213 @@ fn test_write_attempt_in_readonly() {
     let err = mem_type_check(snippet).unwrap_err();
     assert!(format!("{}", err.diagnostic).contains("expecting read-only statements, detected a writing operation"));
 }
+
+#[test]
+#[cfg(feature = "developer-mode")]
+fn test_compile_and_diagnose() {
+    use vm::analysis::compile_and_diagnose;
+    use vm::types::QualifiedContractIdentifier;
+
+    let contract = "(define-read-only (bad-sum) (+ 1 u1))
+(define-public (use-trait (t <missing-trait>))
+  (ok true))";
+
+    let (analysis, diagnostics) = compile_and_diagnose(&QualifiedContractIdentifier::transient(), contract);
+    assert!(analysis.is_none());
+    assert_eq!(diagnostics.len(), 2);
+
+    // the type error is found after the parse error, but comes first in the source
+    assert_eq!(diagnostics[0].spans[0].start_line, 1);
+    assert!(diagnostics[0].message.contains("'+' cannot mix signed and unsigned integers (got 'int' and 'uint')"));
+    assert_eq!(diagnostics[1].spans[0].start_line, 2);
+    assert!(diagnostics[1].message.contains("missing-trait"));
+
+    let (analysis, diagnostics) = compile_and_diagnose(&QualifiedContractIdentifier::transient(), "(define-read-only (sum) (+ 1 1))");
+    assert!(analysis.is_some());
+    assert!(diagnostics.is_empty());
+
+    let (analysis, diagnostics) = compile_and_diagnose(&QualifiedContractIdentifier::transient(), "(define-read-only (sum) (+ 1 1)");
+    assert!(analysis.is_none());
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn test_run_passes_individually() {
+    use vm::analysis::{run_read_only_checker, run_type_checker};
+    use vm::types::QualifiedContractIdentifier;
+    use vm::database::MemoryBackingStore;
+    use vm::costs::LimitedCostTracker;
+
+    let contract = "(define-trait getter ((get-value () (response int int))))
+                    (define-data-var value int 0)
+                    (define-read-only (get-value) (ok (var-get value)))
+                    (define-public (set-value (x int)) (begin (var-set value x) (ok x)))";
+    let contract_identifier = QualifiedContractIdentifier::local("passes").unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    let mut contract_analysis = ContractAnalysis::new(contract_identifier.clone(),
+                                                      parse(&contract_identifier, contract).unwrap(),
+                                                      LimitedCostTracker::new_max_limit());
+    run_read_only_checker(&mut contract_analysis, &mut analysis_db).unwrap();
+    run_type_checker(&mut contract_analysis, &mut analysis_db).unwrap();
+
+    let full_analysis = type_check(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                                   &mut analysis_db, false).unwrap();
+    assert_eq!(contract_analysis.public_function_types, full_analysis.public_function_types);
+    assert_eq!(contract_analysis.read_only_function_types, full_analysis.read_only_function_types);
+    assert_eq!(contract_analysis.defined_traits, full_analysis.defined_traits);
+}
+
+#[test]
+fn test_type_checker_collecting_errors() {
+    use vm::analysis::run_type_checker_collecting_errors;
+    use vm::types::{QualifiedContractIdentifier, TypeSignature};
+    use vm::database::MemoryBackingStore;
+    use vm::costs::LimitedCostTracker;
+
+    let contract = "(define-read-only (first) (ok (+ 1 true)))
+                    (define-read-only (second (x uint)) (* (- x 1) (+ u1 u2)))
+                    (define-read-only (fine) (ok u1))";
+    let contract_identifier = QualifiedContractIdentifier::local("errors").unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    let mut contract_analysis = ContractAnalysis::new(contract_identifier.clone(),
+                                                      parse(&contract_identifier, contract).unwrap(),
+                                                      LimitedCostTracker::new_max_limit());
+    let errors = run_type_checker_collecting_errors(&mut contract_analysis, &mut analysis_db).unwrap_err();
+
+    // the enclosing (ok ..) and (* ..) expressions are not reported again.
+    let errors: Vec<_> = errors.into_iter().map(|e| (e.err, e.diagnostic.spans[0].start_line)).collect();
+    assert_eq!(errors, vec![
+        (CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::BoolType), 1),
+        (CheckErrors::IntUintMismatch(Box::new(IntUintMismatch { op: "-".to_string(), expected: TypeSignature::UIntType, found: TypeSignature::IntType })), 2)]);
+
+    let contract = "(define-read-only (fine) (ok u1))";
+    let mut contract_analysis = ContractAnalysis::new(contract_identifier.clone(),
+                                                      parse(&contract_identifier, contract).unwrap(),
+                                                      LimitedCostTracker::new_max_limit());
+    run_type_checker_collecting_errors(&mut contract_analysis, &mut analysis_db).unwrap();
+    assert_eq!(contract_analysis.read_only_function_types.len(), 1);
+}
+
+#[test]
+fn test_max_tuple_arity() {
+    use vm::analysis::{run_analysis_with_config, AnalysisConfig};
+    use vm::types::QualifiedContractIdentifier;
+    use vm::database::MemoryBackingStore;
+    use vm::costs::LimitedCostTracker;
+
+    let contract_identifier = QualifiedContractIdentifier::local("tuples").unwrap();
+    let config = AnalysisConfig { max_tuple_arity: 2 };
+
+    let at_limit = [
+        "(tuple (a 1) (b 2))",
+        "(define-map pairs ((a int) (b int)) ((c int) (d int)))",
+        "(define-read-only (first (pair (tuple (a int) (b int)))) (get a pair))",
+    ];
+    let too_wide = [
+        "(tuple (a 1) (b 2) (c 3))",
+        "(define-map triples ((a int) (b int) (c int)) ((d int)))",
+        "(define-data-var triples (list 2 (tuple (a int) (b int) (c int))) (list))",
+        "(define-read-only (first (pair (optional (tuple (a int) (b int) (c int))))) pair)",
+    ];
+
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    for contract in at_limit.iter() {
+        run_analysis_with_config(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                                 &mut analysis_db, false, LimitedCostTracker::new_max_limit(), config.clone())
+            .unwrap();
+    }
+    for contract in too_wide.iter() {
+        let (err, _) = run_analysis_with_config(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                                                &mut analysis_db, false, LimitedCostTracker::new_max_limit(), config.clone())
+            .unwrap_err();
+        assert_eq!(err.err, CheckErrors::TupleTooLarge { arity: 3, limit: 2 });
+        // the default configuration accepts the contract
+        type_check(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                   &mut analysis_db, false).unwrap();
+    }
+}
+
+#[test]
+fn test_run_analysis_without_costs() {
+    use vm::analysis::{run_analysis, run_analysis_without_costs};
+    use vm::types::QualifiedContractIdentifier;
+    use vm::database::MemoryBackingStore;
+    use vm::costs::{LimitedCostTracker, ExecutionCost};
+
+    let contracts = [
+        "(define-data-var value int 0)
+         (define-read-only (get-value) (ok (var-get value)))
+         (define-public (set-value (x int)) (begin (var-set value x) (ok x)))",
+        "(define-map scores ((id int)) ((score uint)))
+         (define-read-only (get-score (id int)) (map-get? scores ((id id))))",
+        "(define-trait getter ((get-value () (response int int))))",
+        "(define-read-only (sum) (+ 1 true))",
+        "(define-data-var value int 0)
+         (define-read-only (set-value (x int)) (var-set value x))",
+        "(define-public (no-response) (+ 1 2))",
+    ];
+    let contract_identifier = QualifiedContractIdentifier::local("lint").unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    for contract in contracts.iter() {
+        let costed = type_check(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                                &mut analysis_db, false);
+        let free = run_analysis_without_costs(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                                              &mut analysis_db, false);
+        match (costed, free) {
+            (Ok(costed), Ok(free)) => {
+                assert_eq!(costed.public_function_types, free.public_function_types);
+                assert_eq!(costed.read_only_function_types, free.read_only_function_types);
+            },
+            (Err(costed), Err(free)) => assert_eq!(costed.err, free.err),
+            _ => panic!("Analysis with and without costs disagree for {}", contract)
+        }
+    }
+
+    // there is no budget to exceed
+    let budget = LimitedCostTracker::new(ExecutionCost::runtime(1));
+    assert!(run_analysis(&contract_identifier, &mut parse(&contract_identifier, contracts[0]).unwrap(),
+                         &mut analysis_db, false, budget).is_err());
+    assert!(run_analysis_without_costs(&contract_identifier, &mut parse(&contract_identifier, contracts[0]).unwrap(),
+                                       &mut analysis_db, false).is_ok());
+}
+
+#[test]
+fn test_analysis_pass_costs() {
+    use vm::analysis::{run_analysis, PassName};
+    use vm::types::QualifiedContractIdentifier;
+    use vm::database::MemoryBackingStore;
+    use vm::costs::{ExecutionCost, LimitedCostTracker};
+
+    let contract = "(define-map balances ((owner principal)) ((amount uint)))
+                    (define-read-only (balance-of (owner principal))
+                      (default-to u0 (get amount (map-get? balances (tuple (owner owner))))))
+                    (define-public (mint (amount uint))
+                      (begin (map-set balances (tuple (owner tx-sender)) (tuple (amount (+ amount (balance-of tx-sender)))))
+                             (ok amount)))";
+    let contract_identifier = QualifiedContractIdentifier::local("costs").unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    let mut contract_analysis = run_analysis(&contract_identifier, &mut parse(&contract_identifier, contract).unwrap(),
+                                             &mut analysis_db, false, LimitedCostTracker::new_max_limit()).unwrap();
+
+    let pass_costs = contract_analysis.pass_costs();
+    let pass_names: Vec<_> = pass_costs.iter().map(|(name, _)| *name).collect();
+    assert_eq!(pass_names, vec![PassName::ReadOnlyChecker, PassName::TypeChecker, PassName::TraitChecker]);
+
+    let mut sum = ExecutionCost::zero();
+    for (_, cost) in pass_costs.iter() {
+        sum.add(cost).unwrap();
+    }
+    assert_eq!(sum, contract_analysis.take_contract_cost_tracker().get_total());
+
+    let type_checker_cost = &pass_costs[1].1;
+    assert!(type_checker_cost.runtime > 0);
+}
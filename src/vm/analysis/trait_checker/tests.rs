@@ -5,7 +5,7 @@ use vm::analysis::{AnalysisDatabase, contract_interface_builder::build_contract_
 use vm::database::MemoryBackingStore;
 use vm::analysis::mem_type_check;
 use vm::analysis::type_check;
-use vm::types::{TypeSignature, QualifiedContractIdentifier};
+use vm::types::{TypeSignature, QualifiedContractIdentifier, TraitIdentifier};
 
 #[test]
 fn test_dynamic_dispatch_by_defining_trait() {
@@ -818,3 +818,69 @@ fn test_good_call_2_with_trait() {
     }).unwrap();
 }
 
+
+#[test]
+fn test_defined_trait_signatures() {
+    let contract_defining_traits =
+        "(define-trait trait-1 (
+            (get-1 (uint) (response uint uint))))
+        (define-trait trait-2 (
+            (get-2 (int <trait-1>) (response bool int))
+            (get-3 () (response uint uint))))";
+
+    let (_, analysis) = mem_type_check(contract_defining_traits).unwrap();
+    let signatures = analysis.get_defined_trait_signatures();
+
+    let trait_1_id = TraitIdentifier {
+        name: "trait-1".into(),
+        contract_identifier: QualifiedContractIdentifier::transient()
+    };
+
+    assert_eq!(signatures.len(), 2);
+    assert_eq!(signatures[1],
+               ("trait-2".into(), vec![
+                   ("get-2".into(),
+                    vec![TypeSignature::IntType, TypeSignature::TraitReferenceType(trait_1_id)],
+                    TypeSignature::new_response(TypeSignature::BoolType, TypeSignature::IntType).unwrap()),
+                   ("get-3".into(),
+                    vec![],
+                    TypeSignature::new_response(TypeSignature::UIntType, TypeSignature::UIntType).unwrap())]));
+}
+
+#[test]
+fn test_dynamic_dispatch_imported_trait_shadowed_by_local_trait() {
+    let defining_contract_src =
+        "(define-trait token (
+            (get-balance () (response uint uint))))";
+    let dispatching_contract_src =
+        "(use-trait imported-token .defining-contract.token)
+        (define-trait token (
+            (transfer (uint) (response bool uint))))
+        (define-public (balance (contract <imported-token>))
+            (contract-call? contract get-balance))
+        (define-public (transfer (contract <token>))
+            (contract-call? contract transfer u1))";
+    let bad_dispatching_contract_src =
+        "(use-trait imported-token .defining-contract.token)
+        (define-trait token (
+            (transfer (uint) (response bool uint))))
+        (define-public (transfer (contract <imported-token>))
+            (contract-call? contract transfer u1))";
+
+    let defining_contract_id = QualifiedContractIdentifier::local("defining-contract").unwrap();
+    let dispatching_contract_id = QualifiedContractIdentifier::local("dispatching-contract").unwrap();
+    let bad_dispatching_contract_id = QualifiedContractIdentifier::local("bad-dispatching-contract").unwrap();
+
+    let mut defining_contract = parse(&defining_contract_id, defining_contract_src).unwrap();
+    let mut dispatching_contract = parse(&dispatching_contract_id, dispatching_contract_src).unwrap();
+    let mut bad_dispatching_contract = parse(&bad_dispatching_contract_id, bad_dispatching_contract_src).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+
+    let err = db.execute(|db| {
+        type_check(&defining_contract_id, &mut defining_contract, db, true).unwrap();
+        type_check(&dispatching_contract_id, &mut dispatching_contract, db, true).unwrap();
+        type_check(&bad_dispatching_contract_id, &mut bad_dispatching_contract, db, true)
+    }).unwrap_err();
+    assert_eq!(err.err, CheckErrors::TraitMethodUnknown("token".to_string(), "transfer".to_string()));
+}
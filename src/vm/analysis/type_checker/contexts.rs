@@ -30,6 +30,9 @@ pub struct ContractContext {
     fungible_tokens: HashSet<ClarityName>,
     non_fungible_tokens: HashMap<ClarityName, TypeSignature>,
     traits: HashMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
+    // traits imported with use-trait, by their full identifier: a local trait
+    //   may share the name of an imported one.
+    imported_traits: HashMap<TraitIdentifier, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: HashSet<TraitIdentifier>,
 }
 
@@ -63,6 +66,7 @@ impl ContractContext {
             fungible_tokens: HashSet::new(),
             non_fungible_tokens: HashMap::new(),
             traits: HashMap::new(),
+            imported_traits: HashMap::new(),
             implemented_traits: HashSet::new(),
         }
     }
@@ -157,6 +161,17 @@ impl ContractContext {
         self.traits.get(trait_name)
     }
 
+    pub fn add_imported_trait(&mut self, trait_identifier: TraitIdentifier, trait_signature: BTreeMap<ClarityName, FunctionSignature>) {
+        self.imported_traits.insert(trait_identifier, trait_signature);
+    }
+
+    /// Look up the signature of a trait by its identifier: either a trait imported
+    ///   with use-trait, or one defined by this contract.
+    pub fn get_trait_by_identifier(&self, trait_identifier: &TraitIdentifier) -> Option<&BTreeMap<ClarityName, FunctionSignature>> {
+        self.imported_traits.get(trait_identifier)
+            .or_else(|| self.traits.get(&trait_identifier.name))
+    }
+
     pub fn get_map_type(&self, map_name: &str) -> Option<&(TypeSignature, TypeSignature)> {
         self.map_types.get(map_name)
     }
@@ -16,13 +16,13 @@ use vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker, CostErrors,
                 cost_functions, analysis_typecheck_cost, CostOverflowingMath};
 
 use super::AnalysisDatabase;
-pub use super::types::{ContractAnalysis, AnalysisPass};
+pub use super::types::{ContractAnalysis, AnalysisPass, AnalysisConfig};
 
 use self::contexts::{TypeMap, TypingContext, ContractContext};
 
 pub use self::natives::{TypedNativeFunction, SimpleNativeFunction};
 
-pub use super::errors::{CheckResult, CheckError, CheckErrors, check_argument_count,
+pub use super::errors::{CheckResult, CheckError, CheckErrors, IntUintMismatch, check_argument_count,
                         check_arguments_at_least};
 
 
@@ -45,15 +45,52 @@ Is illegally typed in our language.
 
 */
 
-pub struct TypeChecker <'a, 'b> {
+// The name bound by a top-level definition, if any. Malformed definitions are
+//   left for the type checker to report.
+fn defined_name(expression: &SymbolicExpression) -> Option<&ClarityName> {
+    match DefineFunctionsParsed::try_parse(expression).ok()?? {
+        DefineFunctionsParsed::Constant { name, .. } |
+        DefineFunctionsParsed::NonFungibleToken { name, .. } |
+        DefineFunctionsParsed::BoundedFungibleToken { name, .. } |
+        DefineFunctionsParsed::UnboundedFungibleToken { name } |
+        DefineFunctionsParsed::Map { name, .. } |
+        DefineFunctionsParsed::PersistedVariable { name, .. } |
+        DefineFunctionsParsed::Trait { name, .. } => Some(name),
+        DefineFunctionsParsed::PrivateFunction { signature, .. } |
+        DefineFunctionsParsed::ReadOnlyFunction { signature, .. } |
+        DefineFunctionsParsed::PublicFunction { signature, .. } => signature.first()?.match_atom(),
+        DefineFunctionsParsed::UseTrait { .. } | DefineFunctionsParsed::ImplTrait { .. } => None
+    }
+}
+
+// Reject contracts which define the same top-level name twice, before any definition is checked.
+//   The error spans point at the second definition, followed by the first.
+fn check_duplicate_definitions(expressions: &[SymbolicExpression]) -> CheckResult<()> {
+    let mut definitions = HashMap::new();
+    for expression in expressions.iter() {
+        if let Some(name) = defined_name(expression) {
+            if let Some(previous) = definitions.insert(name, expression) {
+                let mut error = CheckError::new(CheckErrors::NameAlreadyUsed(name.to_string()));
+                error.set_expressions(vec![expression.clone(), previous.clone()]);
+                return Err(error)
+            }
+        }
+    }
+    Ok(())
+}
+
+pub struct TypeChecker <'a, 'b, T = LimitedCostTracker> {
     pub type_map: TypeMap,
     contract_context: ContractContext,
     function_return_tracker: Option<Option<TypeSignature>>,
     db: &'a mut AnalysisDatabase<'b>,
-    pub cost_track: LimitedCostTracker,
+    pub cost_track: T,
+    max_tuple_arity: u64,
+    collect_errors: bool,
+    collected_errors: Vec<CheckError>,
 }
 
-impl CostTracker for TypeChecker<'_, '_> {
+impl <T: CostTracker> CostTracker for TypeChecker<'_, '_, T> {
     fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
         self.cost_track.add_cost(cost)
     }
@@ -71,7 +108,7 @@ impl CostTracker for TypeChecker<'_, '_> {
 impl AnalysisPass for TypeChecker <'_, '_> {
     fn run_pass(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
         let cost_track = contract_analysis.take_contract_cost_tracker();
-        let mut command = TypeChecker::new(analysis_db, cost_track);
+        let mut command = TypeChecker::new(analysis_db, cost_track, &contract_analysis.config);
         // run the analysis, and replace the cost tracker whether or not the
         //   analysis succeeded.
         match command.run(contract_analysis) {
@@ -89,9 +126,56 @@ impl AnalysisPass for TypeChecker <'_, '_> {
     }
 }
 
+impl TypeChecker <'_, '_> {
+    /// Type check the contract, continuing past recoverable type errors to report as many
+    ///   errors as feasible in one pass. Each failed subexpression is recorded once, and is
+    ///   given the NoType sentinel so that its siblings are still checked; errors which only
+    ///   follow from an already reported error are not repeated. Unrecoverable errors abort
+    ///   the pass, and are returned after the errors collected so far.
+    pub fn run_pass_collecting_errors(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Result<(), Vec<CheckError>> {
+        let cost_track = contract_analysis.take_contract_cost_tracker();
+        let mut command = TypeChecker::new(analysis_db, cost_track, &contract_analysis.config);
+        command.collect_errors = true;
+
+        let result = command.run(contract_analysis);
+        let mut errors = command.collected_errors.drain(..).collect::<Vec<_>>();
+        if let Err(error) = result {
+            errors.push(error);
+        }
+
+        if errors.is_empty() {
+            let cost_track = command.into_contract_analysis(contract_analysis);
+            contract_analysis.replace_contract_cost_tracker(cost_track);
+            Ok(())
+        } else {
+            let TypeChecker { cost_track, .. } = command;
+            contract_analysis.replace_contract_cost_tracker(cost_track);
+            Err(errors)
+        }
+    }
+}
+
+impl <'a, 'b, T: CostTracker> TypeChecker <'a, 'b, T> {
+    /// Type check the contract, charging `cost_track` rather than the analysis' own cost
+    ///   tracker. Like `build_ast`, this accepts `&mut ()` to type check without costs.
+    pub fn run_pass_with_cost_tracker(contract_analysis: &mut ContractAnalysis, analysis_db: &'a mut AnalysisDatabase<'b>, cost_track: T) -> CheckResult<()> {
+        let mut command = TypeChecker::new(analysis_db, cost_track, &contract_analysis.config);
+        command.run(contract_analysis)?;
+        command.into_contract_analysis(contract_analysis);
+        Ok(())
+    }
+}
+
 pub type TypeResult = CheckResult<TypeSignature>;
 
 impl FunctionType {
+    pub fn is_arithmetic(&self) -> bool {
+        match self {
+            FunctionType::ArithmeticVariadic | FunctionType::ArithmeticBinary | FunctionType::ArithmeticComparison => true,
+            _ => false
+        }
+    }
+
     pub fn check_args<T: CostTracker>(&self, accounting: &mut T, args: &[TypeSignature]) -> CheckResult<TypeSignature> {
         match self {
             FunctionType::Variadic(expected_type, return_type) => {
@@ -170,6 +254,13 @@ impl FunctionType {
     }
 }
 
+fn is_int_uint_mix(left: &TypeSignature, right: &TypeSignature) -> bool {
+    match (left, right) {
+        (TypeSignature::IntType, TypeSignature::UIntType) | (TypeSignature::UIntType, TypeSignature::IntType) => true,
+        _ => false
+    }
+}
+
 fn trait_type_size(trait_sig: &BTreeMap<ClarityName, FunctionSignature>) -> CheckResult<u64> {
     let mut total_size = 0;
     for (_func_name, value) in trait_sig.iter() {
@@ -201,17 +292,60 @@ pub fn no_type() -> TypeSignature {
     TypeSignature::NoType
 }
 
-impl <'a, 'b> TypeChecker <'a, 'b> {
-    fn new(db: &'a mut AnalysisDatabase<'b>, cost_track: LimitedCostTracker) -> TypeChecker<'a, 'b> {
+impl <'a, 'b, T: CostTracker> TypeChecker <'a, 'b, T> {
+    fn new(db: &'a mut AnalysisDatabase<'b>, cost_track: T, config: &AnalysisConfig) -> TypeChecker<'a, 'b, T> {
         Self {
             db, cost_track,
+            max_tuple_arity: config.max_tuple_arity,
             contract_context: ContractContext::new(),
             function_return_tracker: None,
             type_map: TypeMap::new(),
+            collect_errors: false,
+            collected_errors: Vec::new(),
         }
     }
 
-    fn into_contract_analysis(self, contract_analysis: &mut ContractAnalysis) -> LimitedCostTracker {
+    pub fn check_tuple_arity(&self, arity: u64) -> CheckResult<()> {
+        if arity > self.max_tuple_arity {
+            Err(CheckErrors::TupleTooLarge { arity, limit: self.max_tuple_arity }.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Check the arity of every tuple type within a declared type.
+    fn check_declared_type(&self, type_sig: &TypeSignature) -> CheckResult<()> {
+        match type_sig {
+            TypeSignature::TupleType(tuple_sig) => {
+                self.check_tuple_arity(tuple_sig.len())?;
+                for field_type in tuple_sig.get_type_map().values() {
+                    self.check_declared_type(field_type)?;
+                }
+                Ok(())
+            },
+            TypeSignature::ListType(list_data) => self.check_declared_type(list_data.get_list_item_type()),
+            TypeSignature::OptionalType(inner_type) => self.check_declared_type(inner_type),
+            TypeSignature::ResponseType(inner_types) => {
+                self.check_declared_type(&inner_types.0)?;
+                self.check_declared_type(&inner_types.1)
+            },
+            _ => Ok(())
+        }
+    }
+
+    // When collecting errors, record a recoverable error unless it follows from an error
+    //   reported since `errors_before`. Otherwise, the error is returned.
+    fn recover_error(&mut self, error: CheckError, errors_before: usize) -> CheckResult<()> {
+        if !self.collect_errors || !error.err.is_recoverable() {
+            return Err(error)
+        }
+        if self.collected_errors.len() == errors_before {
+            self.collected_errors.push(error);
+        }
+        Ok(())
+    }
+
+    fn into_contract_analysis(self, contract_analysis: &mut ContractAnalysis) -> T {
         self.contract_context.into_contract_analysis(contract_analysis);
         contract_analysis.type_map = Some(self.type_map);
         self.cost_track
@@ -242,6 +376,8 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     }
 
     pub fn run(&mut self, contract_analysis: &mut ContractAnalysis) -> CheckResult<()> {
+        check_duplicate_definitions(&contract_analysis.expressions)?;
+
         // charge for the eventual storage cost of the analysis --
         //  it is linear in the size of the AST.
         let mut size: u64 = 0;
@@ -262,13 +398,20 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
         let mut local_context = TypingContext::new();
 
         for exp in contract_analysis.expressions.iter() {
+            let errors_before = self.collected_errors.len();
             let mut result_res = self.try_type_check_define(&exp, &mut local_context);
             if let Err(ref mut error) = result_res {
                 if !error.has_expression() {
                     error.set_expression(&exp);
                 }
             }
-            let result = result_res?;
+            let result = match result_res {
+                Ok(result) => result,
+                Err(error) => {
+                    self.recover_error(error, errors_before)?;
+                    continue
+                }
+            };
             if result.is_none() {
                 // was _not_ a define statement, so handle like a normal statement.
                 self.type_check(&exp, &local_context)?;
@@ -312,6 +455,7 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     pub fn type_check(&mut self, expr: &SymbolicExpression, context: &TypingContext) -> TypeResult {
         runtime_cost!(cost_functions::ANALYSIS_VISIT, self, 1)?;
 
+        let errors_before = self.collected_errors.len();
         let mut result = self.inner_type_check(expr, context);
 
         if let Err(ref mut error) = result {
@@ -320,7 +464,14 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
             }
         }
 
-        result
+        match result {
+            Err(error) if self.collect_errors => {
+                self.recover_error(error, errors_before)?;
+                self.type_map.set_type(expr, no_type())?;
+                Ok(no_type())
+            },
+            result => result
+        }
     }
 
     fn type_check_all(&mut self, args: &[SymbolicExpression], context: &TypingContext) -> CheckResult<Vec<TypeSignature>> {
@@ -338,6 +489,21 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
         func_type.check_args(self, &typed_args)
     }
 
+    /// Like `type_check_function_type`, but for the native `function_name`: arithmetic
+    ///   arguments mixing int and uint fail with an IntUintMismatch rather than a TypeError.
+    fn type_check_native_function_type(&mut self, function_name: &str, func_type: &FunctionType,
+                                       args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+        let typed_args = self.type_check_all(args, context)?;
+        func_type.check_args(self, &typed_args)
+            .map_err(|e| match e.err {
+                CheckErrors::TypeError(ref expected, ref found) if func_type.is_arithmetic() && is_int_uint_mix(expected, found) => {
+                    CheckError::new(CheckErrors::IntUintMismatch(Box::new(IntUintMismatch {
+                        op: function_name.to_string(), expected: expected.clone(), found: found.clone() })))
+                },
+                _ => e
+            })
+    }
+
     fn get_function_type(&self, function_name: &str) -> Option<FunctionType> {
         self.contract_context.get_function_type(function_name)
             .cloned()
@@ -360,6 +526,7 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
         let mut function_context = context.extend()?;
         for (arg_name, arg_type) in args.iter() {
             self.contract_context.check_name_used(arg_name)?;
+            self.check_declared_type(arg_type)?;
             
             match arg_type {
                 TypeSignature::TraitReferenceType(trait_id) => {
@@ -412,6 +579,8 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
         let value_type = TypeSignature::from(
             TupleTypeSignature::parse_name_type_pair_list::<()>(value_type, &mut ())
                 .map_err(|_| { CheckErrors::BadMapTypeDefinition })?);
+        self.check_declared_type(&key_type)?;
+        self.check_declared_type(&value_type)?;
 
         Ok((map_name.clone(), (key_type, value_type)))
     }
@@ -420,7 +589,7 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     fn try_native_function_check(&mut self, function: &str, args: &[SymbolicExpression], context: &TypingContext) -> Option<TypeResult> {
         if let Some(ref native_function) = NativeFunctions::lookup_by_name(function) {
             let typed_function = TypedNativeFunction::type_native_function(native_function);
-            Some(typed_function.type_check_appliction(self, args, context))
+            Some(typed_function.type_check_appliction(self, function, args, context))
         } else {
             None
         }
@@ -492,6 +661,7 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     fn type_check_define_persisted_variable(&mut self, var_name: &ClarityName, var_type: &SymbolicExpression, initial: &SymbolicExpression, context: &mut TypingContext) -> CheckResult<(ClarityName, TypeSignature)> {
         let expected_type = TypeSignature::parse_type_repr::<()>(var_type, &mut ())
             .map_err(|_e| CheckErrors::DefineVariableBadSignature)?;
+        self.check_declared_type(&expected_type)?;
 
         self.type_check_expects(initial, context, &expected_type)?;
 
@@ -509,6 +679,7 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     fn type_check_define_nft(&mut self, asset_name: &ClarityName, nft_type: &SymbolicExpression, _context: &mut TypingContext) -> CheckResult<(ClarityName, TypeSignature)> {
         let asset_type = TypeSignature::parse_type_repr::<()>(&nft_type, &mut ())
             .or_else(|_| Err(CheckErrors::DefineNFTBadSignature))?;
+        self.check_declared_type(&asset_type)?;
 
         Ok((asset_name.clone(), asset_type))
     }
@@ -516,6 +687,12 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     fn type_check_define_trait(&mut self, trait_name: &ClarityName, function_types: &[SymbolicExpression], _context: &mut TypingContext) -> CheckResult<(ClarityName, BTreeMap<ClarityName, FunctionSignature>)> {
         
         let trait_signature = TypeSignature::parse_trait_type_repr(&function_types, &mut ())?;
+        for function_signature in trait_signature.values() {
+            for arg_type in function_signature.args.iter() {
+                self.check_declared_type(arg_type)?;
+            }
+            self.check_declared_type(&function_signature.returns)?;
+        }
 
         Ok((trait_name.clone(), trait_signature))
     } 
@@ -590,6 +767,7 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
                             let type_size = trait_type_size(&trait_sig)?;
                             runtime_cost!(cost_functions::ANALYSIS_USE_TRAIT_ENTRY, self, type_size)?;
                             runtime_cost!(cost_functions::ANALYSIS_BIND_NAME, self, type_size)?;
+                            self.contract_context.add_imported_trait(trait_identifier.clone(), trait_sig.clone());
                             self.contract_context.add_trait(trait_identifier.name.clone(), trait_sig)?
                         },
                         None => {
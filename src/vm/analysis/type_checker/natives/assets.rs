@@ -2,9 +2,9 @@ use vm::representations::{SymbolicExpression};
 use vm::types::{TypeSignature, TupleTypeSignature, BlockInfoProperty, MAX_VALUE_SIZE};
 use super::{TypeChecker, TypingContext, TypeResult, FunctionType, no_type}; 
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult, check_argument_count};
-use vm::costs::{cost_functions};
+use vm::costs::{cost_functions, CostTracker};
 
-pub fn check_special_get_owner(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_get_owner<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
 
     let asset_name = args[0].match_atom()
@@ -22,7 +22,7 @@ pub fn check_special_get_owner(checker: &mut TypeChecker, args: &[SymbolicExpres
         Box::new(TypeSignature::PrincipalType)).into())
 }
 
-pub fn check_special_get_balance(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_get_balance<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
 
     let asset_name = args[0].match_atom()
@@ -40,7 +40,7 @@ pub fn check_special_get_balance(checker: &mut TypeChecker, args: &[SymbolicExpr
     Ok(TypeSignature::UIntType)
 }
 
-pub fn check_special_mint_asset(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_mint_asset<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(3, args)?;
 
     let asset_name = args[0].match_atom()
@@ -61,7 +61,7 @@ pub fn check_special_mint_asset(checker: &mut TypeChecker, args: &[SymbolicExpre
                   TypeSignature::UIntType))).into())
 }
 
-pub fn check_special_mint_token(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_mint_token<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(3, args)?;
 
     let asset_name = args[0].match_atom()
@@ -84,7 +84,7 @@ pub fn check_special_mint_token(checker: &mut TypeChecker, args: &[SymbolicExpre
                   TypeSignature::UIntType))).into())
 }
 
-pub fn check_special_transfer_asset(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_transfer_asset<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(4, args)?;
 
     let token_name = args[0].match_atom()
@@ -106,7 +106,7 @@ pub fn check_special_transfer_asset(checker: &mut TypeChecker, args: &[SymbolicE
                   TypeSignature::UIntType))).into())
 }
 
-pub fn check_special_transfer_token(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_transfer_token<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(4, args)?;
 
     let token_name = args[0].match_atom()
@@ -7,15 +7,15 @@ use std::convert::TryFrom;
 use std::convert::TryInto;
 
 use vm::analysis::type_checker::{
-    TypeResult, TypingContext, CheckResult, check_argument_count, CheckErrors, no_type, TypeChecker};
+    TypeResult, TypingContext, CheckResult, check_argument_count, CheckError, CheckErrors, no_type, TypeChecker};
 use super::{TypedNativeFunction, SimpleNativeFunction};
 
-use vm::costs::{cost_functions, analysis_typecheck_cost};
+use vm::costs::{cost_functions, analysis_typecheck_cost, CostTracker};
 
-fn get_simple_native_or_user_define(function_name: &str, checker: &mut TypeChecker) -> CheckResult<FunctionType> {
+fn get_simple_native_or_user_define<T: CostTracker>(function_name: &str, checker: &mut TypeChecker<T>) -> CheckResult<FunctionType> {
     runtime_cost!(cost_functions::ANALYSIS_LOOKUP_FUNCTION, checker, 1)?;
     if let Some(ref native_function) = NativeFunctions::lookup_by_name(function_name) {
-        if let TypedNativeFunction::Simple(SimpleNativeFunction(function_type)) = TypedNativeFunction::type_native_function(native_function) {
+        if let TypedNativeFunction::Simple(SimpleNativeFunction(function_type)) = TypedNativeFunction::<T>::type_native_function(native_function) {
             Ok(function_type)
         } else {
             Err(CheckErrors::IllegalOrUnknownFunctionApplication(function_name.to_string()).into())
@@ -26,7 +26,27 @@ fn get_simple_native_or_user_define(function_name: &str, checker: &mut TypeCheck
     }
 }
 
-pub fn check_special_map(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+// Detects callbacks whose arity cannot match the arity expected by a higher-order
+//   function (e.g., `(map binary-fn a-list)`), so that the user gets a targeted error
+//   rather than a generic argument count error, pointing at the callback.
+fn check_callback_arity(iterable_name: &str, callback: &SymbolicExpression, function_name: &str, function_type: &FunctionType, expected: usize) -> CheckResult<()> {
+    let found = match function_type {
+        FunctionType::Fixed(function) => function.args.len(),
+        FunctionType::UnionArgs(..) => 1,
+        FunctionType::ArithmeticBinary | FunctionType::ArithmeticComparison => 2,
+        _ => return Ok(())
+    };
+    if found != expected {
+        let mut error = CheckError::new(
+            CheckErrors::IncorrectCallbackArity(iterable_name.to_string(), function_name.to_string(), expected, found));
+        error.set_expression(callback);
+        Err(error)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn check_special_map<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let function_name = args[0].match_atom()
@@ -34,6 +54,7 @@ pub fn check_special_map(checker: &mut TypeChecker, args: &[SymbolicExpression],
     // we will only lookup native or defined functions here.
     //   you _cannot_ map a special function.
     let function_type = get_simple_native_or_user_define(function_name, checker)?;
+    check_callback_arity("map", &args[0], function_name, &function_type, 1)?;
 
     runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
     let argument_type = checker.type_check(&args[1], context)?;
@@ -54,7 +75,7 @@ pub fn check_special_map(checker: &mut TypeChecker, args: &[SymbolicExpression],
     }
 }
 
-pub fn check_special_filter(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_filter<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let function_name = args[0].match_atom()
@@ -62,6 +83,7 @@ pub fn check_special_filter(checker: &mut TypeChecker, args: &[SymbolicExpressio
     // we will only lookup native or defined functions here.
     //   you _cannot_ map a special function.
     let function_type = get_simple_native_or_user_define(function_name, checker)?;
+    check_callback_arity("filter", &args[0], function_name, &function_type, 1)?;
 
     runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
     let argument_type = checker.type_check(&args[1], context)?;
@@ -83,7 +105,7 @@ pub fn check_special_filter(checker: &mut TypeChecker, args: &[SymbolicExpressio
     Ok(argument_type)
 }
 
-pub fn check_special_fold(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_fold<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(3, args)?;
     
     let function_name = args[0].match_atom()
@@ -91,6 +113,7 @@ pub fn check_special_fold(checker: &mut TypeChecker, args: &[SymbolicExpression]
     // we will only lookup native or defined functions here.
     //   you _cannot_ fold a special function.
     let function_type = get_simple_native_or_user_define(function_name, checker)?;
+    check_callback_arity("fold", &args[0], function_name, &function_type, 2)?;
     
     runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
     let argument_type = checker.type_check(&args[1], context)?;
@@ -116,7 +139,7 @@ pub fn check_special_fold(checker: &mut TypeChecker, args: &[SymbolicExpression]
     Ok(return_type)
 }
 
-pub fn check_special_concat(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_concat<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let lhs_type = checker.type_check(&args[0], context)?;
@@ -155,7 +178,7 @@ pub fn check_special_concat(checker: &mut TypeChecker, args: &[SymbolicExpressio
     }
 }
 
-pub fn check_special_append(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_append<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
 
     runtime_cost!(cost_functions::ANALYSIS_ITERABLE_FUNC, checker, 1)?;
@@ -178,7 +201,7 @@ pub fn check_special_append(checker: &mut TypeChecker, args: &[SymbolicExpressio
     }
 }
 
-pub fn check_special_as_max_len(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_as_max_len<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
 
     let expected_len = match args[1].expr {
@@ -210,7 +233,7 @@ pub fn check_special_as_max_len(checker: &mut TypeChecker, args: &[SymbolicExpre
     }
 }
 
-pub fn check_special_len(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_len<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
 
     let collection_type = checker.type_check(&args[0], context)?;
@@ -9,9 +9,9 @@ use vm::analysis::type_checker::{TypeResult, TypingContext,
                                  check_arguments_at_least,
                                  CheckError, CheckErrors, no_type, TypeChecker};
 
-use vm::costs::{cost_functions, analysis_typecheck_cost};
+use vm::costs::{cost_functions, analysis_typecheck_cost, CostTracker};
 
-fn check_and_type_map_arg_tuple(checker: &mut TypeChecker, expr: &SymbolicExpression, context: &TypingContext) -> TypeResult {
+fn check_and_type_map_arg_tuple<T: CostTracker>(checker: &mut TypeChecker<T>, expr: &SymbolicExpression, context: &TypingContext) -> TypeResult {
     match tuples::get_definition_type_of_tuple_argument(expr) {
         Explicit => checker.type_check(expr, context),
         Implicit(ref inner_expr) => {
@@ -23,7 +23,7 @@ fn check_and_type_map_arg_tuple(checker: &mut TypeChecker, expr: &SymbolicExpres
     }
 }
 
-pub fn check_special_fetch_entry(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_fetch_entry<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
 
     check_arguments_at_least(2, args)?;
 
@@ -48,7 +48,7 @@ pub fn check_special_fetch_entry(checker: &mut TypeChecker, args: &[SymbolicExpr
     }
 }
 
-pub fn check_special_delete_entry(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_delete_entry<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(2, args)?;
 
     let map_name = args[0].match_atom()
@@ -69,7 +69,7 @@ pub fn check_special_delete_entry(checker: &mut TypeChecker, args: &[SymbolicExp
     }
 }
 
-fn check_set_or_insert_entry(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_set_or_insert_entry<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(3, args)?;
     
     let map_name = args[0].match_atom()
@@ -96,10 +96,10 @@ fn check_set_or_insert_entry(checker: &mut TypeChecker, args: &[SymbolicExpressi
     }
 }
 
-pub fn check_special_set_entry(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_set_entry<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_set_or_insert_entry(checker, args, context)
 }
 
-pub fn check_special_insert_entry(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_insert_entry<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_set_or_insert_entry(checker, args, context)
 }
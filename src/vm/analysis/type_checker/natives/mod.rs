@@ -9,22 +9,22 @@ use super::{TypeChecker, TypingContext, TypeResult, no_type, check_argument_coun
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use std::convert::TryFrom;
 
-use vm::costs::{cost_functions, analysis_typecheck_cost, CostOverflowingMath};
+use vm::costs::{cost_functions, analysis_typecheck_cost, CostOverflowingMath, CostTracker};
 
 mod assets;
 mod iterables;
 mod maps;
 mod options;
 
-pub enum TypedNativeFunction {
-    Special(SpecialNativeFunction),
+pub enum TypedNativeFunction<T> {
+    Special(SpecialNativeFunction<T>),
     Simple(SimpleNativeFunction)
 }
 
-pub struct SpecialNativeFunction(&'static dyn Fn(&mut TypeChecker, &[SymbolicExpression], &TypingContext) -> TypeResult);
+pub struct SpecialNativeFunction<T>(fn(&mut TypeChecker<T>, &[SymbolicExpression], &TypingContext) -> TypeResult);
 pub struct SimpleNativeFunction(pub FunctionType);
 
-fn check_special_list_cons(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_list_cons<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     let typed_args = checker.type_check_all(args, context)?;
     for type_arg in typed_args.iter() {
         runtime_cost!(cost_functions::ANALYSIS_LIST_ITEMS_CHECK, checker, type_arg.type_size()?)?;
@@ -34,26 +34,75 @@ fn check_special_list_cons(checker: &mut TypeChecker, args: &[SymbolicExpression
         .map(TypeSignature::from)
 }
 
-fn check_special_print(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_print<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     checker.type_check(&args[0], context)
 }
 
-fn check_special_as_contract(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_as_contract<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     checker.type_check(&args[0], context)
 }
 
-fn check_special_at_block(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_at_block<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     checker.type_check_expects(&args[0], context, &BUFF_32)?;
     checker.type_check(&args[1], context)
 }
 
-fn check_special_begin(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+// If `expr` is `(unwrap! (as-max-len? var ...) ...)` or `(unwrap-panic (as-max-len? var ...))`
+//   for a local variable `var`, and the unwrapped type is strictly narrower than `var`'s
+//   current type, returns the narrowed binding.
+fn find_length_refinement<T: CostTracker>(checker: &TypeChecker<T>, expr: &SymbolicExpression, context: &TypingContext) -> Option<(ClarityName, TypeSignature)> {
+    let (function_name, args) = expr.match_list()?.split_first()?;
+    match NativeFunctions::lookup_by_name(function_name.match_atom()?)? {
+        NativeFunctions::UnwrapRet | NativeFunctions::Unwrap => {},
+        _ => return None
+    }
+    let (inner_name, inner_args) = args.first()?.match_list()?.split_first()?;
+    match NativeFunctions::lookup_by_name(inner_name.match_atom()?)? {
+        NativeFunctions::AsMaxLen => {},
+        _ => return None
+    }
+    let var_name = inner_args.first()?.match_atom()?;
+    let current_type = context.lookup_variable_type(var_name)?;
+    let narrowed_type = checker.type_map.get_type(expr)?;
+    if narrowed_type != current_type && current_type.admits_type(narrowed_type) {
+        Some((var_name.clone(), narrowed_type.clone()))
+    } else {
+        None
+    }
+}
+
+/// Type check a body of expressions evaluated in sequence. Once an expression
+///   unwraps an `as-max-len?` of a local variable, the following expressions see
+///   the variable with the narrowed length. The refinement only lasts for the rest
+///   of this sequence.
+fn type_check_sequence<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> CheckResult<Vec<TypeSignature>> {
+    let mut refined_context = None;
+    let mut result = Vec::new();
+    for arg in args.iter() {
+        let refinement = {
+            let current_context = refined_context.as_ref().unwrap_or(context);
+            result.push(checker.type_check(arg, current_context)?);
+            find_length_refinement(checker, arg, current_context)
+        };
+        if let Some((var_name, narrowed_type)) = refinement {
+            if refined_context.is_none() {
+                refined_context = Some(context.extend()?);
+            }
+            if let Some(ref mut refined_context) = refined_context {
+                refined_context.variable_types.insert(var_name, narrowed_type);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn check_special_begin<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(1, args)?;
         
-    let mut typed_args = checker.type_check_all(args, context)?;
+    let mut typed_args = type_check_sequence(checker, args, context)?;
     
     let last_return = typed_args.pop()
         .ok_or(CheckError::new(CheckErrors::CheckerImplementationFailure))?;
@@ -61,7 +110,7 @@ fn check_special_begin(checker: &mut TypeChecker, args: &[SymbolicExpression], c
     Ok(last_return)
 }
 
-fn inner_handle_tuple_get(tuple_type_sig: &TupleTypeSignature, field_to_get: &str, checker: &mut TypeChecker) -> TypeResult {
+fn inner_handle_tuple_get<T: CostTracker>(tuple_type_sig: &TupleTypeSignature, field_to_get: &str, checker: &mut TypeChecker<T>) -> TypeResult {
     runtime_cost!(cost_functions::ANALYSIS_CHECK_TUPLE_GET, checker, tuple_type_sig.len())?;
 
     let return_type = tuple_type_sig.field_type(field_to_get)
@@ -70,7 +119,7 @@ fn inner_handle_tuple_get(tuple_type_sig: &TupleTypeSignature, field_to_get: &st
     Ok(return_type)
 }
 
-fn check_special_get(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_get<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let field_to_get = args[0].match_atom()
@@ -93,12 +142,13 @@ fn check_special_get(checker: &mut TypeChecker, args: &[SymbolicExpression], con
     }
 }
 
-pub fn check_special_tuple_cons(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_tuple_cons<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(1, args)?;
     
     let mut tuple_type_data = Vec::new();
 
     runtime_cost!(cost_functions::ANALYSIS_CHECK_TUPLE_CONS, checker, args.len())?;
+    checker.check_tuple_arity(args.len() as u64)?;
 
     handle_binding_list(args, |var_name, var_sexp| {
         checker.type_check(var_sexp, context)
@@ -115,7 +165,7 @@ pub fn check_special_tuple_cons(checker: &mut TypeChecker, args: &[SymbolicExpre
     Ok(TypeSignature::TupleType(tuple_signature))
 }
 
-fn check_special_let(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_let<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(2, args)?;
 
     let binding_list = args[0].match_list()
@@ -137,7 +187,7 @@ fn check_special_let(checker: &mut TypeChecker, args: &[SymbolicExpression], con
         Ok(())
     })?;
     
-    let mut typed_args = checker.type_check_all(&args[1..args.len()], &out_context)?;
+    let mut typed_args = type_check_sequence(checker, &args[1..args.len()], &out_context)?;
     
     let last_return = typed_args.pop()
         .ok_or(CheckError::new(CheckErrors::CheckerImplementationFailure))?;
@@ -145,7 +195,7 @@ fn check_special_let(checker: &mut TypeChecker, args: &[SymbolicExpression], con
     Ok(last_return)
 }
 
-fn check_special_fetch_var(checker: &mut TypeChecker, args: &[SymbolicExpression], _context: &TypingContext) -> TypeResult {
+fn check_special_fetch_var<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], _context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     
     let var_name = args[0].match_atom()
@@ -159,7 +209,7 @@ fn check_special_fetch_var(checker: &mut TypeChecker, args: &[SymbolicExpression
     Ok(value_type.clone())
 }
 
-fn check_special_set_var(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_set_var<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(2, args)?;
     
     let var_name = args[0].match_atom()
@@ -180,7 +230,7 @@ fn check_special_set_var(checker: &mut TypeChecker, args: &[SymbolicExpression],
     }
 }
 
-fn check_special_equals(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_equals<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(1, args)?;
 
     let mut arg_types = checker.type_check_all(args, context)?;
@@ -195,7 +245,7 @@ fn check_special_equals(checker: &mut TypeChecker, args: &[SymbolicExpression],
     Ok(TypeSignature::BoolType)
 }
 
-fn check_special_if(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_special_if<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(3, args)?;
     
     checker.type_check_expects(&args[0], context, &TypeSignature::BoolType)?;
@@ -211,7 +261,7 @@ fn check_special_if(checker: &mut TypeChecker, args: &[SymbolicExpression], cont
         .map_err(|_| CheckErrors::IfArmsMustMatch(expr1.clone(), expr2.clone()).into())
 }
 
-fn check_contract_call(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_contract_call<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(2, args)?;
 
     let func_name = args[1].match_atom()
@@ -221,14 +271,21 @@ fn check_contract_call(checker: &mut TypeChecker, args: &[SymbolicExpression], c
     let expected_sig = match &args[0].expr {
         SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(ref contract_identifier))) => {
             // Static dispatch
+            // a missing dependency is reported at the contract principal, and a missing
+            //   function of an existing contract at the function name.
+            let public_function_type = checker.db.get_public_function_type(&contract_identifier, func_name)
+                .map_err(|mut e| { e.set_expression(&args[0]); e })?;
+
             let contract_call_function = {
-                if let Some(FunctionType::Fixed(function)) = checker.db.get_public_function_type(&contract_identifier, func_name)? {
+                if let Some(FunctionType::Fixed(function)) = public_function_type {
                     Ok(function)
                 } else if let Some(FunctionType::Fixed(function)) = checker.db.get_read_only_function_type(&contract_identifier, func_name)? {
                     Ok(function)
                 } else {
-                    Err(CheckError::new(CheckErrors::NoSuchPublicFunction(contract_identifier.to_string(),
-                                                                          func_name.to_string())))
+                    let mut error = CheckError::new(CheckErrors::NoSuchPublicFunction(contract_identifier.to_string(),
+                                                                                      func_name.to_string()));
+                    error.set_expression(&args[1]);
+                    Err(error)
                 }
             }?;
 
@@ -247,7 +304,7 @@ fn check_contract_call(checker: &mut TypeChecker, args: &[SymbolicExpression], c
 
             runtime_cost!(cost_functions::ANALYSIS_LOOKUP_FUNCTION, checker, 1)?;
 
-            let trait_signature = checker.contract_context.get_trait(&trait_id.name)
+            let trait_signature = checker.contract_context.get_trait_by_identifier(&trait_id)
                 .ok_or(CheckErrors::TraitReferenceUnknown(trait_id.name.to_string()))?;
             let func_signature = trait_signature.get(func_name)
                 .ok_or(CheckErrors::TraitMethodUnknown(trait_id.name.to_string(), func_name.to_string()))?;
@@ -267,7 +324,7 @@ fn check_contract_call(checker: &mut TypeChecker, args: &[SymbolicExpression], c
     Ok(expected_sig.returns)
 }
 
-fn check_get_block_info(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+fn check_get_block_info<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(2, args)?;
 
     let block_info_prop_str = args[0].match_atom()
@@ -281,16 +338,16 @@ fn check_get_block_info(checker: &mut TypeChecker, args: &[SymbolicExpression],
     Ok(TypeSignature::new_option(block_info_prop.type_result())?)
 }
 
-impl TypedNativeFunction {
-    pub fn type_check_appliction(&self, checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+impl <T: CostTracker> TypedNativeFunction<T> {
+    pub fn type_check_appliction(&self, checker: &mut TypeChecker<T>, function_name: &str, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
         use self::TypedNativeFunction::{Special, Simple};
         match self {
             Special(SpecialNativeFunction(check)) => check(checker, args, context),
-            Simple(SimpleNativeFunction(function_type)) => checker.type_check_function_type(function_type, args, context),
+            Simple(SimpleNativeFunction(function_type)) => checker.type_check_native_function_type(function_name, function_type, args, context),
         }
     }
 
-    pub fn type_native_function(function: &NativeFunctions) -> TypedNativeFunction {
+    pub fn type_native_function(function: &NativeFunctions) -> TypedNativeFunction<T> {
         use self::TypedNativeFunction::{Special, Simple};
         use vm::functions::NativeFunctions::*;
         match function {
@@ -375,52 +432,52 @@ impl TypedNativeFunction {
                                          .expect("FAIL: ClarityName failed to accept default arg name")),
                     ],
                     returns: TypeSignature::new_response(TypeSignature::BoolType, TypeSignature::UIntType).unwrap() }))),
-            GetTokenBalance => Special(SpecialNativeFunction(&assets::check_special_get_balance)),
-            GetAssetOwner => Special(SpecialNativeFunction(&assets::check_special_get_owner)),
-            TransferToken => Special(SpecialNativeFunction(&assets::check_special_transfer_token)),
-            TransferAsset => Special(SpecialNativeFunction(&assets::check_special_transfer_asset)),
-            MintAsset => Special(SpecialNativeFunction(&assets::check_special_mint_asset)),
-            MintToken => Special(SpecialNativeFunction(&assets::check_special_mint_token)),
-            Equals => Special(SpecialNativeFunction(&check_special_equals)),
-            If => Special(SpecialNativeFunction(&check_special_if)),
-            Let => Special(SpecialNativeFunction(&check_special_let)),
-            FetchVar => Special(SpecialNativeFunction(&check_special_fetch_var)),
-            SetVar => Special(SpecialNativeFunction(&check_special_set_var)),
-            Map => Special(SpecialNativeFunction(&iterables::check_special_map)),
-            Filter => Special(SpecialNativeFunction(&iterables::check_special_filter)),
-            Fold => Special(SpecialNativeFunction(&iterables::check_special_fold)),
-            Append => Special(SpecialNativeFunction(&iterables::check_special_append)),
-            Concat => Special(SpecialNativeFunction(&iterables::check_special_concat)),
-            AsMaxLen => Special(SpecialNativeFunction(&iterables::check_special_as_max_len)),
-            Len => Special(SpecialNativeFunction(&iterables::check_special_len)),
-            ListCons => Special(SpecialNativeFunction(&check_special_list_cons)),
-            FetchEntry => Special(SpecialNativeFunction(&maps::check_special_fetch_entry)),
-            SetEntry => Special(SpecialNativeFunction(&maps::check_special_set_entry)),
-            InsertEntry => Special(SpecialNativeFunction(&maps::check_special_insert_entry)),
-            DeleteEntry => Special(SpecialNativeFunction(&maps::check_special_delete_entry)),
-            TupleCons => Special(SpecialNativeFunction(&check_special_tuple_cons)),
-            TupleGet => Special(SpecialNativeFunction(&check_special_get)),
-            Begin => Special(SpecialNativeFunction(&check_special_begin)),
-            Print => Special(SpecialNativeFunction(&check_special_print)),
-            AsContract => Special(SpecialNativeFunction(&check_special_as_contract)),
-            ContractCall => Special(SpecialNativeFunction(&check_contract_call)),
-            GetBlockInfo => Special(SpecialNativeFunction(&check_get_block_info)),
-            ConsSome => Special(SpecialNativeFunction(&options::check_special_some)),
-            ConsOkay => Special(SpecialNativeFunction(&options::check_special_okay)),
-            ConsError => Special(SpecialNativeFunction(&options::check_special_error)),
-            DefaultTo => Special(SpecialNativeFunction(&options::check_special_default_to)),
-            Asserts => Special(SpecialNativeFunction(&options::check_special_asserts)),
-            UnwrapRet => Special(SpecialNativeFunction(&options::check_special_unwrap_or_ret)),
-            UnwrapErrRet => Special(SpecialNativeFunction(&options::check_special_unwrap_err_or_ret)),
-            Unwrap => Special(SpecialNativeFunction(&options::check_special_unwrap)),
-            UnwrapErr => Special(SpecialNativeFunction(&options::check_special_unwrap_err)),
-            TryRet => Special(SpecialNativeFunction(&options::check_special_try_ret)),
-            Match => Special(SpecialNativeFunction(&options::check_special_match)),
-            IsOkay => Special(SpecialNativeFunction(&options::check_special_is_response)),
-            IsErr => Special(SpecialNativeFunction(&options::check_special_is_response)),
-            IsNone => Special(SpecialNativeFunction(&options::check_special_is_optional)),
-            IsSome => Special(SpecialNativeFunction(&options::check_special_is_optional)),
-            AtBlock => Special(SpecialNativeFunction(&check_special_at_block)),
+            GetTokenBalance => Special(SpecialNativeFunction(assets::check_special_get_balance)),
+            GetAssetOwner => Special(SpecialNativeFunction(assets::check_special_get_owner)),
+            TransferToken => Special(SpecialNativeFunction(assets::check_special_transfer_token)),
+            TransferAsset => Special(SpecialNativeFunction(assets::check_special_transfer_asset)),
+            MintAsset => Special(SpecialNativeFunction(assets::check_special_mint_asset)),
+            MintToken => Special(SpecialNativeFunction(assets::check_special_mint_token)),
+            Equals => Special(SpecialNativeFunction(check_special_equals)),
+            If => Special(SpecialNativeFunction(check_special_if)),
+            Let => Special(SpecialNativeFunction(check_special_let)),
+            FetchVar => Special(SpecialNativeFunction(check_special_fetch_var)),
+            SetVar => Special(SpecialNativeFunction(check_special_set_var)),
+            Map => Special(SpecialNativeFunction(iterables::check_special_map)),
+            Filter => Special(SpecialNativeFunction(iterables::check_special_filter)),
+            Fold => Special(SpecialNativeFunction(iterables::check_special_fold)),
+            Append => Special(SpecialNativeFunction(iterables::check_special_append)),
+            Concat => Special(SpecialNativeFunction(iterables::check_special_concat)),
+            AsMaxLen => Special(SpecialNativeFunction(iterables::check_special_as_max_len)),
+            Len => Special(SpecialNativeFunction(iterables::check_special_len)),
+            ListCons => Special(SpecialNativeFunction(check_special_list_cons)),
+            FetchEntry => Special(SpecialNativeFunction(maps::check_special_fetch_entry)),
+            SetEntry => Special(SpecialNativeFunction(maps::check_special_set_entry)),
+            InsertEntry => Special(SpecialNativeFunction(maps::check_special_insert_entry)),
+            DeleteEntry => Special(SpecialNativeFunction(maps::check_special_delete_entry)),
+            TupleCons => Special(SpecialNativeFunction(check_special_tuple_cons)),
+            TupleGet => Special(SpecialNativeFunction(check_special_get)),
+            Begin => Special(SpecialNativeFunction(check_special_begin)),
+            Print => Special(SpecialNativeFunction(check_special_print)),
+            AsContract => Special(SpecialNativeFunction(check_special_as_contract)),
+            ContractCall => Special(SpecialNativeFunction(check_contract_call)),
+            GetBlockInfo => Special(SpecialNativeFunction(check_get_block_info)),
+            ConsSome => Special(SpecialNativeFunction(options::check_special_some)),
+            ConsOkay => Special(SpecialNativeFunction(options::check_special_okay)),
+            ConsError => Special(SpecialNativeFunction(options::check_special_error)),
+            DefaultTo => Special(SpecialNativeFunction(options::check_special_default_to)),
+            Asserts => Special(SpecialNativeFunction(options::check_special_asserts)),
+            UnwrapRet => Special(SpecialNativeFunction(options::check_special_unwrap_or_ret)),
+            UnwrapErrRet => Special(SpecialNativeFunction(options::check_special_unwrap_err_or_ret)),
+            Unwrap => Special(SpecialNativeFunction(options::check_special_unwrap)),
+            UnwrapErr => Special(SpecialNativeFunction(options::check_special_unwrap_err)),
+            TryRet => Special(SpecialNativeFunction(options::check_special_try_ret)),
+            Match => Special(SpecialNativeFunction(options::check_special_match)),
+            IsOkay => Special(SpecialNativeFunction(options::check_special_is_response)),
+            IsErr => Special(SpecialNativeFunction(options::check_special_is_response)),
+            IsNone => Special(SpecialNativeFunction(options::check_special_is_optional)),
+            IsSome => Special(SpecialNativeFunction(options::check_special_is_optional)),
+            AtBlock => Special(SpecialNativeFunction(check_special_at_block)),
         }
     }
 }
@@ -4,9 +4,9 @@ use vm::types::{TypeSignature};
 use vm::analysis::type_checker::{TypeResult, TypingContext, check_argument_count, check_arguments_at_least,
                                  CheckError, CheckErrors, no_type, TypeChecker};
 
-use vm::costs::{cost_functions, analysis_typecheck_cost};
+use vm::costs::{cost_functions, analysis_typecheck_cost, CostTracker};
 
-pub fn check_special_okay(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_okay<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
 
     runtime_cost!(cost_functions::ANALYSIS_OPTION_CONS, checker, 1)?;
@@ -16,7 +16,7 @@ pub fn check_special_okay(checker: &mut TypeChecker, args: &[SymbolicExpression]
     Ok(resp_type)
 }
 
-pub fn check_special_some(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_some<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
 
     runtime_cost!(cost_functions::ANALYSIS_OPTION_CONS, checker, 1)?;
@@ -26,7 +26,7 @@ pub fn check_special_some(checker: &mut TypeChecker, args: &[SymbolicExpression]
     Ok(resp_type)
 }
 
-pub fn check_special_error(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_error<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
 
     runtime_cost!(cost_functions::ANALYSIS_OPTION_CONS, checker, 1)?;
@@ -36,7 +36,7 @@ pub fn check_special_error(checker: &mut TypeChecker, args: &[SymbolicExpression
     Ok(resp_type)
 }
 
-pub fn check_special_is_response(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_is_response<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -50,7 +50,7 @@ pub fn check_special_is_response(checker: &mut TypeChecker, args: &[SymbolicExpr
     }
 }
 
-pub fn check_special_is_optional(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_is_optional<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -64,7 +64,7 @@ pub fn check_special_is_optional(checker: &mut TypeChecker, args: &[SymbolicExpr
     }
 }
 
-pub fn check_special_default_to(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_default_to<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let default = checker.type_check(&args[0], context)?;
@@ -81,7 +81,7 @@ pub fn check_special_default_to(checker: &mut TypeChecker, args: &[SymbolicExpre
     }
 }
 
-pub fn check_special_asserts(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_asserts<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
 
     checker.type_check_expects(&args[0], context, &TypeSignature::BoolType)?;
@@ -92,7 +92,7 @@ pub fn check_special_asserts(checker: &mut TypeChecker, args: &[SymbolicExpressi
     Ok(TypeSignature::BoolType)
 }
 
-fn inner_unwrap(input: TypeSignature, checker: &mut TypeChecker) -> TypeResult {
+fn inner_unwrap<T: CostTracker>(input: TypeSignature, checker: &mut TypeChecker<T>) -> TypeResult {
     runtime_cost!(cost_functions::ANALYSIS_OPTION_CHECK, checker, 1)?;
 
     match input {
@@ -115,7 +115,7 @@ fn inner_unwrap(input: TypeSignature, checker: &mut TypeChecker) -> TypeResult {
     }
 }
 
-fn inner_unwrap_err(input: TypeSignature, checker: &mut TypeChecker) -> TypeResult {
+fn inner_unwrap_err<T: CostTracker>(input: TypeSignature, checker: &mut TypeChecker<T>) -> TypeResult {
     runtime_cost!(cost_functions::ANALYSIS_OPTION_CHECK, checker, 1)?;
 
     if let TypeSignature::ResponseType(response_type) = input {
@@ -130,7 +130,7 @@ fn inner_unwrap_err(input: TypeSignature, checker: &mut TypeChecker) -> TypeResu
     }
 }
 
-pub fn check_special_unwrap_or_ret(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_unwrap_or_ret<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -141,7 +141,7 @@ pub fn check_special_unwrap_or_ret(checker: &mut TypeChecker, args: &[SymbolicEx
     inner_unwrap(input, checker)
 }
 
-pub fn check_special_unwrap_err_or_ret(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_unwrap_err_or_ret<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(2, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -152,7 +152,7 @@ pub fn check_special_unwrap_err_or_ret(checker: &mut TypeChecker, args: &[Symbol
     inner_unwrap_err(input, checker)
 }
 
-pub fn check_special_try_ret(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_try_ret<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -184,7 +184,7 @@ pub fn check_special_try_ret(checker: &mut TypeChecker, args: &[SymbolicExpressi
     }
 }
 
-pub fn check_special_unwrap(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_unwrap<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -192,7 +192,7 @@ pub fn check_special_unwrap(checker: &mut TypeChecker, args: &[SymbolicExpressio
     inner_unwrap(input, checker)
 }
 
-pub fn check_special_unwrap_err(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_unwrap_err<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_argument_count(1, args)?;
     
     let input = checker.type_check(&args[0], context)?;
@@ -200,8 +200,8 @@ pub fn check_special_unwrap_err(checker: &mut TypeChecker, args: &[SymbolicExpre
     inner_unwrap_err(input, checker)
 }
 
-fn eval_with_new_binding(body: &SymbolicExpression, bind_name: ClarityName, bind_type: TypeSignature, 
-                         checker: &mut TypeChecker, context: &TypingContext) -> TypeResult {
+fn eval_with_new_binding<T: CostTracker>(body: &SymbolicExpression, bind_name: ClarityName, bind_type: TypeSignature, 
+                         checker: &mut TypeChecker<T>, context: &TypingContext) -> TypeResult {
     let mut inner_context = context.extend()?;
 
     runtime_cost!(cost_functions::ANALYSIS_BIND_NAME, checker, bind_type.type_size()?)?;
@@ -217,7 +217,7 @@ fn eval_with_new_binding(body: &SymbolicExpression, bind_name: ClarityName, bind
     checker.type_check(body, &inner_context)
 }
 
-fn check_special_match_opt(option_type: TypeSignature, checker: &mut TypeChecker,
+fn check_special_match_opt<T: CostTracker>(option_type: TypeSignature, checker: &mut TypeChecker<T>,
                            args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     if args.len() != 3 {
         Err(CheckErrors::BadMatchOptionSyntax(
@@ -245,7 +245,7 @@ fn check_special_match_opt(option_type: TypeSignature, checker: &mut TypeChecker
         .map_err(|_| CheckErrors::MatchArmsMustMatch(some_branch_type, none_branch_type).into())
 }
 
-fn check_special_match_resp(resp_type: (TypeSignature, TypeSignature), checker: &mut TypeChecker,
+fn check_special_match_resp<T: CostTracker>(resp_type: (TypeSignature, TypeSignature), checker: &mut TypeChecker<T>,
                             args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     if args.len() != 4 {
         Err(CheckErrors::BadMatchResponseSyntax(
@@ -278,7 +278,7 @@ fn check_special_match_resp(resp_type: (TypeSignature, TypeSignature), checker:
         .map_err(|_| CheckErrors::MatchArmsMustMatch(ok_branch_type, err_branch_type).into())
 }
 
-pub fn check_special_match(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
+pub fn check_special_match<T: CostTracker>(checker: &mut TypeChecker<T>, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
     check_arguments_at_least(1, args)?;
 
     let input = checker.type_check(&args[0], context)?;
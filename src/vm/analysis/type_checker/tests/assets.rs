@@ -126,8 +126,8 @@ fn test_bad_asset_usage() {
                        "(ft-transfer? stackaroos u2 u100 tx-sender)",
                        "(ft-transfer? stackaroos true tx-sender tx-sender)",
                        "(ft-transfer? stackaroos u2 tx-sender u100)",
-                       "(define-fungible-token stackaroos true)",
-                       "(define-non-fungible-token stackaroos integer)",
+                       "(define-fungible-token stackaroos-2 true)",
+                       "(define-non-fungible-token stacka-nfts-2 integer)",
                        "(ft-mint? stackaroos 100 tx-sender)",
                        "(ft-transfer? stackaroos 1 tx-sender tx-sender)",
     ];
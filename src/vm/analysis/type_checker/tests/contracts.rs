@@ -3,11 +3,11 @@ use serde_json;
 
 use vm::ast::parse;
 use vm::analysis::errors::CheckErrors;
-use vm::analysis::{AnalysisDatabase, contract_interface_builder::build_contract_interface};
+use vm::analysis::{AnalysisDatabase, contract_interface_builder::{build_contract_interface, InterfaceDiff}};
 use vm::database::MemoryBackingStore;
 use vm::analysis::mem_type_check;
 use vm::analysis::type_check;
-use vm::types::QualifiedContractIdentifier;
+use vm::types::{QualifiedContractIdentifier, TypeSignature};
 
 const SIMPLE_TOKENS: &str =
         "(define-map tokens ((account principal)) ((balance uint)))
@@ -142,9 +142,9 @@ fn test_names_tokens_contracts_interface() {
 
     let contract_analysis = mem_type_check(INTERFACE_TEST_CONTRACT).unwrap().1;
     let test_contract_json_str = build_contract_interface(&contract_analysis).serialize();
-    let test_contract_json = serde_json::from_str(&test_contract_json_str).unwrap();
+    let test_contract_json: serde_json::Value = serde_json::from_str(&test_contract_json_str).unwrap();
 
-    let test_contract_json_expected = serde_json::from_str(r#"{
+    let test_contract_json_expected: serde_json::Value = serde_json::from_str(r#"{
         "functions": [
             { "name": "f00",
                 "access": "private",
@@ -354,6 +354,71 @@ fn test_names_tokens_contracts_interface() {
 }
 
 
+#[test]
+fn test_contract_interface_bounded_json() {
+    let small_contract = "(define-public (f (a int)) (ok a))";
+    let small_interface = build_contract_interface(&mem_type_check(small_contract).unwrap().1);
+    let small_json = small_interface.to_json_bounded(1024).unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&small_json).unwrap(),
+               serde_json::from_str::<serde_json::Value>(&small_interface.serialize()).unwrap());
+
+    let mut large_contract = String::new();
+    for i in 0..200 {
+        large_contract.push_str(&format!(
+            "(define-read-only (f{} (a (tuple (x (list 10 (tuple (y int) (z (buff 10))))) (w bool)))) a)\n", i));
+    }
+    let large_interface = build_contract_interface(&mem_type_check(&large_contract).unwrap().1);
+    assert_eq!(large_interface.to_json_bounded(64 * 1024).unwrap_err().max_bytes, 64 * 1024);
+    assert!(large_interface.to_json_bounded(1024 * 1024).is_ok());
+}
+
+#[test]
+fn test_contract_interface_diff() {
+    let interface_of = |contract: &str| build_contract_interface(&mem_type_check(contract).unwrap().1);
+    let deployed = interface_of(
+        "(define-map balances ((owner principal)) ((amount uint)))
+         (define-data-var supply uint u0)
+         (define-private (helper (a int)) a)
+         (define-public (transfer (amount uint) (to principal)) (ok amount))
+         (define-read-only (get-supply) (var-get supply))");
+
+    let additive = interface_of(
+        "(define-map balances ((owner principal)) ((amount uint)))
+         (define-map allowances ((owner principal)) ((amount uint)))
+         (define-data-var supply uint u0)
+         (define-public (transfer (amount uint) (to principal)) (ok amount))
+         (define-public (burn (amount uint)) (ok amount))
+         (define-read-only (get-supply) (var-get supply))");
+    let diff = deployed.diff(&additive);
+    assert_eq!(diff.functions.added, vec!["burn".to_string()]);
+    assert_eq!(diff.maps.added, vec!["allowances".to_string()]);
+    // private functions are not part of the callable interface
+    assert!(diff.functions.removed.is_empty());
+    assert!(diff.data_vars.is_empty());
+    assert!(diff.is_backward_compatible());
+
+    let removed = interface_of(
+        "(define-map balances ((owner principal)) ((amount uint)))
+         (define-data-var supply uint u0)
+         (define-read-only (get-supply) (var-get supply))");
+    let diff = deployed.diff(&removed);
+    assert_eq!(diff.functions.removed, vec!["transfer".to_string()]);
+    assert!(!diff.is_backward_compatible());
+
+    let changed = interface_of(
+        "(define-map balances ((owner principal)) ((amount uint)))
+         (define-data-var supply int 0)
+         (define-public (transfer (amount int) (to principal)) (ok amount))
+         (define-read-only (get-supply) (var-get supply))");
+    let diff = deployed.diff(&changed);
+    assert_eq!(diff.functions.changed, vec!["get-supply".to_string(), "transfer".to_string()]);
+    assert_eq!(diff.data_vars.changed, vec!["supply".to_string()]);
+    assert!(!diff.is_backward_compatible());
+
+    assert!(deployed.diff(&deployed).is_backward_compatible());
+    assert_eq!(deployed.diff(&deployed), InterfaceDiff::default());
+}
+
 #[test]
 fn test_names_tokens_contracts() {
     let tokens_contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
@@ -488,6 +553,145 @@ fn test_same_function_name() {
     }).unwrap();
 }
 
+#[test]
+fn test_external_dependencies() {
+    let token_id = QualifiedContractIdentifier::parse("SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5.token").unwrap();
+    let names_id = QualifiedContractIdentifier::parse("SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5.names").unwrap();
+    let trait_id = QualifiedContractIdentifier::parse("SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.traits").unwrap();
+    let helper_id = QualifiedContractIdentifier::local("helper").unwrap();
+    let contract_id = QualifiedContractIdentifier::local("dependent").unwrap();
+
+    let token = "(define-read-only (balance) u1)";
+    let names = "(define-read-only (lookup (n int)) (+ n 1))";
+    let traits = "(define-trait getter ((get-value () (response uint uint))))";
+    let helper = "(define-read-only (help) 1)";
+    let dependent =
+        "(use-trait getter 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.traits.getter)
+         (define-read-only (total)
+           (+ (contract-call? 'SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5.names lookup 1)
+              (contract-call? .helper help)))
+         (define-read-only (balance)
+           (contract-call? 'SP139Q3N9RXCJCD1XVA4N5RYWQ5K9XQ0T9PKQ8EE5.token balance))
+         (define-public (get-from (source <getter>))
+           (contract-call? source get-value))";
+
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+
+    let analysis = db.execute(|db| {
+        for (id, src) in &[(&token_id, token), (&names_id, names), (&trait_id, traits), (&helper_id, helper)] {
+            type_check(id, &mut parse(id, src).unwrap(), db, true)?;
+        }
+        type_check(&contract_id, &mut parse(&contract_id, dependent).unwrap(), db, true)
+    }).unwrap();
+
+    assert_eq!(analysis.external_dependencies(), vec![names_id, token_id, trait_id]);
+    assert_eq!(analysis.deployer_dependencies(), vec![helper_id]);
+}
+
+#[test]
+fn test_public_function_args() {
+    let contract = "(define-public (transfer (amount uint) (to principal))
+                      (begin (print to) (ok amount)))
+                    (define-read-only (get-total) u0)";
+
+    let analysis = mem_type_check(contract).unwrap().1;
+    let functions = analysis.public_function_args();
+    assert_eq!(functions.len(), 1);
+
+    let (name, args, returns) = &functions[0];
+    assert_eq!(name.as_str(), "transfer");
+    assert_eq!(args.iter().map(|arg| arg.name.as_str()).collect::<Vec<_>>(), vec!["amount", "to"]);
+    assert_eq!(args.iter().map(|arg| arg.signature.clone()).collect::<Vec<_>>(),
+               vec![TypeSignature::UIntType, TypeSignature::PrincipalType]);
+    assert_eq!(returns, &TypeSignature::new_response(TypeSignature::UIntType, TypeSignature::NoType).unwrap());
+}
+
+#[test]
+fn test_data_vars() {
+    let contract = "(define-data-var owner principal tx-sender)
+                    (define-data-var count uint u0)
+                    (define-constant limit 10)";
+
+    let analysis = mem_type_check(contract).unwrap().1;
+    assert_eq!(analysis.data_vars(),
+               vec![("count".into(), TypeSignature::UIntType),
+                    ("owner".into(), TypeSignature::PrincipalType)]);
+}
+
+#[test]
+fn test_duplicate_definitions() {
+    let contract = "(define-private (foo) 1)
+(define-data-var bar int 0)
+(define-public (foo) (ok 2))";
+
+    let error = mem_type_check(contract).unwrap_err();
+    assert_eq!(error.err, CheckErrors::NameAlreadyUsed("foo".to_string()));
+
+    let lines: Vec<_> = error.diagnostic.spans.iter()
+        .map(|span| (span.start_line, span.end_line))
+        .collect();
+    assert_eq!(lines, vec![(3, 3), (1, 1)]);
+
+    let contract = "(define-map foo ((a int)) ((b int)))
+(define-fungible-token foo)";
+    assert_eq!(mem_type_check(contract).unwrap_err().err,
+               CheckErrors::NameAlreadyUsed("foo".to_string()));
+}
+
+#[test]
+fn test_contract_call_missing_target() {
+    let tokens_contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
+    let caller_contract_id = QualifiedContractIdentifier::local("caller").unwrap();
+
+    let missing_contract = "(define-public (f)
+  (contract-call? .no-such-contract token-transfer tx-sender u1))";
+    let missing_function = "(define-public (f)
+  (contract-call? .tokens no-such-function tx-sender u1))";
+
+    for (caller_src, expected_err, expected_position) in
+        [(missing_contract,
+          CheckErrors::NoSuchContract(QualifiedContractIdentifier::local("no-such-contract").unwrap().to_string()),
+          (2, 19)),
+         (missing_function,
+          CheckErrors::NoSuchPublicFunction(tokens_contract_id.to_string(), "no-such-function".to_string()),
+          (2, 27))].iter() {
+        let mut tokens_contract = parse(&tokens_contract_id, SIMPLE_TOKENS).unwrap();
+        let mut caller = parse(&caller_contract_id, caller_src).unwrap();
+        let mut marf = MemoryBackingStore::new();
+        let mut db = marf.as_analysis_db();
+
+        let error = db.execute(|db| {
+            type_check(&tokens_contract_id, &mut tokens_contract, db, true)?;
+            type_check(&caller_contract_id, &mut caller, db, false)
+        }).unwrap_err();
+        assert_eq!(&error.err, expected_err);
+        let span = &error.diagnostic.spans[0];
+        assert_eq!(&(span.start_line, span.start_column), expected_position);
+    }
+}
+
+#[test]
+fn test_accounted_size() {
+    let small = "(define-data-var count uint u0)";
+    let large = "(define-data-var count uint u0)
+                 (define-public (increment)
+                   (ok (var-set count (+ (var-get count) u1))))";
+
+    let small_size = mem_type_check(small).unwrap().1.accounted_size().unwrap();
+    let large_size = mem_type_check(large).unwrap().1.accounted_size().unwrap();
+    assert_eq!(small_size, small.len() as u64);
+    assert!(small_size > 0);
+    assert!(large_size > small_size);
+
+    // an analysis run on the parsed expressions alone has no size to report
+    let contract_id = QualifiedContractIdentifier::transient();
+    let mut contract = parse(&contract_id, small).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let analysis = type_check(&contract_id, &mut contract, &mut marf.as_analysis_db(), false).unwrap();
+    assert_eq!(analysis.accounted_size(), None);
+}
+
 #[test]
 fn test_expects() {
     use vm::analysis::type_check;
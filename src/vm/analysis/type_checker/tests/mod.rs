@@ -3,7 +3,7 @@ use vm::ast::errors::ParseErrors;
 use vm::representations::SymbolicExpression;
 use vm::analysis::type_checker::{TypeResult, TypeChecker, TypingContext};
 use vm::analysis::{AnalysisDatabase};
-use vm::analysis::errors::CheckErrors;
+use vm::analysis::errors::{CheckErrors, IntUintMismatch};
 use vm::analysis::mem_type_check;
 use vm::analysis::type_check;
 use vm::analysis::types::ContractAnalysis;
@@ -545,8 +545,8 @@ fn test_lists() {
         "(len 1)"];
     let bad_expected = [
         CheckErrors::TypeError(BoolType, IntType),
-        CheckErrors::IncorrectArgumentCount(1, 2),
-        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::IncorrectCallbackArity("fold".to_string(), "hash160".to_string(), 2, 1),
+        CheckErrors::IncorrectCallbackArity("fold".to_string(), "hash160".to_string(), 2, 1),
         CheckErrors::TypeError(IntType, BoolType),
         CheckErrors::TypeError(IntType, BoolType),
         CheckErrors::TypeError(IntType, BoolType),
@@ -556,7 +556,7 @@ fn test_lists() {
         CheckErrors::IncorrectArgumentCount(2, 3),
         CheckErrors::UnknownFunction("ynot".to_string()),
         CheckErrors::IllegalOrUnknownFunctionApplication("if".to_string()),
-        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::IncorrectCallbackArity("map".to_string(), "mod".to_string(), 1, 2),
         CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
         CheckErrors::ExpectedListOrBuffer(UIntType),
         CheckErrors::ExpectedListOrBuffer(IntType)];
@@ -598,7 +598,7 @@ fn test_buff() {
         "(len 1)"];
     let bad_expected = [
         CheckErrors::TypeError(BoolType, IntType),
-        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::IncorrectCallbackArity("fold".to_string(), "hash160".to_string(), 2, 1),
         CheckErrors::TypeError(IntType, BoolType),
         CheckErrors::TypeError(IntType, BoolType),
         CheckErrors::TypeError(IntType, BoolType),
@@ -607,7 +607,7 @@ fn test_buff() {
         CheckErrors::IncorrectArgumentCount(2, 3),
         CheckErrors::UnknownFunction("ynot".to_string()),
         CheckErrors::IllegalOrUnknownFunctionApplication("if".to_string()),
-        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::IncorrectCallbackArity("map".to_string(), "mod".to_string(), 1, 2),
         CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
         CheckErrors::ExpectedListOrBuffer(UIntType),
         CheckErrors::ExpectedListOrBuffer(IntType)];
@@ -621,6 +621,45 @@ fn test_buff() {
     }
 }
 
+#[test]
+fn test_callback_arity_mismatch() {
+    let bad = [
+        "(define-private (binary-fn (a int) (b int)) (+ a b))
+         (define-private (a-list) (list 1 2 3))
+         (map binary-fn (a-list))",
+        "(define-private (binary-fn (a int) (b int)) (> a b))
+         (filter binary-fn (list 1 2 3))",
+        "(define-private (unary-fn (a int)) a)
+         (fold unary-fn (list 1 2 3) 0)"];
+    let bad_expected = [
+        CheckErrors::IncorrectCallbackArity("map".to_string(), "binary-fn".to_string(), 1, 2),
+        CheckErrors::IncorrectCallbackArity("filter".to_string(), "binary-fn".to_string(), 1, 2),
+        CheckErrors::IncorrectCallbackArity("fold".to_string(), "unary-fn".to_string(), 2, 1)];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &mem_type_check(&bad_test).unwrap_err().err);
+    }
+
+    let err = mem_type_check(bad[0]).unwrap_err();
+    assert!(err.diagnostic.message.contains("function 'binary-fn' passed to 'map' must take 1 argument(s), but takes 2"));
+    assert!(err.diagnostic.suggestion.unwrap().contains("(map f list)"));
+
+    // the diagnostic points at the callback
+    let fold_unary = "(define-private (unary-fn (a int)) a)
+(fold unary-fn (list 1 2 3) 0)";
+    let err = mem_type_check(fold_unary).unwrap_err();
+    assert_eq!(err.err, CheckErrors::IncorrectCallbackArity("fold".to_string(), "unary-fn".to_string(), 2, 1));
+    let span = &err.diagnostic.spans[0];
+    assert_eq!((span.start_line, span.start_column, span.end_column), (2, 7, 14));
+
+    let map_ternary = "(define-private (ternary-fn (a int) (b int) (c int)) (+ a b c))
+(map ternary-fn (list 1 2 3))";
+    let err = mem_type_check(map_ternary).unwrap_err();
+    assert_eq!(err.err, CheckErrors::IncorrectCallbackArity("map".to_string(), "ternary-fn".to_string(), 1, 3));
+    let span = &err.diagnostic.spans[0];
+    assert_eq!((span.start_line, span.start_column, span.end_column), (2, 6, 15));
+}
+
 #[test]
 fn test_buff_fold() {
     let good = [
@@ -678,6 +717,51 @@ fn test_buff_as_max_len() {
     }
 }
 
+#[test]
+fn test_as_max_len_refinement() {
+    let refined = "(define-data-var stored (list 10 int) (list))
+        (define-public (store (items (list 20 int)))
+          (begin
+            (unwrap! (as-max-len? items u5) (err u1))
+            (ok (var-set stored (concat items items)))))
+        (define-public (store-let (items (list 20 int)))
+          (let ((doubled true))
+            (unwrap-panic (as-max-len? items u5))
+            (ok (var-set stored (concat items items)))))";
+    mem_type_check(refined).unwrap();
+
+    // the refinement does not apply to expressions before the unwrap
+    let unrefined = "(define-data-var stored (list 10 int) (list))
+        (define-public (store (items (list 20 int)))
+          (begin
+            (var-set stored (concat items items))
+            (unwrap! (as-max-len? items u5) (err u1))
+            (ok true)))";
+    assert!(match mem_type_check(unrefined).unwrap_err().err {
+        CheckErrors::TypeError(_, _) => true,
+        _ => false
+    });
+
+    // ...nor does it leak out of the enclosing begin
+    let leaked = "(define-data-var stored (list 10 int) (list))
+        (define-public (store (items (list 20 int)))
+          (begin
+            (begin (unwrap! (as-max-len? items u5) (err u1)) true)
+            (ok (var-set stored (concat items items)))))";
+    assert!(match mem_type_check(leaked).unwrap_err().err {
+        CheckErrors::TypeError(_, _) => true,
+        _ => false
+    });
+
+    // a looser bound never widens the variable's type
+    let widened = "(define-data-var stored (list 10 int) (list))
+        (define-public (store (items (list 5 int)))
+          (begin
+            (unwrap! (as-max-len? items u50) (err u1))
+            (ok (var-set stored (concat items items)))))";
+    mem_type_check(widened).unwrap();
+}
+
 #[test]
 fn test_native_append() {
     let good = [
@@ -874,7 +958,7 @@ fn test_simple_uints() {
 
     let bad = ["(> u1 1)", "(to-uint true)", "(to-int false)"];
 
-    let bad_expected = [ CheckErrors::TypeError(UIntType, IntType),
+    let bad_expected = [ CheckErrors::IntUintMismatch(Box::new(IntUintMismatch { op: ">".to_string(), expected: UIntType, found: IntType })),
                          CheckErrors::TypeError(IntType, BoolType),
                          CheckErrors::TypeError(UIntType, BoolType) ];
 
@@ -888,6 +972,25 @@ fn test_simple_uints() {
     }
 }
 
+#[test]
+fn test_int_uint_mismatch() {
+    mem_type_check("(+ 1 2)").unwrap();
+
+    let err = mem_type_check("(+ 1 u2)").unwrap_err();
+    assert_eq!(err.err, CheckErrors::IntUintMismatch(Box::new(IntUintMismatch { op: "+".to_string(), expected: IntType, found: UIntType })));
+    assert_eq!(err.diagnostic.message, "'+' cannot mix signed and unsigned integers (got 'int' and 'uint')");
+
+    assert_eq!(mem_type_check("(pow u2 (+ 1 2))").unwrap_err().err,
+               CheckErrors::IntUintMismatch(Box::new(IntUintMismatch { op: "pow".to_string(), expected: UIntType, found: IntType })));
+
+    // a mismatch nested in an argument is reported for the expression it occurs in
+    assert_eq!(mem_type_check("(* 3 (- u1 1))").unwrap_err().err,
+               CheckErrors::IntUintMismatch(Box::new(IntUintMismatch { op: "-".to_string(), expected: UIntType, found: IntType })));
+    // other arithmetic type errors are unchanged
+    assert_eq!(mem_type_check("(+ 1 true)").unwrap_err().err,
+               CheckErrors::TypeError(IntType, BoolType));
+}
+
 #[test]
 fn test_response_inference() {
     let good = ["(define-private (foo (x int)) (err x))
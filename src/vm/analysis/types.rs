@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
-use vm::{SymbolicExpression, ClarityName};
-use vm::types::{TypeSignature, FunctionType, QualifiedContractIdentifier, TraitIdentifier};
-use vm::types::signatures::FunctionSignature;
+use vm::{SymbolicExpression, SymbolicExpressionType, ClarityName, Value};
+use vm::representations::depth_traverse;
+use vm::functions::NativeFunctions;
+use vm::types::{TypeSignature, FunctionType, QualifiedContractIdentifier, TraitIdentifier, PrincipalData};
+use vm::types::signatures::{FunctionSignature, FunctionArg};
 use vm::analysis::analysis_db::{AnalysisDatabase};
 use vm::analysis::errors::{CheckResult, CheckErrors};
 use vm::analysis::type_checker::contexts::TypeMap;
@@ -15,6 +17,29 @@ pub trait AnalysisPass {
     fn run_pass(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()>;
 }
 
+/// The analysis passes run by `run_analysis`, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassName {
+    ReadOnlyChecker,
+    TypeChecker,
+    TraitChecker,
+}
+
+/// Limits enforced during analysis, beyond those implied by the cost and value size limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisConfig {
+    /// The maximum number of fields of any tuple type in a contract.
+    pub max_tuple_arity: u64,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> AnalysisConfig {
+        AnalysisConfig {
+            max_tuple_arity: u64::max_value(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ContractAnalysis {
     pub contract_identifier: QualifiedContractIdentifier,
@@ -29,12 +54,18 @@ pub struct ContractAnalysis {
     pub defined_traits: BTreeMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: BTreeSet<TraitIdentifier>,    
     pub contract_interface: Option<ContractInterface>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_size: Option<u64>,
     #[serde(skip)]
     pub expressions: Vec<SymbolicExpression>,
     #[serde(skip)]
     pub type_map: Option<TypeMap>,
     #[serde(skip)]
-    pub cost_track: Option<LimitedCostTracker>
+    pub cost_track: Option<LimitedCostTracker>,
+    #[serde(skip)]
+    pub config: AnalysisConfig,
+    #[serde(skip)]
+    pub pass_costs: Vec<(PassName, ExecutionCost)>,
 }
 
 impl ContractAnalysis {
@@ -44,6 +75,7 @@ impl ContractAnalysis {
             expressions,
             type_map: None,
             contract_interface: None,
+            contract_size: None,
             private_function_types: BTreeMap::new(),
             public_function_types: BTreeMap::new(),
             read_only_function_types: BTreeMap::new(),
@@ -54,7 +86,9 @@ impl ContractAnalysis {
             implemented_traits: BTreeSet::new(),
             fungible_tokens: BTreeSet::new(),
             non_fungible_tokens: BTreeMap::new(),
-            cost_track: Some(cost_track)
+            cost_track: Some(cost_track),
+            config: AnalysisConfig::default(),
+            pass_costs: Vec::new(),
         }
     }
 
@@ -68,6 +102,39 @@ impl ContractAnalysis {
         self.cost_track.replace(cost_track);
     }
 
+    /// Run the analysis pass `P`, recording the cost it charged to this analysis' cost tracker.
+    pub fn run_costed_pass<P: AnalysisPass>(&mut self, name: PassName, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
+        let total_before = self.cost_track_total();
+        P::run_pass(self, analysis_db)?;
+        let pass_cost = self.cost_track_total().saturating_sub(&total_before);
+        self.pass_costs.push((name, pass_cost));
+        Ok(())
+    }
+
+    fn cost_track_total(&self) -> ExecutionCost {
+        self.cost_track.as_ref()
+            .map(|cost_track| cost_track.get_total())
+            .unwrap_or_else(ExecutionCost::zero)
+    }
+
+    /// The cost charged by each analysis pass that completed, in the order the passes ran.
+    ///   Empty for an analysis that was loaded from the database.
+    pub fn pass_costs(&self) -> Vec<(PassName, ExecutionCost)> {
+        self.pass_costs.clone()
+    }
+
+    /// Record the length of the contract source, the size metric used when charging
+    ///   for parsing and storing the contract.
+    pub fn set_contract_size(&mut self, source_code: &str) {
+        self.contract_size = Some(source_code.len() as u64);
+    }
+
+    /// The size this contract is accounted at: the byte length of its source code.
+    ///   None if the analysis was produced without access to the source.
+    pub fn accounted_size(&self) -> Option<u64> {
+        self.contract_size
+    }
+
     pub fn add_map_type(&mut self, name: ClarityName, key_type: TypeSignature, map_type: TypeSignature) {
         self.map_types.insert(name, (key_type, map_type));
     }
@@ -136,6 +203,86 @@ impl ContractAnalysis {
         self.defined_traits.get(name)
     }
 
+    /// Returns each defined trait's name along with its function signatures, as
+    ///   (function name, argument types, return type) triples.
+    /// Trait-typed arguments are returned as TraitReferenceType signatures.
+    pub fn get_defined_trait_signatures(&self) -> Vec<(ClarityName, Vec<(ClarityName, Vec<TypeSignature>, TypeSignature)>)> {
+        self.defined_traits.iter()
+            .map(|(trait_name, functions)| {
+                let signatures = functions.iter()
+                    .map(|(function_name, signature)| {
+                        (function_name.clone(), signature.args.clone(), signature.returns.clone())
+                    })
+                    .collect();
+                (trait_name.clone(), signatures)
+            })
+            .collect()
+    }
+
+    /// Returns each data var's name and declared type, in name order.
+    pub fn data_vars(&self) -> Vec<(ClarityName, TypeSignature)> {
+        self.persisted_variable_types.iter()
+            .map(|(name, var_type)| (name.clone(), var_type.clone()))
+            .collect()
+    }
+
+    /// Returns each public function's name along with its arguments, in the order
+    ///   they were declared in the `define-public`, and its return type.
+    pub fn public_function_args(&self) -> Vec<(ClarityName, Vec<FunctionArg>, TypeSignature)> {
+        self.public_function_types.iter()
+            .filter_map(|(name, function_type)| match function_type {
+                FunctionType::Fixed(function) => Some((name.clone(), function.args.clone(), function.returns.clone())),
+                _ => None
+            })
+            .collect()
+    }
+
+    /// Returns the contracts this contract references from other issuers, either as the target
+    ///   of a `contract-call?` or through a trait identifier (`use-trait`, `impl-trait`).
+    /// References resolved against this contract's own issuer, which includes every sugared
+    ///   `.contract-name` reference, are returned by `deployer_dependencies` instead.
+    pub fn external_dependencies(&self) -> Vec<QualifiedContractIdentifier> {
+        self.referenced_contracts().into_iter()
+            .filter(|contract| contract.issuer != self.contract_identifier.issuer)
+            .collect()
+    }
+
+    /// Returns the contracts this contract references which share its issuer, and so can only
+    ///   be checked once the deployer of the contract is known.
+    pub fn deployer_dependencies(&self) -> Vec<QualifiedContractIdentifier> {
+        self.referenced_contracts().into_iter()
+            .filter(|contract| contract.issuer == self.contract_identifier.issuer)
+            .collect()
+    }
+
+    fn referenced_contracts(&self) -> BTreeSet<QualifiedContractIdentifier> {
+        let mut contracts = BTreeSet::new();
+        for expression in self.expressions.iter() {
+            depth_traverse::<_, _, ()>(expression, |expr| {
+                match expr.expr {
+                    SymbolicExpressionType::Field(ref trait_identifier) => {
+                        contracts.insert(trait_identifier.contract_identifier.clone());
+                    },
+                    SymbolicExpressionType::List(ref list) => {
+                        let is_contract_call = list.first()
+                            .and_then(|function| function.match_atom())
+                            .and_then(|name| NativeFunctions::lookup_by_name(name))
+                            .is_some_and(|function| match function { NativeFunctions::ContractCall => true, _ => false });
+                        if is_contract_call {
+                            if let Some(Value::Principal(PrincipalData::Contract(contract))) = list.get(1).and_then(|target| target.match_literal_value()) {
+                                contracts.insert(contract.clone());
+                            }
+                        }
+                    },
+                    _ => {}
+                }
+                Ok(())
+            }).ok();
+        }
+        contracts.remove(&self.contract_identifier);
+        contracts
+    }
+
     pub fn check_trait_compliance(&self, trait_identifier: &TraitIdentifier, trait_definition: &BTreeMap<ClarityName, FunctionSignature>) -> CheckResult<()> {
 
         let trait_name = trait_identifier.name.to_string(); 
@@ -0,0 +1,93 @@
+use vm::representations::SymbolicExpression;
+use vm::representations::SymbolicExpressionType::{Atom, AtomValue, LiteralValue};
+use vm::functions::NativeFunctions;
+use vm::types::Value;
+use vm::diagnostic::Diagnostic;
+use super::native_application;
+
+#[cfg(test)]
+mod tests;
+
+///
+/// Advisory check for dead code: reports the expressions of a `begin` which follow an
+///   expression that always exits early, such as `(asserts! false ..)` or `(try! (err ..))`.
+///   Unlike the analysis passes, this check never fails a contract: it only returns warnings,
+///   and is not run by `run_analysis`.
+///
+/// An expression is only considered to always exit when that follows from its syntax alone:
+///   it is an `asserts!` of `false`, an `unwrap!`, `unwrap-panic` or `try!` of `none` or an
+///   `(err ..)` literal, an `unwrap-err!` or `unwrap-err-panic` of an `(ok ..)` literal, or a
+///   `begin` containing such an expression.
+pub fn check_unreachable_code(expressions: &[SymbolicExpression]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for expression in expressions.iter() {
+        check_expression(expression, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn check_expression(expression: &SymbolicExpression, diagnostics: &mut Vec<Diagnostic>) {
+    if let Some(list) = expression.match_list() {
+        if let Some((NativeFunctions::Begin, args)) = native_application(list) {
+            if let Some(exit_position) = args.iter().position(always_exits) {
+                let unreachable = &args[exit_position + 1..];
+                if !unreachable.is_empty() {
+                    diagnostics.push(Diagnostic::warning(
+                        "unreachable code: the preceding expression in 'begin' always exits early".to_string(),
+                        unreachable.iter().map(|e| e.span.clone()).collect()));
+                }
+            }
+        }
+
+        for item in list.iter() {
+            check_expression(item, diagnostics);
+        }
+    }
+}
+
+fn always_exits(expression: &SymbolicExpression) -> bool {
+    use vm::functions::NativeFunctions::*;
+
+    let (function, args) = match expression.match_list().and_then(native_application) {
+        Some(application) => application,
+        None => return false
+    };
+    match function {
+        Asserts => args.first().map(is_false).unwrap_or(false),
+        UnwrapRet | Unwrap | TryRet => match args.first() {
+            Some(input) => is_none(input) || match applied_native(input) {
+                Some(ConsError) => true,
+                _ => false
+            },
+            None => false
+        },
+        UnwrapErrRet | UnwrapErr => match args.first().and_then(applied_native) {
+            Some(ConsOkay) => true,
+            _ => false
+        },
+        Begin => args.iter().any(always_exits),
+        _ => false
+    }
+}
+
+fn applied_native(expression: &SymbolicExpression) -> Option<NativeFunctions> {
+    expression.match_list()
+        .and_then(native_application)
+        .map(|(function, _)| function)
+}
+
+fn is_none(expression: &SymbolicExpression) -> bool {
+    match expression.expr {
+        Atom(ref name) => name.as_str() == "none",
+        AtomValue(ref value) | LiteralValue(ref value) => *value == Value::none(),
+        _ => false
+    }
+}
+
+fn is_false(expression: &SymbolicExpression) -> bool {
+    match expression.expr {
+        Atom(ref name) => name.as_str() == "false",
+        AtomValue(ref value) | LiteralValue(ref value) => *value == Value::Bool(false),
+        _ => false
+    }
+}
@@ -0,0 +1,73 @@
+use vm::ast::parse;
+use vm::analysis::unreachable_code_checker::check_unreachable_code;
+use vm::analysis::mem_type_check;
+use vm::diagnostic::Level;
+use vm::types::QualifiedContractIdentifier;
+
+fn check(contract: &str) -> Vec<(u32, u32)> {
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let expressions = parse(&contract_identifier, contract).unwrap();
+    check_unreachable_code(&expressions).iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.level, Level::Warning);
+            (diagnostic.spans[0].start_line, diagnostic.spans.len() as u32)
+        })
+        .collect()
+}
+
+#[test]
+fn test_unreachable_after_early_exit() {
+    let contract = "(define-data-var counter int 0)
+(define-public (bump)
+  (begin
+    (asserts! false (err u1))
+    (var-set counter 1)
+    (ok true)))";
+
+    // the dead code type checks, so it is only reported by the advisory check
+    mem_type_check(contract).unwrap();
+
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let diagnostics = check_unreachable_code(&parse(&contract_identifier, contract).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, Level::Warning);
+    assert_eq!(diagnostics[0].message, "unreachable code: the preceding expression in 'begin' always exits early");
+    let lines: Vec<_> = diagnostics[0].spans.iter().map(|span| span.start_line).collect();
+    assert_eq!(lines, vec![5, 6]);
+}
+
+#[test]
+fn test_always_exiting_expressions() {
+    let dead_code = [
+        "(begin (asserts! false (err u1)) (ok u2))",
+        "(begin (unwrap! none (err u1)) (ok u2))",
+        "(begin (unwrap-panic (err u1)) (ok u2))",
+        "(begin (unwrap-err! (ok u1) (err u1)) (ok u2))",
+        "(begin (unwrap-err-panic (ok u1)) (ok u2))",
+        "(begin (begin (print u1) (try! none)) (ok u2))",
+    ];
+    for contract in dead_code.iter() {
+        assert_eq!(check(contract), vec![(1, 1)], "{}", contract);
+    }
+
+    // nested begins are checked, too
+    assert_eq!(check("(define-read-only (f)\n  (if true (begin (asserts! false (err u1)) (print u1) (ok u2)) (ok u3)))"),
+               vec![(2, 2)]);
+}
+
+#[test]
+fn test_reachable_code() {
+    let reachable = [
+        "(begin (asserts! true (err u1)) (ok u2))",
+        "(begin (try! (ok u1)) (ok u2))",
+        "(begin (unwrap! (some u1) (err u1)) (ok u2))",
+        // an (err ..) value is not an exit
+        "(begin (err u1) (ok u2))",
+        // an always-exiting last expression leaves nothing unreachable
+        "(begin (print u1) (try! (err u1)))",
+        "(define-private (f (x (optional int))) (begin (unwrap! x (err u1)) (ok u2)))",
+    ];
+    for contract in reachable.iter() {
+        assert_eq!(check(contract), vec![], "{}", contract);
+    }
+}
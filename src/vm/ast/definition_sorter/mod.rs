@@ -55,7 +55,9 @@ impl <'a> DefinitionSorter {
         let mut walker = GraphWalker::new();
         let sorted_indexes = walker.get_sorted_dependencies(&self.graph)?;
         
-        if let Some(deps) = walker.get_cycling_dependencies(&self.graph, &sorted_indexes) {
+        if let Some(mut deps) = walker.get_cycling_dependencies(&self.graph, &sorted_indexes) {
+            // report the definitions in source order
+            deps.sort();
             let mut deps_props = vec![];
             for i in deps.iter() {
                 let exp = &contract_ast.pre_expressions[*i];
@@ -64,8 +66,10 @@ impl <'a> DefinitionSorter {
                 }
             }
             let functions_names = deps_props.iter().map(|i| i.0.to_string()).collect();
+            let defined_names = deps_props.iter().map(|i| i.2.clone()).collect();
 
-            let error = ParseError::new(ParseErrors::CircularReference(functions_names));
+            let mut error = ParseError::new(ParseErrors::CircularReference(functions_names));
+            error.set_pre_expressions(defined_names);
             return Err(error)
         }
 
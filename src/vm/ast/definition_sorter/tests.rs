@@ -68,6 +68,25 @@ fn should_raise_dependency_cycle_case_1() {
     assert!(match err.err { ParseErrors::CircularReference(_) => true, _ => false });
 }
 
+#[test]
+fn should_report_dependency_cycle_spans() {
+    let contract = "(define-private (is-even (x int)) (if (is-eq x 0) true (is-odd (- x 1))))
+(define-private (is-odd (x int)) (if (is-eq x 0) false (is-even (- x 1))))";
+
+    let err = run_scoped_parsing_helper(contract).unwrap_err();
+    assert_eq!(err.err, ParseErrors::CircularReference(vec!["is-even".to_string(), "is-odd".to_string()]));
+    let spans: Vec<_> = err.diagnostic.spans.iter()
+        .map(|span| (span.start_line, span.start_column, span.end_column))
+        .collect();
+    assert_eq!(spans, vec![(1, 18, 24), (2, 18, 23)]);
+
+    let self_referential = "(define-private (loop (x int)) (loop x))";
+    let err = run_scoped_parsing_helper(self_referential).unwrap_err();
+    assert_eq!(err.err, ParseErrors::CircularReference(vec!["loop".to_string()]));
+    let span = &err.diagnostic.spans[0];
+    assert_eq!((span.start_line, span.start_column, span.end_column), (1, 18, 21));
+}
+
 #[test]
 fn should_raise_dependency_cycle_case_2() {
     let contract = r#"
@@ -3,7 +3,7 @@ use vm::diagnostic::{Diagnostic, DiagnosableError};
 use vm::types::{TypeSignature, TupleTypeSignature};
 use vm::MAX_CALL_STACK_DEPTH;
 use std::error;
-use std::fmt;
+use std::{fmt, cmp};
 use vm::costs::{ExecutionCost, CostErrors};
 
 pub type ParseResult <T> = Result<T, ParseError>;
@@ -18,15 +18,22 @@ pub enum ParseErrors {
     FailedCapturingInput,
     SeparatorExpected(String),
     ProgramTooLarge,
-    IllegalVariableName(String),
+    EmptyProgram,
+    IllegalVariableName { name: String, line: u32, column: u32 },
+    ReservedName(String),
     IllegalContractName(String),
     UnknownQuotedValue(String),
     FailedParsingIntValue(String),
+    IntLiteralOutOfRange { literal: String, line: u32, column: u32 },
+    NonCanonicalIntLiteral(String),
     FailedParsingBuffer(String),
+    BufferLiteralTooLarge { bytes: usize, limit: u32 },
     FailedParsingHexValue(String, String),
     FailedParsingPrincipal(String),
     FailedParsingField(String),
     FailedParsingRemainder(String),
+    UnterminatedStringLiteral { line: u32, column: u32 },
+    InvalidUtf8Boundary(usize),
     ClosingParenthesisUnexpected,
     ClosingParenthesisExpected,
     ClosingTupleLiteralUnexpected,
@@ -38,8 +45,8 @@ pub enum ParseErrors {
     DefineTraitBadSignature,
     ImplTraitBadSignature,
     TraitReferenceUnknown(String),
-    CommaSeparatorUnexpected,
-    ColonSeparatorUnexpected,
+    CommaSeparatorUnexpected { line: u32, column: u32 },
+    ColonSeparatorUnexpected { line: u32, column: u32 },
 }
 
 #[derive(Debug, PartialEq)]
@@ -49,6 +56,12 @@ pub struct ParseError {
     pub diagnostic: Diagnostic,
 }
 
+impl From<ParseError> for Diagnostic {
+    fn from(e: ParseError) -> Self {
+        e.diagnostic
+    }
+}
+
 impl ParseError {
     pub fn new(err: ParseErrors) -> ParseError {
         let diagnostic = Diagnostic::err(&err);
@@ -124,21 +137,28 @@ impl DiagnosableError for ParseErrors {
             ParseErrors::FailedCapturingInput => format!("Failed to capture value from input"),
             ParseErrors::SeparatorExpected(found) => format!("Expected whitespace or a close parens. Found: '{}'", found),
             ParseErrors::ProgramTooLarge => format!("Program too large to parse"),
+            ParseErrors::EmptyProgram => "Program contains no expressions".to_string(),
             ParseErrors::IllegalContractName(contract_name) => format!("Illegal contract name: '{}'", contract_name),
-            ParseErrors::IllegalVariableName(var_name) => format!("Illegal variable name: '{}'", var_name),
+            ParseErrors::IllegalVariableName { name, line, column } => format!("Illegal variable name: '{}' at line {}, column {}.", name, line, column),
+            ParseErrors::ReservedName(name) => format!("Reserved name '{}' cannot be used as a binding", name),
             ParseErrors::UnknownQuotedValue(value) => format!("Unknown 'quoted value '{}'", value),
             ParseErrors::FailedParsingIntValue(value) => format!("Failed to parse int literal '{}'", value),
+            ParseErrors::IntLiteralOutOfRange { literal, line, column } => format!("Int literal '{}' at line {}, column {} does not fit in 128 bits", literal, line, column),
+            ParseErrors::NonCanonicalIntLiteral(value) => format!("Int literal '{}' is not in canonical form (no leading zeros or negative zero)", value),
             ParseErrors::FailedParsingHexValue(value, x) => format!("Invalid hex-string literal {}: {}", value, x),
             ParseErrors::FailedParsingPrincipal(value) => format!("Invalid principal literal: {}", value),
             ParseErrors::FailedParsingBuffer(value) => format!("Invalid buffer literal: {}", value),
+            ParseErrors::BufferLiteralTooLarge { bytes, limit } => format!("Buffer literal of {} bytes exceeds the maximum value size of {} bytes", bytes, limit),
             ParseErrors::FailedParsingField(value) => format!("Invalid field literal: {}", value),
             ParseErrors::FailedParsingRemainder(remainder) => format!("Failed to lex input remainder: '{}'", remainder),
+            ParseErrors::UnterminatedStringLiteral { line, column } => format!("String literal opened at line {}, column {} is never closed", line, column),
+            ParseErrors::InvalidUtf8Boundary(index) => format!("Lexer stopped inside a multi-byte character at byte {}", index),
             ParseErrors::ClosingParenthesisUnexpected => format!("Tried to close list which isn't open."),
             ParseErrors::ClosingParenthesisExpected => format!("List expressions (..) left opened."),
             ParseErrors::ClosingTupleLiteralUnexpected => format!("Tried to close tuple literal which isn't open."),
             ParseErrors::ClosingTupleLiteralExpected => format!("Tuple literal {{..}} left opened."),
-            ParseErrors::ColonSeparatorUnexpected => format!("Misplaced colon."),
-            ParseErrors::CommaSeparatorUnexpected => format!("Misplaced comma."),
+            ParseErrors::ColonSeparatorUnexpected { line, column } => format!("Misplaced colon at line {}, column {}.", line, column),
+            ParseErrors::CommaSeparatorUnexpected { line, column } => format!("Misplaced comma at line {}, column {}.", line, column),
             ParseErrors::CircularReference(function_names) => format!("detected interdependent functions ({})", function_names.join(", ")),
             ParseErrors::NameAlreadyUsed(name) => format!("defining '{}' conflicts with previous value", name),
             ParseErrors::ImportTraitBadSignature => format!("(use-trait ...) expects a trait name and a trait identifier"),
@@ -156,3 +176,73 @@ impl DiagnosableError for ParseErrors {
         }
     }
 }
+
+const TAB_WIDTH: usize = 4;
+
+/// Render a parse error with the offending source line and a `^` under the error's column:
+///
+/// ```text
+/// error: Misplaced colon at line 2, column 8.
+///   --> line 2, column 8
+///   |
+/// 2 |     { a: b: })
+///   |           ^
+/// ```
+///
+/// Columns count bytes, so the caret is placed by counting the characters ahead of the
+///   column, and tabs are expanded in both the line and the caret padding. Errors without
+///   a position (or positioned outside the source) only render their message.
+pub fn render_parse_error(source: &str, err: &ParseError) -> String {
+    let mut rendered = format!("error: {}", err.diagnostic.message);
+    let span = match err.diagnostic.spans.first() {
+        Some(span) if span.start_line > 0 && span.start_column > 0 => span,
+        _ => return rendered
+    };
+    let line = match source.lines().nth(span.start_line as usize - 1) {
+        Some(line) => line,
+        None => return rendered
+    };
+
+    let mut column_index = cmp::min(span.start_column as usize - 1, line.len());
+    while !line.is_char_boundary(column_index) {
+        column_index -= 1;
+    }
+    let expand_tabs = |text: &str| text.replace('\t', &" ".repeat(TAB_WIDTH));
+    let caret_offset = expand_tabs(&line[..column_index]).chars().count();
+
+    let gutter = " ".repeat(span.start_line.to_string().len());
+    rendered.push_str(&format!("\n{} --> line {}, column {}", gutter, span.start_line, span.start_column));
+    rendered.push_str(&format!("\n{} |", gutter));
+    rendered.push_str(&format!("\n{} | {}", span.start_line, expand_tabs(line)));
+    rendered.push_str(&format!("\n{} | {}^", gutter, " ".repeat(caret_offset)));
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::ast::parser;
+    use vm::representations::Span;
+
+    #[test]
+    fn test_render_parse_error() {
+        let source = "(begin\n\t{ a: b: })";
+        let err = parser::parse(source).unwrap_err();
+        let rendered = render_parse_error(source, &err);
+        assert_eq!(rendered, "error: Misplaced colon at line 2, column 8.
+  --> line 2, column 8
+  |
+2 |     { a: b: })
+  |           ^");
+
+        // columns count bytes: the caret is placed after the multi-byte characters.
+        let mut err = ParseError::new(ParseErrors::FailedParsingIntValue("1x".into()));
+        err.diagnostic.spans = vec![Span { start_line: 1, start_column: 10, end_line: 1, end_column: 11 }];
+        let rendered = render_parse_error("(\"été\" 1x)", &err);
+        assert!(rendered.ends_with("1 | (\"été\" 1x)\n  |        ^"));
+
+        // without a position, only the message is rendered.
+        let err = ParseError::new(ParseErrors::ProgramTooLarge);
+        assert_eq!(render_parse_error(source, &err), format!("error: {}", err.diagnostic.message));
+    }
+}
@@ -10,12 +10,15 @@ pub mod stack_depth_checker;
 use vm::errors::{Error, RuntimeErrorType};
 use vm::costs::{cost_functions, CostTracker};
 
-use vm::representations::{SymbolicExpression};
+use vm::representations::{SymbolicExpression, PreSymbolicExpression};
+use vm::diagnostic::DiagnosticSink;
 use vm::types::QualifiedContractIdentifier;
 
+use std::time::{Duration, Instant};
+
 pub use self::types::ContractAST;
 use self::types::BuildASTPass;
-use self::errors::ParseResult;
+use self::errors::{ParseResult, ParseError};
 use self::expression_identifier::ExpressionIdentifier;
 use self::sugar_expander::SugarExpander;
 use self::definition_sorter::DefinitionSorter;
@@ -30,24 +33,196 @@ pub fn parse(contract_identifier: &QualifiedContractIdentifier, source_code: &st
 }
 
 pub fn build_ast<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str, cost_track: &mut T) -> ParseResult<ContractAST> {
+    inner_build_ast(contract_identifier, source_code, cost_track, false, None)
+}
+
+/// Build the AST in strict mode: see `parser::parse_strict`.
+pub fn build_ast_strict<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str, cost_track: &mut T) -> ParseResult<ContractAST> {
+    inner_build_ast(contract_identifier, source_code, cost_track, true, None)
+}
+
+/// Build the AST, reporting problems to `diagnostics` rather than stopping at the first one.
+/// When one of the AST passes fails, the top-level expressions containing the error are dropped
+///   and the remaining expressions are tried again, so that later problems are reported too.
+///   The AST built from the remaining expressions is returned, or None if the source
+///   could not be parsed at all.
+pub fn build_ast_with_diagnostics<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str,
+                                                  cost_track: &mut T, diagnostics: &mut DiagnosticSink) -> Option<ContractAST> {
+    let cost_result = runtime_cost!(cost_functions::AST_PARSE, cost_track, source_code.len() as u64);
+    diagnostics.capture(cost_result.map_err(ParseError::from))?;
+    let mut pre_expressions = diagnostics.capture(parser::parse(source_code))?;
+    loop {
+        match build_ast_from_pre_expressions(contract_identifier, pre_expressions.clone(), cost_track) {
+            Ok(contract_ast) => return Some(contract_ast),
+            Err(e) => {
+                let error_spans = e.diagnostic.spans.clone();
+                diagnostics.push(e.into());
+                let expressions_count = pre_expressions.len();
+                pre_expressions.retain(|expr| !error_spans.iter().any(|span| expr.span().contains(span)));
+                if pre_expressions.len() == expressions_count {
+                    return None
+                }
+            }
+        }
+    }
+}
+
+/// Parse the source into pre-expressions with their identifiers assigned (see `SymbolicExpression::id`),
+///   without running the remaining AST passes.
+pub fn parse_with_ids(source_code: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
+    let mut pre_expressions = parser::parse(source_code)?;
+    expression_identifier::update_expression_id(&mut pre_expressions)?;
+    Ok(pre_expressions)
+}
+
+/// The time spent in each phase of building an AST, as measured by `build_ast_timed`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhaseTimings {
+    pub lex: Duration,
+    pub parse: Duration,
+    pub stack_depth: Duration,
+    /// both expression identifier passes: before and after the sugar is expanded.
+    pub expression_identify: Duration,
+    pub definition_sort: Duration,
+    pub traits_resolve: Duration,
+    pub sugar_expand: Duration,
+}
+
+impl PhaseTimings {
+    /// The phases with their timings, in the order they are run.
+    pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+        vec![("lex", self.lex),
+             ("parse", self.parse),
+             ("stack-depth", self.stack_depth),
+             ("expression-identify", self.expression_identify),
+             ("definition-sort", self.definition_sort),
+             ("traits-resolve", self.traits_resolve),
+             ("sugar-expand", self.sugar_expand)]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases().iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+
+    // the timing of a pass of the pipeline, if it is one of the measured phases
+    fn pass_mut(&mut self, pass: &ASTPass) -> Option<&mut Duration> {
+        match pass {
+            ASTPass::StackDepthCheck => Some(&mut self.stack_depth),
+            ASTPass::IdentifyPreExpressions | ASTPass::IdentifyExpressions => Some(&mut self.expression_identify),
+            ASTPass::SortDefinitions => Some(&mut self.definition_sort),
+            ASTPass::ResolveTraits => Some(&mut self.traits_resolve),
+            ASTPass::ExpandSugar => Some(&mut self.sugar_expand),
+            ASTPass::Custom(_) => None
+        }
+    }
+}
+
+// runs `f`, adding the time it took to `elapsed` if there is one to measure.
+fn timed<R, F: FnOnce() -> R>(elapsed: Option<&mut Duration>, f: F) -> R {
+    match elapsed {
+        Some(elapsed) => {
+            let start = Instant::now();
+            let result = f();
+            *elapsed += start.elapsed();
+            result
+        },
+        None => f()
+    }
+}
+
+/// Build the AST like `build_ast` (or `build_ast_strict`, if `strict` is set), additionally
+///   measuring the time spent in each phase. This is for profiling the compiler: `build_ast`
+///   itself does not read the clock.
+pub fn build_ast_timed<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str,
+                                       cost_track: &mut T, strict: bool) -> ParseResult<(ContractAST, PhaseTimings)> {
+    let mut timings = PhaseTimings::default();
+    let contract_ast = inner_build_ast(contract_identifier, source_code, cost_track, strict, Some(&mut timings))?;
+    Ok((contract_ast, timings))
+}
+
+fn inner_build_ast<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str, cost_track: &mut T,
+                                   strict: bool, mut timings: Option<&mut PhaseTimings>) -> ParseResult<ContractAST> {
     runtime_cost!(cost_functions::AST_PARSE, cost_track, source_code.len() as u64)?;
-    let pre_expressions = parser::parse(source_code)?;
-    let mut contract_ast = ContractAST::new(contract_identifier.clone(), pre_expressions);
-    StackDepthChecker::run_pass(&mut contract_ast)?;
-    ExpressionIdentifier::run_pre_expression_pass(&mut contract_ast)?;
-    DefinitionSorter::run_pass(&mut contract_ast, cost_track)?;
-    TraitsResolver::run_pass(&mut contract_ast)?;
-    SugarExpander::run_pass(&mut contract_ast)?;
-    ExpressionIdentifier::run_expression_pass(&mut contract_ast)?;
+    let lexed = timed(timings.as_mut().map(|timings| &mut timings.lex), || if strict {
+        parser::lex_strict(source_code)
+    } else {
+        parser::lex(source_code)
+    })?;
+    let pre_expressions = timed(timings.as_mut().map(|timings| &mut timings.parse), || if strict {
+        parser::parse_lexed_strict(lexed)
+    } else {
+        parser::parse_lexed(lexed)
+    })?;
+    run_passes(ContractAST::new(contract_identifier.clone(), pre_expressions), cost_track, &standard_passes(), timings)
+}
+
+fn build_ast_from_pre_expressions<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, pre_expressions: Vec<PreSymbolicExpression>, cost_track: &mut T) -> ParseResult<ContractAST> {
+    run_passes(ContractAST::new(contract_identifier.clone(), pre_expressions), cost_track, &standard_passes(), None)
+}
+
+fn run_passes<T: CostTracker>(mut contract_ast: ContractAST, cost_track: &mut T, passes: &[ASTPass],
+                              mut timings: Option<&mut PhaseTimings>) -> ParseResult<ContractAST> {
+    for pass in passes.iter() {
+        let elapsed = timings.as_mut().and_then(|timings| timings.pass_mut(pass));
+        timed(elapsed, || match pass {
+            ASTPass::StackDepthCheck => StackDepthChecker::run_pass(&mut contract_ast),
+            ASTPass::IdentifyPreExpressions => ExpressionIdentifier::run_pre_expression_pass(&mut contract_ast),
+            ASTPass::SortDefinitions => DefinitionSorter::run_pass(&mut contract_ast, cost_track),
+            ASTPass::ResolveTraits => TraitsResolver::run_pass(&mut contract_ast),
+            ASTPass::ExpandSugar => SugarExpander::run_pass(&mut contract_ast),
+            ASTPass::IdentifyExpressions => ExpressionIdentifier::run_expression_pass(&mut contract_ast),
+            ASTPass::Custom(run_pass) => run_pass(&mut contract_ast),
+        })?;
+    }
     Ok(contract_ast)
 }
 
+/// A pass of the pipeline that turns parsed pre-expressions into the contract's expressions.
+///   The standard passes must keep the order of `standard_passes()`:
+/// * `StackDepthCheck` runs first, as the other passes recurse into the expressions.
+/// * `IdentifyPreExpressions` must precede `SortDefinitions`, which tells definitions from
+///   references by their ids.
+/// * `ExpandSugar` turns the pre-expressions into expressions, so it runs after all the passes
+///   over pre-expressions, and only `IdentifyExpressions` (and passes over `expressions`) follow it.
+///
+/// Custom passes rewriting pre-expressions belong right after `StackDepthCheck`, so that the
+///   rewritten expressions are identified, sorted and expanded like parsed ones. A custom
+///   pass must not nest expressions deeper than the stack depth check allows.
+pub enum ASTPass {
+    StackDepthCheck,
+    IdentifyPreExpressions,
+    SortDefinitions,
+    ResolveTraits,
+    ExpandSugar,
+    IdentifyExpressions,
+    Custom(Box<dyn Fn(&mut ContractAST) -> ParseResult<()>>),
+}
+
+/// The passes run by `build_ast`, in order.
+pub fn standard_passes() -> Vec<ASTPass> {
+    vec![ASTPass::StackDepthCheck,
+         ASTPass::IdentifyPreExpressions,
+         ASTPass::SortDefinitions,
+         ASTPass::ResolveTraits,
+         ASTPass::ExpandSugar,
+         ASTPass::IdentifyExpressions]
+}
+
+/// Build the AST like `build_ast`, but running the given `passes` rather than `standard_passes()`.
+pub fn build_ast_with_passes<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str,
+                                             cost_track: &mut T, passes: &[ASTPass]) -> ParseResult<ContractAST> {
+    runtime_cost!(cost_functions::AST_PARSE, cost_track, source_code.len() as u64)?;
+    let pre_expressions = parser::parse(source_code)?;
+    run_passes(ContractAST::new(contract_identifier.clone(), pre_expressions), cost_track, passes, None)
+}
+
 #[cfg(test)]
 mod tests {
     use vm::costs::LimitedCostTracker;
     use vm::representations::depth_traverse;
     use std::collections::HashMap;
     use super::*;
+    use vm::ast::errors::ParseErrors;
 
     fn dependency_edge_counting_runtime(iters: usize) -> u64 {
         let mut progn = "(define-private (a0) 1)".to_string();
@@ -95,4 +270,152 @@ mod tests {
             }).unwrap();
         }
     }
+
+    #[test]
+    fn test_build_ast_timed() {
+        let mut progn = "(define-private (a0) 1)".to_string();
+        for i in 1..50 {
+            progn.push_str(&format!("\n(define-private (a{}) (+ (a{}) {}))", i, i - 1, i));
+        }
+
+        let (timed_ast, timings) = build_ast_timed(&QualifiedContractIdentifier::transient(), &progn, &mut (), false).unwrap();
+        let ast = build_ast(&QualifiedContractIdentifier::transient(), &progn, &mut ()).unwrap();
+        assert_eq!(timed_ast.expressions, ast.expressions);
+
+        let phases: Vec<_> = timings.phases().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(phases, vec!["lex", "parse", "stack-depth", "expression-identify",
+                                "definition-sort", "traits-resolve", "sugar-expand"]);
+        assert!(timings.total() > Duration::from_secs(0));
+
+        // the strict pipeline applies the strict parser's checks
+        assert!(build_ast_timed(&QualifiedContractIdentifier::transient(), "(+ 1 007)", &mut (), false).is_ok());
+        assert_eq!(build_ast_timed(&QualifiedContractIdentifier::transient(), "(+ 1 007)", &mut (), true).unwrap_err().err,
+                   ParseErrors::NonCanonicalIntLiteral("007".to_string()));
+    }
+
+    #[test]
+    fn test_build_ast_with_custom_pass() {
+        use vm::representations::PreSymbolicExpressionType::{Atom, List};
+
+        fn rename(expression: &mut PreSymbolicExpression, from: &str, to: &str) {
+            match expression.pre_expr {
+                Atom(ref mut name) if name.as_str() == from => *name = to.into(),
+                List(ref mut items) => for item in items.iter_mut() { rename(item, from, to) },
+                _ => {}
+            }
+        }
+
+        let progn = "(define-private (double (x int)) (* 2 x))
+(legacy-double 3)";
+        let contract_identifier = QualifiedContractIdentifier::transient();
+
+        let mut passes = standard_passes();
+        passes.insert(1, ASTPass::Custom(Box::new(|contract_ast: &mut ContractAST| {
+            for expression in contract_ast.pre_expressions.iter_mut() {
+                rename(expression, "legacy-double", "double");
+            }
+            Ok(())
+        })));
+        let ast = build_ast_with_passes(&contract_identifier, progn, &mut (), &passes).unwrap();
+
+        let call = ast.expressions[1].match_list().unwrap();
+        assert_eq!(call[0].match_atom().unwrap().as_str(), "double");
+        // the renamed atom keeps its source span
+        assert_eq!(call[0].span.end_column, 14);
+
+        // the standard passes build the same AST as build_ast
+        assert_eq!(build_ast_with_passes(&contract_identifier, progn, &mut (), &standard_passes()).unwrap().expressions,
+                   build_ast(&contract_identifier, progn, &mut ()).unwrap().expressions);
+    }
+
+    #[test]
+    fn test_stable_expression_ids() {
+        let progn = "(define-private (double (x int))
+  (* 2 x))
+(print { a: (double 1), b: (list 1 2) })";
+
+        fn pre_expression_ids(exprs: &[PreSymbolicExpression], ids: &mut Vec<u64>) {
+            for expr in exprs.iter() {
+                ids.push(expr.id());
+                if let Some(list) = expr.match_list() {
+                    pre_expression_ids(list, ids);
+                }
+            }
+        }
+
+        let mut first = vec![];
+        pre_expression_ids(&parse_with_ids(progn).unwrap(), &mut first);
+        let mut second = vec![];
+        pre_expression_ids(&parse_with_ids(progn).unwrap(), &mut second);
+        assert_eq!(first, second);
+        // ids are assigned in document order
+        assert_eq!(first, (1..(first.len() as u64 + 1)).collect::<Vec<_>>());
+
+        let expression_ids = |ast: &ContractAST| {
+            let mut ids = vec![];
+            for expr in ast.expressions.iter() {
+                depth_traverse::<_,_,()>(expr, |x| { ids.push(x.id()); Ok(()) }).unwrap();
+            }
+            ids.sort();
+            ids
+        };
+        let first = build_ast(&QualifiedContractIdentifier::transient(), &progn, &mut ()).unwrap();
+        let second = build_ast(&QualifiedContractIdentifier::transient(), &progn, &mut ()).unwrap();
+        assert_eq!(expression_ids(&first), expression_ids(&second));
+        assert!(!expression_ids(&first).contains(&0));
+    }
+
+    #[test]
+    #[cfg(feature = "developer-mode")]
+    fn test_source_map() {
+        use vm::ast::types::DefinitionName;
+
+        let progn = "(define-private (double (x int))
+  (* 2 x))
+(print (quadruple 1))
+(define-read-only (quadruple (x int))
+  (double
+    (double x)))";
+
+        let ast = build_ast(&QualifiedContractIdentifier::transient(), &progn, &mut ()).unwrap();
+        let lines: Vec<_> = ast.source_map().into_iter()
+            .map(|(name, span)| (name, span.start_line, span.end_line))
+            .collect();
+
+        // definitions are sorted ahead of their uses.
+        assert_eq!(lines, vec![(DefinitionName::Named("double".into()), 1, 2),
+                               (DefinitionName::Named("quadruple".into()), 4, 6),
+                               (DefinitionName::Anonymous(2), 3, 3)]);
+    }
+
+    #[test]
+    fn test_structural_hash() {
+        let contract_id = QualifiedContractIdentifier::transient();
+        let hash = |source: &str| {
+            build_ast(&contract_id, source, &mut ()).unwrap().structural_hash()
+        };
+
+        let original = "(define-data-var counter int 0)
+(define-public (incr (by int))
+  (begin (var-set counter (+ (var-get counter) by))
+         (ok (var-get counter))))";
+        let reformatted = ";; a counter
+(define-data-var counter
+   int 0)
+
+(define-public (incr (by int)) ;; increments the counter
+        (begin
+          (var-set counter (+ (var-get counter) by))
+          (ok (var-get counter))))";
+        let changed = "(define-data-var counter int 0)
+(define-public (incr (by int))
+  (begin (var-set counter (- (var-get counter) by))
+         (ok (var-get counter))))";
+
+        assert_eq!(hash(original), hash(reformatted));
+        assert_ne!(hash(original), hash(changed));
+        // nesting is part of the structure
+        assert_ne!(hash("(list 1 (list 2 3))"), hash("(list (list 1 2) 3)"));
+        assert_ne!(hash("u1"), hash("1"));
+    }
 }
@@ -1,12 +1,16 @@
 use std::cmp;
 use std::convert::TryInto;
+use std::num::{IntErrorKind, ParseIntError};
 use util::hash::hex_bytes;
 use regex::{Regex, Captures};
 use address::c32::c32_address_decode;
 use vm::ast::errors::{ParseResult, ParseErrors, ParseError};
 use vm::errors::{RuntimeErrorType, InterpreterResult as Result};
-use vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType, ContractName, ClarityName, MAX_STRING_LEN};
-use vm::types::{Value, PrincipalData, TraitIdentifier, QualifiedContractIdentifier};
+use vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType, ContractName, ClarityName, Span, MAX_STRING_LEN};
+use vm::types::{Value, PrincipalData, TraitIdentifier, QualifiedContractIdentifier, MAX_VALUE_SIZE};
+use vm::functions::NativeFunctions;
+use vm::functions::define::DefineFunctions;
+use vm::is_reserved;
 
 pub const CONTRACT_MIN_NAME_LENGTH : usize = 5;
 pub const CONTRACT_MAX_NAME_LENGTH : usize = 40;
@@ -24,6 +28,7 @@ pub enum LexItem {
     Variable(String),
     CommaSeparator,
     ColonSeparator,
+    Directive(String),
     Whitespace
 }
 
@@ -39,6 +44,7 @@ enum TokenType {
     FullyQualifiedContractIdentifierLiteral,
     SugaredFieldIdentifierLiteral,
     FullyQualifiedFieldIdentifierLiteral,
+    Directive,
 }
 
 struct LexMatcher {
@@ -71,6 +77,53 @@ fn get_value_or_err(input: &str, captures: Captures) -> ParseResult<String> {
     Ok(input[matched.start()..matched.end()].to_string())
 }
 
+// a buffer literal larger than a value may be is reported with its size,
+//   separately from other buffer literal failures.
+fn parse_buffer_literal(literal: &str, bytes: Vec<u8>) -> ParseResult<Value> {
+    if bytes.len() > MAX_VALUE_SIZE as usize {
+        return Err(ParseError::new(ParseErrors::BufferLiteralTooLarge { bytes: bytes.len(), limit: MAX_VALUE_SIZE }))
+    }
+    Value::buff_from(bytes)
+        .map_err(|_| ParseError::new(ParseErrors::FailedParsingBuffer(literal.to_string())))
+}
+
+// a literal which is a well-formed integer, but too large for 128 bits, is reported
+//   separately from malformed literals, at the literal's position.
+fn int_literal_error(literal: String, error: ParseIntError, line: u32, column: u32) -> ParseError {
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+            positioned_error(ParseErrors::IntLiteralOutOfRange { literal, line, column }, line, column)
+        },
+        _ => ParseError::new(ParseErrors::FailedParsingIntValue(literal))
+    }
+}
+
+fn positioned_error(error: ParseErrors, line: u32, column: u32) -> ParseError {
+    let mut error = ParseError::new(error);
+    error.diagnostic.spans = vec![Span { start_line: line, start_column: column,
+                                         end_line: line, end_column: column }];
+    error
+}
+
+fn illegal_variable_name(name: String, line: u32, column: u32) -> ParseError {
+    positioned_error(ParseErrors::IllegalVariableName { name, line, column }, line, column)
+}
+
+// in strict mode, integer literals must be written in their canonical form: no
+//   leading zeros (except for a lone zero), and no negative zero.
+fn is_canonical_int_literal(literal: &str) -> bool {
+    let (negative, digits) = if literal.starts_with('-') {
+        (true, &literal[1..])
+    } else {
+        (false, literal)
+    };
+    if digits.len() > 1 && digits.starts_with('0') {
+        false
+    } else {
+        !(negative && digits == "0")
+    }
+}
+
 fn get_lines_at(input: &str) -> Vec<usize> {
     let mut out: Vec<_> = input.match_indices("\n")
         .map(|(ix, _)| ix)
@@ -90,11 +143,19 @@ lazy_static! {
 }
 
 pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+    lex_with_directives(input, None, false)
+}
+
+/// Lex the input, additionally recognizing `directive_prefix` (if given) as the start of a directive:
+///   like a `;;` comment, it runs to the end of the line, and its text after the prefix is captured
+///   in a LexItem::Directive.
+/// If `strict` is set, int and uint literals which are not in canonical form are rejected.
+fn lex_with_directives(input: &str, directive_prefix: Option<&str>, strict: bool) -> ParseResult<Vec<(LexItem, u32, u32)>> {
     // Aaron: I'd like these to be static, but that'd require using
     //    lazy_static (or just hand implementing that), and I'm not convinced
     //    it's worth either (1) an extern macro, or (2) the complexity of hand implementing.
 
-    let lex_matchers: &[LexMatcher] = &[
+    let mut lex_matchers = vec![
         LexMatcher::new(r##""(?P<value>((\\")|([[ -~]&&[^"]]))*)""##, TokenType::StringLiteral),
         LexMatcher::new(";;[ -~]*", TokenType::Whitespace), // ;; comments.
         LexMatcher::new("[\n]+", TokenType::Whitespace),
@@ -119,6 +180,10 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
         LexMatcher::new(&format!("(?P<value>{})", *CLARITY_NAME_REGEX), TokenType::Variable),
     ];
 
+    if let Some(prefix) = directive_prefix {
+        lex_matchers.insert(0, LexMatcher::new(&format!("{}(?P<value>[ -~]*)", regex::escape(prefix)), TokenType::Directive));
+    }
+
     let mut context = LexContext::ExpectNothing;
 
     let mut line_indices = get_lines_at(input);
@@ -140,7 +205,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
         }
 
         did_match = false;
-        let current_slice = &input[munch_index..];
+        let current_slice = slice_from(input, munch_index)?;
         for matcher in lex_matchers.iter() {
             if let Some(captures) = matcher.matcher.captures(current_slice) {
                 let whole_match = captures.get(0).unwrap();
@@ -157,9 +222,10 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             TokenType::RParens => Ok(()),
                             TokenType::RCurly => Ok(()),
                             TokenType::Whitespace => Ok(()),
+                            TokenType::Directive => Ok(()),
                             TokenType::Comma => Ok(()),
                             TokenType::Colon => Ok(()),
-                            _ => Err(ParseError::new(ParseErrors::SeparatorExpected(current_slice[..whole_match.end()].to_string())))
+                            _ => Err(ParseError::new(ParseErrors::SeparatorExpected(slice_to(current_slice, whole_match.end())?.to_string())))
                         }
                     }
                 }?;
@@ -179,6 +245,11 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                         context = LexContext::ExpectNothing;
                         Ok(LexItem::Whitespace)
                     },
+                    TokenType::Directive => {
+                        context = LexContext::ExpectNothing;
+                        let value = get_value_or_err(current_slice, captures)?;
+                        Ok(LexItem::Directive(value))
+                    },
                     TokenType::Comma => {
                         context = LexContext::ExpectNothing;
                         Ok(LexItem::CommaSeparator)
@@ -197,24 +268,30 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                     TokenType::Variable => {
                         let value = get_value_or_err(current_slice, captures)?;
                         if value.contains("#") {
-                            Err(ParseError::new(ParseErrors::IllegalVariableName(value)))
+                            Err(illegal_variable_name(value, current_line, column_pos))
                         } else {
                             Ok(LexItem::Variable(value))
                         }
                     },
                     TokenType::UIntLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
+                        if strict && !is_canonical_int_literal(&str_value) {
+                            return Err(ParseError::new(ParseErrors::NonCanonicalIntLiteral(format!("u{}", str_value))))
+                        }
                         let value = match u128::from_str_radix(&str_value, 10) {
                             Ok(parsed) => Ok(Value::UInt(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(str_value.clone())))
+                            Err(e) => Err(int_literal_error(format!("u{}", str_value), e, current_line, column_pos))
                         }?;
                         Ok(LexItem::LiteralValue(str_value.len(), value))
                     },
                     TokenType::IntLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
+                        if strict && !is_canonical_int_literal(&str_value) {
+                            return Err(ParseError::new(ParseErrors::NonCanonicalIntLiteral(str_value)))
+                        }
                         let value = match i128::from_str_radix(&str_value, 10) {
                             Ok(parsed) => Ok(Value::Int(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(str_value.clone())))
+                            Err(e) => Err(int_literal_error(str_value.clone(), e, current_line, column_pos))
                         }?;
                         Ok(LexItem::LiteralValue(str_value.len(), value))
                     },
@@ -261,17 +338,14 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                     TokenType::TraitReferenceLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
                         let data = str_value.clone().try_into()
-                            .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(str_value.to_string())) })?;
+                            .map_err(|_| { illegal_variable_name(str_value.to_string(), current_line, column_pos) })?;
                         Ok(LexItem::TraitReference(str_value.len(), data))
                     },
                     TokenType::HexStringLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
                         let byte_vec = hex_bytes(&str_value)
                             .map_err(|x| { ParseError::new(ParseErrors::FailedParsingHexValue(str_value.clone(), x.to_string())) })?;
-                        let value = match Value::buff_from(byte_vec) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(str_value.clone())))
-                        }?;
+                        let value = parse_buffer_literal(&str_value, byte_vec)?;
                         Ok(LexItem::LiteralValue(str_value.len(), value))
                     },
                     TokenType::StringLiteral => {
@@ -279,10 +353,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                         let quote_unescaped = str_value.replace("\\\"","\"");
                         let slash_unescaped = quote_unescaped.replace("\\\\","\\");
                         let byte_vec = slash_unescaped.as_bytes().to_vec();
-                        let value = match Value::buff_from(byte_vec) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(str_value.clone())))
-                        }?;
+                        let value = parse_buffer_literal(&str_value, byte_vec)?;
                         Ok(LexItem::LiteralValue(str_value.len(), value))
                     },
                 }?;
@@ -296,12 +367,39 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
     }
 
     if munch_index == input.len() {
-        Ok(result)
+        return Ok(result)
+    }
+
+    let remainder = slice_from(input, munch_index)?;
+    if is_unterminated_string(remainder) {
+        let mut error = ParseError::new(ParseErrors::UnterminatedStringLiteral { line: current_line, column: column_pos });
+        error.diagnostic.spans = vec![Span { start_line: current_line, start_column: column_pos,
+                                             end_line: current_line, end_column: column_pos }];
+        Err(error)
     } else {
-        Err(ParseError::new(ParseErrors::FailedParsingRemainder(input[munch_index..].to_string())))
+        Err(positioned_error(ParseErrors::FailedParsingRemainder(remainder.to_string()), current_line, column_pos))
     }
 }
 
+// the lexer only ever advances by whole regex matches, which end on character boundaries,
+//   but guard the slicing anyways: a bad index should be a parse error, not a panic.
+fn slice_from(input: &str, index: usize) -> ParseResult<&str> {
+    input.get(index..)
+        .ok_or_else(|| ParseError::new(ParseErrors::InvalidUtf8Boundary(index)))
+}
+
+fn slice_to(input: &str, index: usize) -> ParseResult<&str> {
+    input.get(..index)
+        .ok_or_else(|| ParseError::new(ParseErrors::InvalidUtf8Boundary(index)))
+}
+
+// a string literal is unterminated if there is no closing quote before the end of its line.
+//   otherwise, the string literal failed to lex for some other reason (e.g., it contains an illegal character).
+fn is_unterminated_string(remainder: &str) -> bool {
+    remainder.starts_with('"') &&
+        !remainder[1..].split('\n').next().unwrap_or("").contains('"')
+}
+
 pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
     let mut parse_stack = Vec::new();
 
@@ -369,7 +467,7 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
             LexItem::Variable(value) => {
                 let end_column = column_pos + (value.len() as u32) - 1;
                 let value = value.clone().try_into()
-                    .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(value.to_string())) })?;
+                    .map_err(|_| { illegal_variable_name(value.to_string(), line_pos, column_pos) })?;
                 let mut pre_expr = PreSymbolicExpression::atom(value);
                 pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
 
@@ -437,7 +535,7 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
             LexItem::TraitReference(_length, value) => {
                 let end_column = column_pos + (value.len() as u32) - 1;
                 let value = value.clone().try_into()
-                    .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(value.to_string())) })?;
+                    .map_err(|_| { illegal_variable_name(value.to_string(), line_pos, column_pos) })?;
                 let mut pre_expr = PreSymbolicExpression::trait_reference(value);
                 pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
 
@@ -448,33 +546,34 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
             }
             LexItem::ColonSeparator => {
                 match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected)),
+                    None => return Err(positioned_error(ParseErrors::ColonSeparatorUnexpected { line: line_pos, column: column_pos }, line_pos, column_pos)),
                     Some((ref mut list, _, _, parse_context)) => {
                         if let ParseContext::CollectTuple = parse_context {
                             if list.len() % 2 == 0 {
-                                return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected))
+                                return Err(positioned_error(ParseErrors::ColonSeparatorUnexpected { line: line_pos, column: column_pos }, line_pos, column_pos))
                             }
                         } else {
-                            return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected))
+                            return Err(positioned_error(ParseErrors::ColonSeparatorUnexpected { line: line_pos, column: column_pos }, line_pos, column_pos))
                         }
                     }
                 };
             }
             LexItem::CommaSeparator => {
                 match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected)),
+                    None => return Err(positioned_error(ParseErrors::CommaSeparatorUnexpected { line: line_pos, column: column_pos }, line_pos, column_pos)),
                     Some((ref mut list, _, _, parse_context)) => {
                         if let ParseContext::CollectTuple = parse_context {
                             if list.len() == 0 || list.len() % 2 == 1 {
-                                return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected))
+                                return Err(positioned_error(ParseErrors::CommaSeparatorUnexpected { line: line_pos, column: column_pos }, line_pos, column_pos))
                             }
                         } else {
-                            return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected))
+                            return Err(positioned_error(ParseErrors::CommaSeparatorUnexpected { line: line_pos, column: column_pos }, line_pos, column_pos))
                         }
                     }
                 };
             },
-            LexItem::Whitespace => ()
+            LexItem::Whitespace => (),
+            LexItem::Directive(_) => ()
         };
     }
 
@@ -491,10 +590,121 @@ pub fn parse(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
     parse_lexed(lexed)
 }
 
+/// Parse the input, treating directives starting with `directive_prefix` (e.g., `#pragma`)
+///   as comments. Returns the parsed expressions along with the text of each directive
+///   following the prefix, in the order they appear.
+pub fn parse_with_directives(input: &str, directive_prefix: &str) -> ParseResult<(Vec<PreSymbolicExpression>, Vec<String>)> {
+    let lexed = lex_with_directives(input, Some(directive_prefix), false)?;
+    let directives = lexed.iter()
+        .filter_map(|(item, _, _)| match item {
+            LexItem::Directive(directive) => Some(directive.clone()),
+            _ => None
+        })
+        .collect();
+    Ok((parse_lexed(lexed)?, directives))
+}
+
+/// Parse the input in strict mode: in addition to the usual parsing, this rejects
+///   reserved names (e.g., `map` or `block-height`) used in binding positions, i.e., as
+///   `let` variables, as defined names, or as function parameters.
+/// Reserved names remain legal in the call position of a list.
+/// Strict mode also rejects programs without any expressions (only whitespace and comments),
+///   and int literals which are not in canonical form (e.g., `007`, `-0` or `u05`).
+pub fn parse_strict(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
+    parse_lexed_strict(lex_strict(input)?)
+}
+
+/// Lex the input for `parse_strict`, rejecting int literals which are not in canonical form.
+pub fn lex_strict(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+    lex_with_directives(input, None, true)
+}
+
+/// Parse the output of `lex_strict`, applying the remaining checks of `parse_strict`.
+pub fn parse_lexed_strict(input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+    let pre_expressions = parse_lexed(input)?;
+    if pre_expressions.is_empty() {
+        return Err(ParseError::new(ParseErrors::EmptyProgram))
+    }
+    check_reserved_bindings(&pre_expressions)?;
+    Ok(pre_expressions)
+}
+
+fn check_binding_name(expression: &PreSymbolicExpression) -> ParseResult<()> {
+    match expression.match_atom() {
+        Some(name) if is_reserved(name) => Err(ParseError::new(ParseErrors::ReservedName(name.to_string()))),
+        _ => Ok(())
+    }
+}
+
+// checks the binding positions of a single list expression, e.g., the names
+//   bound in (let ((a 1) (b 2)) ...) or (define-private (foo (a int)) ...)
+fn check_list_bindings(list: &[PreSymbolicExpression]) -> ParseResult<()> {
+    let (head, args) = match list.split_first() {
+        Some((head, args)) => (head, args),
+        None => return Ok(())
+    };
+    let head = match head.match_atom() {
+        Some(head) => head,
+        None => return Ok(())
+    };
+
+    if let Some(NativeFunctions::Let) = NativeFunctions::lookup_by_name(head) {
+        if let Some(bindings) = args.first().and_then(|x| x.match_list()) {
+            for binding in bindings.iter() {
+                if let Some(name) = binding.match_list().and_then(|x| x.first()) {
+                    check_binding_name(name)?;
+                }
+            }
+        }
+        return Ok(())
+    }
+
+    match DefineFunctions::lookup_by_name(head) {
+        Some(DefineFunctions::PrivateFunction) | Some(DefineFunctions::PublicFunction) | Some(DefineFunctions::ReadOnlyFunction) => {
+            if let Some((function_name, parameters)) = args.first().and_then(|x| x.match_list()).and_then(|x| x.split_first()) {
+                check_binding_name(function_name)?;
+                for parameter in parameters.iter() {
+                    if let Some(name) = parameter.match_list().and_then(|x| x.first()) {
+                        check_binding_name(name)?;
+                    }
+                }
+            }
+        },
+        Some(DefineFunctions::Constant) | Some(DefineFunctions::Map) | Some(DefineFunctions::PersistedVariable) |
+        Some(DefineFunctions::FungibleToken) | Some(DefineFunctions::NonFungibleToken) | Some(DefineFunctions::Trait) |
+        Some(DefineFunctions::UseTrait) => {
+            if let Some(name) = args.first() {
+                check_binding_name(name)?;
+            }
+        },
+        Some(DefineFunctions::ImplTrait) | None => {}
+    }
+    Ok(())
+}
+
+fn check_reserved_bindings(pre_expressions: &[PreSymbolicExpression]) -> ParseResult<()> {
+    // walk the expressions with an explicit stack: the stack depth checker has not run yet,
+    //   so recursion could overflow on deeply nested input.
+    let mut stack: Vec<_> = pre_expressions.iter().collect();
+    while let Some(expression) = stack.pop() {
+        match expression.pre_expr {
+            PreSymbolicExpressionType::List(ref list) => {
+                check_list_bindings(list)?;
+                stack.extend(list.iter());
+            },
+            PreSymbolicExpressionType::Tuple(ref list) => {
+                stack.extend(list.iter());
+            },
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 
 #[cfg(test)]
 mod test {
-    use vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType};
+    use vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType, Span};
     use vm::{Value, ast};
     use vm::types::{QualifiedContractIdentifier, PrincipalData};
     use vm::ast::errors::{ParseErrors, ParseError};
@@ -581,6 +791,37 @@ r#"z (let ((x 1) (y 2))
 
     }
 
+    #[test]
+    #[cfg(feature = "developer-mode")]
+    fn test_span_accessors() {
+        let input =
+r#"z (let ((x 1) (y 2))
+    (+ x ;; "comments section?"
+        ;; this is also a comment!
+        (let ((x 3)) ;; more commentary
+        (+ x y))
+        x)) x y
+        ;; this is 'quoted comment!"#;
+
+        fn coordinates(span: &Span) -> (u32, u32, u32, u32) {
+            (span.start_line, span.start_column, span.end_line, span.end_column)
+        }
+
+        let parsed = ast::parser::parse(&input).unwrap();
+        assert_eq!(coordinates(parsed[0].span()), (1, 1, 1, 1));
+        assert_eq!(coordinates(parsed[1].span()), (1, 3, 6, 11));
+        assert_eq!(coordinates(parsed[3].span()), (6, 15, 6, 15));
+
+        let let_body = parsed[1].match_list().unwrap()[2].match_list().unwrap();
+        assert_eq!(coordinates(let_body[0].span()), (2, 6, 2, 6));
+        assert_eq!(coordinates(let_body[2].span()), (4, 9, 5, 16));
+
+        let expressions = ast::parse(&QualifiedContractIdentifier::transient(), &input).unwrap();
+        assert_eq!(coordinates(expressions[1].span()), (1, 3, 6, 11));
+        let let_body = expressions[1].match_list().unwrap()[2].match_list().unwrap();
+        assert_eq!(coordinates(let_body[2].span()), (4, 9, 5, 16));
+    }
+
     #[test]
     fn test_parse_tuple_literal () {
       let input = "{id 1337}";
@@ -589,6 +830,13 @@ r#"z (let ((x 1) (y 2))
                               make_atom_value(Value::Int(1337), 1, 5, 1, 8)]))];
       let parsed = ast::parser::parse(&input);
       assert_eq!(Ok(program), parsed, "Should match expected tuple literal");
+
+      let parsed = parsed.unwrap();
+      let tuple = parsed[0].match_tuple().unwrap();
+      assert_eq!(tuple.len(), 2);
+      assert_eq!(tuple[0].match_atom(), Some(&"id".into()));
+      assert_eq!(tuple[1].match_atom_value(), Some(&Value::Int(1337)));
+      assert!(parsed[0].match_list().is_none());
     }
 
     #[test]
@@ -650,6 +898,76 @@ r#"z (let ((x 1) (y 2))
         });
     }
 
+    #[test]
+    fn test_parse_strict_reserved_names() {
+        let good = [
+            // reserved names are fine in the call position, and in non-strict mode
+            "(map not (list true false))",
+            "(let ((a 1) (b block-height)) (+ a b))",
+            "(define-private (foo (a int) (b (list 5 int))) (map + b b))",
+            "{ map: 1, list: 2 }"];
+
+        for program in good.iter() {
+            assert!(ast::parser::parse_strict(program).is_ok());
+        }
+
+        let bad = [
+            ("(let ((map 1)) map)", "map"),
+            ("(let ((a 1)) (let ((block-height 2)) a))", "block-height"),
+            ("(define-private (foo (list int)) list)", "list"),
+            ("(define-public (filter (a int)) (ok a))", "filter"),
+            ("(define-constant tx-sender 1)", "tx-sender"),
+            ("{ a: (let ((fold 1)) fold) }", "fold")];
+
+        for (program, name) in bad.iter() {
+            assert!(ast::parser::parse(program).is_ok());
+            assert_eq!(ast::parser::parse_strict(program).unwrap_err().err,
+                       ParseErrors::ReservedName(name.to_string()));
+        }
+
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        assert!(ast::build_ast(&contract_identifier, bad[0].0, &mut ()).is_ok());
+        assert_eq!(ast::build_ast_strict(&contract_identifier, bad[0].0, &mut ()).unwrap_err().err,
+                   ParseErrors::ReservedName("map".to_string()));
+        assert!(ast::build_ast_strict(&contract_identifier, good[2], &mut ()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_strict_empty_program() {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        for program in ["", "  \n\t  \n", ";; nothing to see here\n  ;; or here"].iter() {
+            assert_eq!(ast::parser::parse(program).unwrap(), vec![]);
+            assert_eq!(ast::build_ast(&contract_identifier, program, &mut ()).unwrap().expressions, vec![]);
+
+            assert_eq!(ast::parser::parse_strict(program).unwrap_err().err, ParseErrors::EmptyProgram);
+            assert_eq!(ast::build_ast_strict(&contract_identifier, program, &mut ()).unwrap_err().err,
+                       ParseErrors::EmptyProgram);
+        }
+        assert!(ast::parser::parse_strict(";; one expression\n(+ 1 2)").is_ok());
+    }
+
+    #[test]
+    fn test_parse_non_canonical_ints() {
+        assert_eq!(ast::parser::parse_strict("0").unwrap()[0].match_atom_value(), Some(&Value::Int(0)));
+        assert_eq!(ast::parser::parse_strict("u0").unwrap()[0].match_atom_value(), Some(&Value::UInt(0)));
+        assert_eq!(ast::parser::parse_strict("-10").unwrap()[0].match_atom_value(), Some(&Value::Int(-10)));
+
+        assert_eq!(ast::parser::parse_strict("-0").unwrap_err().err,
+                   ParseErrors::NonCanonicalIntLiteral("-0".to_string()));
+        assert_eq!(ast::parser::parse_strict("-00").unwrap_err().err,
+                   ParseErrors::NonCanonicalIntLiteral("-00".to_string()));
+        assert_eq!(ast::parser::parse_strict("(+ 1 007)").unwrap_err().err,
+                   ParseErrors::NonCanonicalIntLiteral("007".to_string()));
+        assert_eq!(ast::parser::parse_strict("u05").unwrap_err().err,
+                   ParseErrors::NonCanonicalIntLiteral("u05".to_string()));
+
+        // the default parser still normalizes them
+        assert_eq!(ast::parser::parse("-0").unwrap()[0].match_atom_value(), Some(&Value::Int(0)));
+        assert_eq!(ast::parser::parse("(+ 1 007)").unwrap()[0].match_list().unwrap()[2].match_atom_value(),
+                   Some(&Value::Int(7)));
+        assert_eq!(ast::parser::parse("u05").unwrap()[0].match_atom_value(), Some(&Value::UInt(5)));
+    }
+
     #[test]
     fn test_parse_failures() {
         use vm::errors::{Error, RuntimeErrorType};
@@ -691,8 +1009,16 @@ r#"z (let ((x 1) (y 2))
         assert!(match ast::parser::parse(&middle_hash).unwrap_err().err {
             ParseErrors::FailedParsingRemainder(_) => true, _ => false });
 
-        assert!(match ast::parser::parse(&unicode).unwrap_err().err {
+        // the lexer cannot match the note, so it reports the unlexed remainder, from the note on.
+        let err = ast::parser::parse(&unicode).unwrap_err();
+        assert!(match err.err {
             ParseErrors::FailedParsingRemainder(_) => true, _ => false });
+        assert_eq!((err.diagnostic.spans[0].start_line, err.diagnostic.spans[0].start_column), (1, 9));
+
+        let long_trait_name = format!("(define-public (f (x int)\n  (t <{}>)) (ok x))", "a".repeat(129));
+        let err = ast::parser::parse(&long_trait_name).unwrap_err();
+        assert_eq!(err.err, ParseErrors::IllegalVariableName { name: "a".repeat(129), line: 2, column: 6 });
+        assert_eq!((err.diagnostic.spans[0].start_line, err.diagnostic.spans[0].start_column), (2, 6));
 
         assert!(match ast::parser::parse(&name_with_dot).unwrap_err().err {
             ParseErrors::FailedParsingRemainder(_) => true, _ => false });
@@ -707,25 +1033,27 @@ r#"z (let ((x 1) (y 2))
             ParseErrors::ClosingTupleLiteralUnexpected => true, _ => false });
 
         assert!(match ast::parser::parse(&unexpected_comma).unwrap_err().err {
-            ParseErrors::CommaSeparatorUnexpected => true, _ => false });
+            ParseErrors::CommaSeparatorUnexpected { line: 1, column: 12 } => true, _ => false });
 
         assert!(match ast::parser::parse(&shorthand_tuple).unwrap_err().err {
-            ParseErrors::CommaSeparatorUnexpected => true, _ => false });
+            ParseErrors::CommaSeparatorUnexpected { .. } => true, _ => false });
 
         assert!(match ast::parser::parse(&shorthand_tuple_dangling_comma).unwrap_err().err {
-            ParseErrors::CommaSeparatorUnexpected => true, _ => false });
+            ParseErrors::CommaSeparatorUnexpected { .. } => true, _ => false });
 
         assert!(match ast::parser::parse(&decorative_colon_on_value).unwrap_err().err {
-            ParseErrors::ColonSeparatorUnexpected => true, _ => false });
+            ParseErrors::ColonSeparatorUnexpected { line: 1, column: 7 } => true, _ => false });
 
-        assert!(match ast::parser::parse(&tuple_literal_colon_after_comma).unwrap_err().err {
-            ParseErrors::ColonSeparatorUnexpected => true, _ => false });
+        let error = ast::parser::parse(&tuple_literal_colon_after_comma).unwrap_err();
+        assert!(match error.err {
+            ParseErrors::ColonSeparatorUnexpected { line: 1, column: 9 } => true, _ => false });
+        assert_eq!(error.diagnostic.spans[0].start_column, 9);
 
         assert!(match ast::parser::parse(&empty_tuple_literal_comma).unwrap_err().err {
-            ParseErrors::CommaSeparatorUnexpected => true, _ => false });
+            ParseErrors::CommaSeparatorUnexpected { .. } => true, _ => false });
 
         assert!(match ast::parser::parse(&empty_tuple_literal_colon).unwrap_err().err {
-            ParseErrors::ColonSeparatorUnexpected => true, _ => false });
+            ParseErrors::ColonSeparatorUnexpected { .. } => true, _ => false });
 
         assert!(match ast::parser::parse(&legacy_boolean_literals).unwrap_err().err {
             ParseErrors::FailedParsingRemainder(_) => true, _ => false });
@@ -744,4 +1072,85 @@ r#"z (let ((x 1) (y 2))
         ast::parser::parse(&function_with_LF).unwrap();
     }
 
+    #[test]
+    fn test_parse_with_directives() {
+        let input = "#pragma clarity 1.0
+(define-constant x 1) #pragma trailing
+(+ x 2)";
+        let (directive_parsed, directives) = ast::parser::parse_with_directives(input, "#pragma").unwrap();
+        assert_eq!(directives, vec![" clarity 1.0".to_string(), " trailing".to_string()]);
+
+        // directives are lexed as whitespace, so the expressions are the same as without them.
+        let plain_parsed = ast::parser::parse("\n(define-constant x 1)\n(+ x 2)").unwrap();
+        assert_eq!(directive_parsed, plain_parsed);
+
+        // without the opt-in, directives still fail to lex
+        assert!(match ast::parser::parse(input).unwrap_err().err {
+            ParseErrors::FailedParsingRemainder(_) => true, _ => false });
+    }
+
+    #[test]
+    fn test_unterminated_string_literal() {
+        let error = ast::parser::parse("(foo \"bar)").unwrap_err();
+        assert_eq!(error.err, ParseErrors::UnterminatedStringLiteral { line: 1, column: 6 });
+        assert_eq!(error.diagnostic.spans[0].start_column, 6);
+
+        // the closing quote must be on the same line.
+        assert_eq!(ast::parser::parse("(ok 1)\n(print \"a)\n\"b\")").unwrap_err().err,
+                   ParseErrors::UnterminatedStringLiteral { line: 2, column: 8 });
+
+        // a terminated string literal with an illegal character is a different failure.
+        assert!(match ast::parser::parse("(print \"a\tb\")").unwrap_err().err {
+            ParseErrors::FailedParsingRemainder(_) => true, _ => false });
+    }
+
+    #[test]
+    fn test_int_literal_out_of_range() {
+        // u128::max_value() is 340282366920938463463374607431768211455
+        let error = ast::parser::parse("(ok u340282366920938463463374607431768211456)").unwrap_err();
+        assert_eq!(error.err, ParseErrors::IntLiteralOutOfRange {
+            literal: "u340282366920938463463374607431768211456".to_string(), line: 1, column: 5 });
+        assert_eq!(error.diagnostic.spans[0].start_column, 5);
+        ast::parser::parse("(ok u340282366920938463463374607431768211455)").unwrap();
+
+        // i128::min_value() is -170141183460469231731687303715884105728
+        let error = ast::parser::parse("(list 1\n  -170141183460469231731687303715884105729)").unwrap_err();
+        assert_eq!(error.err, ParseErrors::IntLiteralOutOfRange {
+            literal: "-170141183460469231731687303715884105729".to_string(), line: 2, column: 3 });
+        ast::parser::parse("(list 1\n  -170141183460469231731687303715884105728)").unwrap();
+    }
+
+    #[test]
+    fn test_buffer_literal_too_large() {
+        use vm::types::MAX_VALUE_SIZE;
+
+        let limit = MAX_VALUE_SIZE as usize;
+        let error = ast::parser::parse(&format!("(ok 0x{})", "ab".repeat(limit + 1))).unwrap_err();
+        assert_eq!(error.err, ParseErrors::BufferLiteralTooLarge { bytes: limit + 1, limit: MAX_VALUE_SIZE });
+        assert_eq!(error.diagnostic.message,
+                   format!("Buffer literal of {} bytes exceeds the maximum value size of {} bytes", limit + 1, limit));
+
+        let error = ast::parser::parse(&format!("(ok \"{}\")", "a".repeat(limit + 1))).unwrap_err();
+        assert_eq!(error.err, ParseErrors::BufferLiteralTooLarge { bytes: limit + 1, limit: MAX_VALUE_SIZE });
+
+        ast::parser::parse(&format!("(ok 0x{})", "ab".repeat(limit))).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_utf8_boundary() {
+        use super::{slice_from, slice_to};
+
+        // byte 1 is inside the 4-byte encoding of the note.
+        let input = "🎶(ok 1)";
+        assert_eq!(slice_from(input, 1).unwrap_err().err, ParseErrors::InvalidUtf8Boundary(1));
+        assert_eq!(slice_to(input, 3).unwrap_err().err, ParseErrors::InvalidUtf8Boundary(3));
+
+        assert_eq!(slice_from(input, 4).unwrap(), "(ok 1)");
+        assert_eq!(slice_to(input, 4).unwrap(), "🎶");
+
+        // multi-byte input still fails to lex cleanly.
+        assert!(match ast::parser::parse("(ok 🎶)").unwrap_err().err {
+            ParseErrors::FailedParsingRemainder(_) => true, _ => false });
+    }
+
 }
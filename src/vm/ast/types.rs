@@ -1,15 +1,25 @@
 use std::collections::{HashSet, HashMap};
 use vm::{ClarityName};
 use vm::types::signatures::FunctionSignature;
-use vm::representations::{SymbolicExpression, PreSymbolicExpression, TraitDefinition};
+use vm::representations::{SymbolicExpression, SymbolicExpressionType, PreSymbolicExpression, TraitDefinition, Span};
+use vm::functions::define::DefineFunctions;
 use vm::ast::errors::{ParseResult};
-use vm::types::{QualifiedContractIdentifier, TraitIdentifier};
+use vm::types::{QualifiedContractIdentifier, TraitIdentifier, Value, PrincipalData};
+use util::hash::Sha512Trunc256Sum;
 use std::vec::Drain;
 
 pub trait BuildASTPass {
     fn run_pass(contract_ast: &mut ContractAST) -> ParseResult<()>;
 }
 
+/// Identifies a top-level expression in a source map: definitions are identified by
+///   the name they define, any other expression by its index in `ContractAST::expressions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefinitionName {
+    Named(ClarityName),
+    Anonymous(usize),
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ContractAST {
     pub contract_identifier: QualifiedContractIdentifier,
@@ -48,6 +58,119 @@ impl ContractAST {
     pub fn get_referenced_trait(&self, name: &str) -> Option<&TraitDefinition> {
         self.referenced_traits.get(name)
     }
+
+    /// Map each top-level expression to its source span, in evaluation order.
+    /// Spans are only tracked in developer-mode: otherwise, every span is zero.
+    pub fn source_map(&self) -> Vec<(DefinitionName, Span)> {
+        self.expressions.iter().enumerate()
+            .map(|(index, expression)| {
+                let name = match find_defined_name(expression) {
+                    Some(name) => DefinitionName::Named(name.clone()),
+                    None => DefinitionName::Anonymous(index)
+                };
+                (name, expression.span().clone())
+            })
+            .collect()
+    }
+
+    /// Hash the shape of the expression tree, ignoring spans and expression ids, so that
+    ///   sources differing only in whitespace or comments hash identically. The encoding
+    ///   is spelled out in `write_structural_encoding` and must not change between versions.
+    pub fn structural_hash(&self) -> [u8; 32] {
+        let mut encoding = vec![];
+        write_u32(&mut encoding, self.expressions.len());
+        for expression in self.expressions.iter() {
+            write_structural_encoding(&mut encoding, expression);
+        }
+        Sha512Trunc256Sum::from_data(&encoding).0
+    }
+}
+
+const STRUCTURAL_TAG_ATOM_VALUE: u8 = 0x00;
+const STRUCTURAL_TAG_ATOM: u8 = 0x01;
+const STRUCTURAL_TAG_LIST: u8 = 0x02;
+const STRUCTURAL_TAG_LITERAL_VALUE: u8 = 0x03;
+const STRUCTURAL_TAG_FIELD: u8 = 0x04;
+const STRUCTURAL_TAG_TRAIT_REFERENCE: u8 = 0x05;
+const STRUCTURAL_TAG_TRAIT_DEFINED: u8 = 0x00;
+const STRUCTURAL_TAG_TRAIT_IMPORTED: u8 = 0x01;
+
+// Encoding of an expression: a tag byte followed by
+//   - atoms: the name, as a u32 length-prefixed byte string
+//   - atom and literal values: the consensus serialization of the value
+//   - lists: the u32 item count, followed by the encoding of each item
+//   - fields: the trait identifier
+//   - trait references: the name, a defined/imported tag byte and the trait identifier
+// where a trait identifier is the consensus serialization of its contract principal,
+//   followed by the trait name. All integers are big-endian.
+fn write_structural_encoding(encoding: &mut Vec<u8>, expression: &SymbolicExpression) {
+    use self::SymbolicExpressionType::*;
+    match expression.expr {
+        AtomValue(ref value) => {
+            encoding.push(STRUCTURAL_TAG_ATOM_VALUE);
+            write_value(encoding, value);
+        },
+        Atom(ref name) => {
+            encoding.push(STRUCTURAL_TAG_ATOM);
+            write_bytes(encoding, name.as_bytes());
+        },
+        List(ref items) => {
+            encoding.push(STRUCTURAL_TAG_LIST);
+            write_u32(encoding, items.len());
+            for item in items.iter() {
+                write_structural_encoding(encoding, item);
+            }
+        },
+        LiteralValue(ref value) => {
+            encoding.push(STRUCTURAL_TAG_LITERAL_VALUE);
+            write_value(encoding, value);
+        },
+        Field(ref trait_identifier) => {
+            encoding.push(STRUCTURAL_TAG_FIELD);
+            write_trait_identifier(encoding, trait_identifier);
+        },
+        TraitReference(ref name, ref definition) => {
+            encoding.push(STRUCTURAL_TAG_TRAIT_REFERENCE);
+            write_bytes(encoding, name.as_bytes());
+            let (tag, trait_identifier) = match definition {
+                TraitDefinition::Defined(trait_identifier) => (STRUCTURAL_TAG_TRAIT_DEFINED, trait_identifier),
+                TraitDefinition::Imported(trait_identifier) => (STRUCTURAL_TAG_TRAIT_IMPORTED, trait_identifier),
+            };
+            encoding.push(tag);
+            write_trait_identifier(encoding, trait_identifier);
+        },
+    }
+}
+
+fn write_u32(encoding: &mut Vec<u8>, value: usize) {
+    encoding.extend_from_slice(&(value as u32).to_be_bytes());
+}
+
+fn write_bytes(encoding: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(encoding, bytes.len());
+    encoding.extend_from_slice(bytes);
+}
+
+fn write_value(encoding: &mut Vec<u8>, value: &Value) {
+    value.serialize_write(encoding)
+        .expect("IOError filling byte buffer.");
+}
+
+fn write_trait_identifier(encoding: &mut Vec<u8>, trait_identifier: &TraitIdentifier) {
+    let contract = Value::Principal(PrincipalData::Contract(trait_identifier.contract_identifier.clone()));
+    write_value(encoding, &contract);
+    write_bytes(encoding, trait_identifier.name.as_bytes());
+}
+
+fn find_defined_name(expression: &SymbolicExpression) -> Option<&ClarityName> {
+    let (define_name, args) = expression.match_list()?.split_first()?;
+    DefineFunctions::lookup_by_name(define_name.match_atom()?)?;
+    let first_arg = args.first()?;
+    let defined_name = match first_arg.match_list() {
+        Some(signature) => signature.first()?,
+        None => first_arg
+    };
+    defined_name.match_atom()
 }
 
 pub struct PreExpressionsDrain {
@@ -1,9 +1,11 @@
 use vm::representations::SymbolicExpression;
-use vm::types::{Value, AssetIdentifier, PrincipalData, QualifiedContractIdentifier, TypeSignature};
+use vm::types::{Value, AssetIdentifier, PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature, FunctionType, FixedFunction,
+                parse_name_type_pairs};
+use vm::functions::define::DefineFunctionsParsed;
 use vm::contexts::{OwnedEnvironment, AssetMap, Environment};
-use vm::database::{MarfedKV, ClarityDatabase, SqliteConnection, HeadersDB, RollbackWrapper, RollbackWrapperPersistedLog};
+use vm::database::{MarfedKV, SpeculativeKV, ClarityBackingStore, ClarityDatabase, SqliteConnection, HeadersDB, CachedHeadersDB, RollbackWrapper, RollbackWrapperPersistedLog};
 use vm::analysis::{AnalysisDatabase};
-use vm::errors::{Error as InterpreterError};
+use vm::errors::{Error as InterpreterError, RuntimeErrorType};
 use vm::ast::{ContractAST, errors::ParseError};
 use vm::analysis::{ContractAnalysis, errors::CheckError, errors::CheckErrors};
 use vm::ast;
@@ -15,6 +17,7 @@ use chainstate::stacks::index::marf::MARF;
 use chainstate::stacks::index::TrieHash;
 use chainstate::stacks::events::StacksTransactionEvent;
 
+use std::collections::BTreeMap;
 use std::error;
 use std::fmt;
 
@@ -37,11 +40,12 @@ pub struct ClarityInstance {
 
 ///
 /// A high-level interface for Clarity VM interactions within a single block.
+///   Block info lookups are cached for the lifetime of the connection.
 ///
 pub struct ClarityBlockConnection<'a> {
     datastore: MarfedKV,
     parent: &'a mut ClarityInstance,
-    header_db: &'a dyn HeadersDB,
+    header_db: CachedHeadersDB<'a>,
     cost_track: Option<LimitedCostTracker>
 }
 
@@ -52,7 +56,7 @@ pub struct ClarityBlockConnection<'a> {
 ///   rollback the transaction by dropping this struct.
 pub struct ClarityTransactionConnection<'a> {
     log: Option<RollbackWrapperPersistedLog>,
-    store: &'a mut MarfedKV,
+    store: &'a mut dyn ClarityBackingStore,
     header_db: &'a dyn HeadersDB,
     cost_track: &'a mut Option<LimitedCostTracker>
 }
@@ -63,6 +67,19 @@ pub struct ClarityReadOnlyConnection<'a> {
     header_db: &'a dyn HeadersDB,
 }
 
+///
+/// Interface for speculatively executing transactions against a fork of the chain state
+///   at a given block. Transactions may be committed to the fork as in a ClarityBlockConnection,
+///   but their writes are held in memory and never reach the instance's datastore:
+///   the fork is thrown away with .discard().
+///
+pub struct SpeculativeConnection<'a> {
+    datastore: SpeculativeKV,
+    parent: &'a mut ClarityInstance,
+    header_db: &'a dyn HeadersDB,
+    cost_track: Option<LimitedCostTracker>
+}
+
 #[derive(Debug)]
 pub enum Error {
     Analysis(CheckError),
@@ -78,6 +95,12 @@ impl From<CheckError> for Error {
     }
 }
 
+impl From<CheckErrors> for Error {
+    fn from(e: CheckErrors) -> Self {
+        Error::Analysis(CheckError::new(e))
+    }
+}
+
 impl From<InterpreterError> for Error {
     fn from(e: InterpreterError) -> Self {
         match &e {
@@ -134,6 +157,22 @@ macro_rules! using {
     }
 }
 
+/// Coerce a single contract-call argument to the expected argument type, if possible.
+fn coerce_argument(function_name: &str, arg_name: &str, expected: &TypeSignature, arg: &Value) -> Result<Value, Error> {
+    match (expected, arg) {
+        (TypeSignature::UIntType, Value::Int(i)) => {
+            if *i < 0 {
+                Err(Error::BadTransaction(format!(
+                    "Cannot coerce argument '{}' of '{}': negative integer {} is not a valid uint",
+                    arg_name, function_name, i)))
+            } else {
+                Ok(Value::UInt(*i as u128))
+            }
+        },
+        _ => Ok(arg.clone())
+    }
+}
+
 impl ClarityInstance {
     pub fn new(datastore: MarfedKV) -> ClarityInstance {
         ClarityInstance { datastore: Some(datastore) }
@@ -150,7 +189,7 @@ impl ClarityInstance {
 
         ClarityBlockConnection {
             datastore,
-            header_db,
+            header_db: CachedHeadersDB::new(header_db),
             parent: self,
             cost_track: Some(LimitedCostTracker::new_max_limit())
         }
@@ -167,7 +206,7 @@ impl ClarityInstance {
 
         ClarityBlockConnection {
             datastore,
-            header_db,
+            header_db: CachedHeadersDB::new(header_db),
             parent: self,
             cost_track: Some(LimitedCostTracker::new(limit))
         }
@@ -189,6 +228,32 @@ impl ClarityInstance {
         }
     }
 
+    /// Fork the chain state at `at_block` for speculative execution. Reads of unmodified
+    ///   keys are serviced from the datastore, but all writes stay in the fork.
+    pub fn speculate<'a>(&'a mut self, at_block: &BlockHeaderHash, header_db: &'a dyn HeadersDB) -> SpeculativeConnection<'a> {
+        let datastore = self.datastore.take()
+            // this is a panicking failure: the datastore is only missing while a block or
+            //   speculative connection is open, and those always restore it when they end.
+            .expect("FAIL: use of speculate while a block or speculative connection is still open.");
+
+        SpeculativeConnection {
+            datastore: SpeculativeKV::new(datastore, at_block),
+            header_db,
+            parent: self,
+            cost_track: Some(LimitedCostTracker::new_max_limit())
+        }
+    }
+
+    /// Fetch the stored analysis of `contract` as of `at_block`, or None if no such contract
+    ///   has been deployed.
+    pub fn get_contract_analysis(&mut self, at_block: &BlockHeaderHash, header_db: &dyn HeadersDB,
+                                 contract: &QualifiedContractIdentifier) -> Result<Option<ContractAnalysis>, Error> {
+        let mut conn = self.read_only_connection(at_block, header_db);
+        let contract_analysis = conn.with_analysis_db_readonly(|db| db.load_contract(contract));
+        conn.done();
+        Ok(contract_analysis)
+    }
+
     #[cfg(test)]
     pub fn eval_read_only(&mut self, at_block: &BlockHeaderHash, header_db: &dyn HeadersDB,
                           contract: &QualifiedContractIdentifier, program: &str) -> Result<Value, Error> {
@@ -285,6 +350,55 @@ impl <'a> ClarityReadOnlyConnection <'a> {
     }
 }
 
+impl ClarityConnection for SpeculativeConnection <'_> {
+    /// Do something with ownership of the underlying DB that involves only reading.
+    fn with_clarity_db_readonly_owned<F, R>(&mut self, to_do: F) -> R
+    where F: FnOnce(ClarityDatabase) -> (R, ClarityDatabase) {
+        let mut db = ClarityDatabase::new(&mut self.datastore, &self.header_db);
+        db.begin();
+        let (result, mut db) = to_do(db);
+        db.roll_back();
+        result
+    }
+
+    fn with_analysis_db_readonly<F, R>(&mut self, to_do: F) -> R
+    where F: FnOnce(&mut AnalysisDatabase) -> R {
+        let mut db = AnalysisDatabase::new(&mut self.datastore);
+        db.begin();
+        let result = to_do(&mut db);
+        db.roll_back();
+        result
+    }
+}
+
+impl <'a> SpeculativeConnection <'a> {
+    /// Throw away the fork and every write made in it, returning the
+    ///   datastore to the ClarityInstance.
+    pub fn discard(self) -> LimitedCostTracker {
+        self.parent.datastore.replace(self.datastore.into_inner());
+        self.cost_track.unwrap()
+    }
+
+    pub fn start_transaction_processing <'b> (&'b mut self) -> ClarityTransactionConnection <'b> {
+        let store = &mut self.datastore;
+        let cost_track = &mut self.cost_track;
+        let header_db = &self.header_db;
+        let mut log = RollbackWrapperPersistedLog::new();
+        log.nest();
+        ClarityTransactionConnection {
+            store, cost_track, header_db, log: Some(log)
+        }
+    }
+
+    pub fn as_transaction <F, R> (&mut self, todo: F) -> R
+    where F: FnOnce(&mut ClarityTransactionConnection) -> R {
+        let mut tx = self.start_transaction_processing();
+        let r = todo(&mut tx);
+        tx.commit();
+        r
+    }
+}
+
 impl <'a> ClarityBlockConnection <'a> {
     /// Rolls back all changes in the current block by
     /// (1) dropping all writes from the current MARF tip,
@@ -360,7 +474,104 @@ impl <'a> ClarityBlockConnection <'a> {
         r
     }
 
-    /// Get the MARF root hash
+    /// Write `value` to the data var `var_name` of `contract` in its own transaction, bypassing
+    ///   the contract's functions, e.g. to set up an edge-case state for a test. The value must
+    ///   be admitted by the var's declared type.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn set_data_var(&mut self, contract: &QualifiedContractIdentifier, var_name: &str, value: Value) -> Result<(), Error> {
+        self.as_transaction(|tx| tx.with_clarity_db(|db| {
+            db.set_variable(contract, var_name, value)?;
+            Ok(())
+        }))
+    }
+
+    /// Analyze, save the analysis of, and initialize a contract in a single transaction of the current block.
+    ///  If any step fails, or if abort_call_back returns true after initialization, none of the analysis
+    ///  or the contract state is committed: an aborted deploy returns a BadTransaction error.
+    pub fn deploy_contract <F> (&mut self, identifier: &QualifiedContractIdentifier, contract_content: &str,
+                                abort_call_back: F) -> Result<(ContractAnalysis, AssetMap), Error>
+    where F: FnOnce(&AssetMap, &mut ClarityDatabase) -> bool {
+        let mut tx = self.start_transaction_processing();
+
+        let (contract_ast, contract_analysis) = tx.analyze_smart_contract(identifier, contract_content)?;
+        let mut aborted = false;
+        let (asset_map, _) = tx.initialize_smart_contract(
+            identifier, &contract_ast, contract_content,
+            |asset_map, db| {
+                aborted = abort_call_back(asset_map, db);
+                aborted
+            })?;
+        if aborted {
+            return Err(Error::BadTransaction(format!("Deployment of {} aborted by callback", identifier)))
+        }
+        tx.save_analysis(identifier, &contract_analysis)?;
+
+        // the transaction is only committed once every step succeeded:
+        //   returning early drops (and so rolls back) the transaction.
+        tx.commit();
+        Ok((contract_analysis, asset_map))
+    }
+
+    /// Check that the public and read-only functions defined in `contract_ast` have the
+    ///   signatures required by the trait `trait_identifier`, without running the type checker.
+    /// Because return types are only inferred by the type checker, only the presence and the argument
+    ///   types of each method are checked. Any missing or mismatched methods are reported
+    ///   together in a single IncompleteTraitImplementation error.
+    pub fn check_trait_compliance(&mut self, contract_ast: &ContractAST, trait_identifier: &TraitIdentifier) -> Result<(), Error> {
+        let trait_definition = self.with_analysis_db_readonly(|db| {
+            db.get_defined_trait(&trait_identifier.contract_identifier, &trait_identifier.name)
+        })?.ok_or_else(|| CheckErrors::TraitReferenceUnknown(trait_identifier.name.to_string()))?;
+
+        let mut defined_functions = BTreeMap::new();
+        for expression in contract_ast.expressions.iter() {
+            let signature = match DefineFunctionsParsed::try_parse(expression)? {
+                Some(DefineFunctionsParsed::PublicFunction { signature, .. }) |
+                Some(DefineFunctionsParsed::ReadOnlyFunction { signature, .. }) => signature,
+                _ => continue
+            };
+            let (function_name, arg_symbols) = signature.split_first()
+                .ok_or(CheckErrors::DefineFunctionBadSignature)?;
+            let function_name = function_name.match_atom()
+                .ok_or(CheckErrors::ExpectedName)?;
+            let arg_types: Vec<_> = parse_name_type_pairs(arg_symbols, &mut ())?
+                .into_iter()
+                .map(|(_, arg_type)| arg_type)
+                .collect();
+            defined_functions.insert(function_name.clone(), arg_types);
+        }
+
+        let mut missing = vec![];
+        let mut mismatched = vec![];
+        for (function_name, expected_signature) in trait_definition.iter() {
+            match defined_functions.remove(function_name) {
+                None => missing.push(function_name.to_string()),
+                Some(arg_types) => {
+                    if !expected_signature.check_args_trait_compliance(arg_types) {
+                        mismatched.push(function_name.to_string());
+                    }
+                }
+            }
+        }
+
+        if missing.is_empty() && mismatched.is_empty() {
+            Ok(())
+        } else {
+            Err(CheckErrors::IncompleteTraitImplementation(
+                trait_identifier.name.to_string(), missing, mismatched).into())
+        }
+    }
+
+    /// Run `todo` in a transaction, like `as_transaction`, returning the MARF root hash
+    ///   once the transaction is committed. The block itself is not committed, and a
+    ///   transaction which rolls back its changes leaves the root hash unchanged.
+    pub fn as_transaction_with_root <F, R> (&mut self, todo: F) -> (R, TrieHash)
+    where F: FnOnce(&mut ClarityTransactionConnection) -> R {
+        let result = self.as_transaction(todo);
+        (result, self.get_root_hash())
+    }
+
+    /// Get the MARF root hash. Committed transactions are written to the block's trie
+    ///   as they commit, so within a block this is the root as of the last committed transaction.
     pub fn get_root_hash(&mut self) -> TrieHash {
         self.datastore.get_root_hash()
     }
@@ -416,6 +627,8 @@ impl <'a> ClarityTransactionConnection <'a> {
     }
 
     /// Do something to the underlying DB that involves writing.
+    ///  `to_do` may open nested savepoints with `ClarityDatabase::with_savepoint`:
+    ///  a failed inner savepoint only undoes its own writes.
     pub fn with_clarity_db<F, R>(&mut self, to_do: F) -> Result<R, Error>
     where F: FnOnce(&mut ClarityDatabase) -> Result<R, Error> {
         using!(self.log, "log", |log| {
@@ -454,6 +667,7 @@ impl <'a> ClarityTransactionConnection <'a> {
 
                 match result {
                     Ok(mut contract_analysis) => {
+                        contract_analysis.set_contract_size(contract_content);
                         let cost_track = contract_analysis.take_contract_cost_tracker();
                         (cost_track, Ok((contract_ast, contract_analysis)))
                     },
@@ -544,6 +758,61 @@ impl <'a> ClarityTransactionConnection <'a> {
             abort_call_back)
     }
 
+    /// Execute a contract call in the current block on behalf of a sender with the given account nonce.
+    /// The nonce must match the sender's stored nonce, otherwise a BadNonce error is returned without
+    ///   executing the call. The stored nonce is bumped in the same database transaction as the call,
+    ///   so a call which is rolled back (by a runtime error or the abort callback) does not consume the nonce.
+    pub fn run_contract_call_with_nonce <F> (&mut self, sender: &PrincipalData, nonce: u64, contract: &QualifiedContractIdentifier,
+                                             public_function: &str, args: &[Value], abort_call_back: F) -> Result<(Value, AssetMap, Vec<StacksTransactionEvent>), Error>
+    where F: FnOnce(&AssetMap, &mut ClarityDatabase) -> bool {
+        let expr_args: Vec<_> = args.iter().map(|x| SymbolicExpression::atom_value(x.clone())).collect();
+
+        self.with_abort_callback(
+            |vm_env| {
+                vm_env.execute_in_env(Value::Principal(sender.clone()), |env| {
+                    let expected_nonce = env.global_context.database.get_account_nonce(sender);
+                    if nonce != expected_nonce {
+                        return Err(RuntimeErrorType::BadNonce(expected_nonce, nonce).into())
+                    }
+                    let next_nonce = nonce.checked_add(1)
+                        .ok_or(RuntimeErrorType::ArithmeticOverflow)?;
+                    env.global_context.database.set_account_nonce(sender, next_nonce);
+                    env.execute_contract(contract, public_function, &expr_args, false)
+                })
+                .map_err(Error::from) },
+            abort_call_back)
+    }
+
+    /// Execute a contract call in the current block, first coercing the supplied arguments
+    ///   to the public function's declared argument types.
+    /// Currently, the only supported coercion is Int -> UInt for non-negative integers: arguments
+    ///   which cannot be coerced produce a BadTransaction error before any execution occurs.
+    /// Otherwise, this behaves exactly like `run_contract_call`.
+    pub fn run_contract_call_coerced <F> (&mut self, sender: &PrincipalData, contract: &QualifiedContractIdentifier, public_function: &str,
+                                          args: &[Value], abort_call_back: F) -> Result<(Value, AssetMap, Vec<StacksTransactionEvent>), Error>
+    where F: FnOnce(&AssetMap, &mut ClarityDatabase) -> bool {
+        let function_type = self.with_analysis_db_readonly(|db| {
+            db.get_public_function_type(contract, public_function)
+        })?;
+
+        let coerced_args = match function_type {
+            Some(FunctionType::Fixed(FixedFunction { args: expected_args, .. })) => {
+                if expected_args.len() != args.len() {
+                    // leave it to the strict call to report the argument count error.
+                    args.to_vec()
+                } else {
+                    expected_args.iter().zip(args.iter())
+                        .map(|(expected, arg)| coerce_argument(public_function, &expected.name, &expected.signature, arg))
+                        .collect::<Result<Vec<_>, Error>>()?
+                }
+            },
+            // no such function (or not a fixed-arity function): leave error handling to the strict call.
+            _ => args.to_vec()
+        };
+
+        self.run_contract_call(sender, contract, public_function, &coerced_args, abort_call_back)
+    }
+
     /// Initialize a contract in the current block.
     ///  If an error occurs while processing the initialization, it's modifications will be rolled back.
     /// abort_call_back is called with an AssetMap and a ClarityDatabase reference,
@@ -597,8 +866,9 @@ impl <'a> ClarityTransactionConnection <'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vm::errors::{InterpreterError as VmInterpreterError};
     use vm::analysis::errors::CheckErrors;
-    use vm::types::{Value, StandardPrincipalData};
+    use vm::types::{Value, StandardPrincipalData, TupleData};
     use vm::database::{NULL_HEADER_DB, ClarityBackingStore, MarfedKV};
     use chainstate::stacks::index::storage::{TrieFileStorage};
     use rusqlite::NO_PARAMS;
@@ -720,6 +990,649 @@ mod tests {
         assert!(marf.get_contract_hash(&contract_identifier).is_ok());
     }
 
+    #[test]
+    pub fn test_coerced_contract_call() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            let contract = "(define-public (foo (x uint)) (ok (+ x x)))";
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+
+            // the strict call does not coerce
+            assert!(conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "foo", &[Value::Int(5)],
+                                                                  |_, _| false)).is_err());
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call_coerced(&sender, &contract_identifier, "foo", &[Value::Int(5)],
+                                                                      |_, _| false)).unwrap().0,
+                Value::okay(Value::UInt(10)).unwrap());
+
+            match conn.as_transaction(|tx| tx.run_contract_call_coerced(&sender, &contract_identifier, "foo", &[Value::Int(-1)],
+                                                                        |_, _| false)).unwrap_err() {
+                Error::BadTransaction(msg) => assert!(msg.contains("negative integer -1")),
+                e => panic!("Unexpected error: {}", e)
+            };
+
+            conn.commit_block();
+        }
+    }
+
+    #[test]
+    pub fn test_nested_savepoints() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let outer_principal: PrincipalData = StandardPrincipalData(0, [1; 20]).into();
+        let inner_principal: PrincipalData = StandardPrincipalData(0, [2; 20]).into();
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            conn.as_transaction(|tx| {
+                tx.with_clarity_db(|db| {
+                    db.set_account_nonce(&outer_principal, 1);
+                    let inner_result: Result<(), Error> = db.with_savepoint(|db| {
+                        db.set_account_nonce(&inner_principal, 2);
+                        // the inner write is visible inside of the savepoint
+                        assert_eq!(db.get_account_nonce(&inner_principal), 2);
+                        Err(Error::BadTransaction("abort inner savepoint".into()))
+                    });
+                    assert!(inner_result.is_err());
+                    // ...but not after it has been rolled back
+                    assert_eq!(db.get_account_nonce(&inner_principal), 0);
+                    assert_eq!(db.get_account_nonce(&outer_principal), 1);
+                    Ok(())
+                }).unwrap();
+            });
+
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.get_account_nonce(&outer_principal)), 1);
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.get_account_nonce(&inner_principal)), 0);
+
+            conn.commit_block();
+        }
+    }
+
+    #[test]
+    pub fn test_map_entries() {
+        let mut marf = MarfedKV::temporary();
+        marf.enable_key_index().unwrap();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+
+        let contract = "
+            (define-map scores ((id int)) ((score uint)))
+            (define-public (set-score (id int) (score uint))
+              (ok (map-set scores ((id id)) ((score score)))))
+            (define-public (remove-score (id int))
+              (ok (map-delete scores ((id id)))))";
+
+        let make_key = |id| Value::from(TupleData::from_data(vec![("id".into(), Value::Int(id))]).unwrap());
+        let make_value = |score| Value::from(TupleData::from_data(vec![("score".into(), Value::UInt(score))]).unwrap());
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+
+            for (id, score) in [(1, 10), (2, 20), (3, 30), (4, 40)].iter() {
+                conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "set-score",
+                                                              &[Value::Int(*id), Value::UInt(*score)], |_, _| false)).unwrap();
+            }
+            conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "remove-score",
+                                                          &[Value::Int(4)], |_, _| false)).unwrap();
+
+            conn.commit_block();
+        }
+
+        {
+            let mut conn = clarity_instance.read_only_connection(&BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                                 &NULL_HEADER_DB);
+            let entries = conn.with_clarity_db_readonly(|db| db.map_entries(&contract_identifier, "scores")).unwrap();
+            assert_eq!(entries,
+                       vec![(make_key(1), make_value(10)),
+                            (make_key(2), make_value(20)),
+                            (make_key(3), make_value(30))]);
+
+            assert!(conn.with_clarity_db_readonly(|db| db.map_entries(&contract_identifier, "no-such-map")).is_err());
+            conn.done();
+        }
+    }
+
+    #[test]
+    pub fn test_map_entries_without_key_index() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "
+            (define-map scores ((id int)) ((score uint)))
+            (map-set scores ((id 1)) ((score u10)))";
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+
+            conn.commit_block();
+        }
+
+        {
+            let mut conn = clarity_instance.read_only_connection(&BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                                 &NULL_HEADER_DB);
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.map_entries(&contract_identifier, "scores")).unwrap_err(),
+                       VmInterpreterError::KeyIndexUnavailable.into());
+            conn.done();
+        }
+
+        // the index would be missing the entries which were already written.
+        let mut marf = clarity_instance.destroy();
+        assert_eq!(marf.enable_key_index().unwrap_err(),
+                   VmInterpreterError::KeyIndexUnavailable.into());
+    }
+
+    #[test]
+    pub fn test_nft_tokens_owned() {
+        let mut marf = MarfedKV::temporary();
+        marf.enable_key_index().unwrap();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender: PrincipalData = StandardPrincipalData::transient().into();
+        let other = PrincipalData::parse("SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR").unwrap();
+
+        let contract = "
+            (define-non-fungible-token art uint)
+            (define-public (mint (id uint) (owner principal))
+              (nft-mint? art id owner))
+            (define-public (give (id uint) (recipient principal))
+              (nft-transfer? art id tx-sender recipient))";
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+
+            for (id, owner) in [(1, &sender), (2, &other), (3, &sender), (4, &other), (5, &sender)].iter() {
+                conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "mint",
+                                                              &[Value::UInt(*id), Value::from((*owner).clone())], |_, _| false)).unwrap();
+            }
+            conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "give",
+                                                          &[Value::UInt(3), Value::from(other.clone())], |_, _| false)).unwrap();
+
+            conn.commit_block();
+        }
+
+        {
+            let mut conn = clarity_instance.read_only_connection(&BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                                 &NULL_HEADER_DB);
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.nft_tokens_owned(&contract_identifier, "art", &sender)).unwrap(),
+                       vec![Value::UInt(1), Value::UInt(5)]);
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.nft_tokens_owned(&contract_identifier, "art", &other)).unwrap(),
+                       vec![Value::UInt(2), Value::UInt(3), Value::UInt(4)]);
+
+            assert!(conn.with_clarity_db_readonly(|db| db.nft_tokens_owned(&contract_identifier, "no-such-nft", &sender)).is_err());
+            conn.done();
+        }
+    }
+
+    #[test]
+    pub fn test_speculative_fork() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let spec_contract_identifier = QualifiedContractIdentifier::local("bar").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+        let block = BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap();
+
+        let contract = "
+            (define-data-var count int 0)
+            (define-public (incr)
+              (begin (var-set count (+ 1 (var-get count)))
+                     (ok (var-get count))))
+            (define-read-only (get-count) (var-get count))";
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &block, &NULL_HEADER_DB);
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+            conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "incr", &[], |_, _| false)).unwrap();
+            conn.commit_block();
+        }
+
+        {
+            let mut conn = clarity_instance.speculate(&block, &NULL_HEADER_DB);
+
+            // reads fall through to the datastore
+            assert_eq!(conn.as_transaction(|tx| tx.eval_read_only(&contract_identifier, "(get-count)")).unwrap(),
+                       Value::Int(1));
+
+            for _ in 0..2 {
+                conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "incr", &[], |_, _| false)).unwrap();
+            }
+            assert_eq!(conn.as_transaction(|tx| tx.eval_read_only(&contract_identifier, "(get-count)")).unwrap(),
+                       Value::Int(3));
+
+            // contracts can be deployed into the fork, too
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&spec_contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &spec_contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&spec_contract_identifier, &ct_analysis).unwrap();
+            });
+            conn.as_transaction(|tx| tx.run_contract_call(&sender, &spec_contract_identifier, "incr", &[], |_, _| false)).unwrap();
+            assert_eq!(conn.as_transaction(|tx| tx.eval_read_only(&spec_contract_identifier, "(get-count)")).unwrap(),
+                       Value::Int(1));
+
+            conn.discard();
+        }
+
+        assert_eq!(clarity_instance.eval_read_only(&block, &NULL_HEADER_DB, &contract_identifier, "(get-count)").unwrap(),
+                   Value::Int(1));
+
+        {
+            let mut conn = clarity_instance.read_only_connection(&block, &NULL_HEADER_DB);
+            assert!(conn.with_analysis_db_readonly(|db| db.load_contract(&spec_contract_identifier)).is_none());
+            conn.done();
+        }
+    }
+
+    #[test]
+    pub fn test_get_contract_src() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let block = BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap();
+
+        let contract = ";; a contract, with its comments and    spacing\n\
+                        (define-data-var count int 0)\t\n\
+                        (define-read-only (get-count) (var-get count))  ;; trailing comment\n\n";
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &block, &NULL_HEADER_DB);
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+            conn.commit_block();
+        }
+
+        {
+            let mut conn = clarity_instance.read_only_connection(&block, &NULL_HEADER_DB);
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.get_contract_src(&contract_identifier)).unwrap().as_bytes(),
+                       contract.as_bytes());
+            assert!(conn.with_clarity_db_readonly(
+                |db| db.get_contract_src(&QualifiedContractIdentifier::local("bar").unwrap())).is_none());
+            conn.done();
+        }
+    }
+
+    #[test]
+    pub fn test_deploy_contract() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+
+        let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                    &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                    &NULL_HEADER_DB);
+
+        let failing_init = "(define-data-var bar int 0)
+                            (define-public (get-bar) (ok (var-get bar)))
+                            (var-set bar (/ 1 0))";
+        assert!(format!("{:?}", conn.deploy_contract(&contract_identifier, failing_init, |_, _| false).unwrap_err())
+                .contains("DivisionByZero"));
+
+        let contract = "(define-data-var bar int 0)
+                        (define-public (get-bar) (ok (var-get bar)))
+                        (var-set bar 1)";
+        assert!(format!("{}", conn.deploy_contract(&contract_identifier, contract, |_, _| true).unwrap_err())
+                .contains("aborted"));
+
+        // neither failed deploy persisted its analysis or its state
+        conn.as_transaction(|tx| {
+            assert!(tx.with_analysis_db_readonly(|db| db.load_contract(&contract_identifier)).is_none());
+            assert!(tx.with_clarity_db_readonly(|db| db.get_contract_src(&contract_identifier)).is_none());
+        });
+
+        let (analysis, _) = conn.deploy_contract(&contract_identifier, contract, |_, _| false).unwrap();
+        let saved_analysis = conn.as_transaction(|tx| tx.with_analysis_db_readonly(|db| db.load_contract(&contract_identifier)))
+            .unwrap();
+        assert_eq!(saved_analysis.public_function_types, analysis.public_function_types);
+        assert_eq!(
+            conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "get-bar", &[],
+                                   |_, _| false)).unwrap().0,
+            Value::okay(Value::Int(1)).unwrap());
+
+        conn.commit_block();
+    }
+
+    #[test]
+    pub fn test_check_contract_call_args() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+
+        let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                    &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                    &NULL_HEADER_DB);
+
+        let contract = "(define-public (transfer (amount uint) (memo (optional (buff 4))))
+                          (ok amount))
+                        (define-read-only (get-count) u1)";
+        conn.deploy_contract(&contract_identifier, contract, |_, _| false).unwrap();
+
+        conn.as_transaction(|tx| tx.with_clarity_db_readonly(|db| {
+            let memo = Value::some(Value::buff_from(vec![1, 2]).unwrap()).unwrap();
+            db.check_contract_call_args(&contract_identifier, "transfer", &[Value::UInt(10), memo])
+                .unwrap();
+            db.check_contract_call_args(&contract_identifier, "transfer", &[Value::UInt(10), Value::none()])
+                .unwrap();
+            db.check_contract_call_args(&contract_identifier, "get-count", &[])
+                .unwrap();
+
+            match db.check_contract_call_args(&contract_identifier, "transfer", &[Value::UInt(10), Value::Int(1)])
+                .unwrap_err() {
+                InterpreterError::Unchecked(CheckErrors::ArgumentTypeError { index, expected, found }) => {
+                    assert_eq!(index, 1);
+                    assert_eq!(format!("{}", expected), "(optional (buff 4))");
+                    assert_eq!(found, Value::Int(1));
+                },
+                e => panic!("Unexpected error: {:?}", e)
+            }
+
+            assert!(match db.check_contract_call_args(&contract_identifier, "transfer", &[Value::UInt(10)]).unwrap_err() {
+                InterpreterError::Unchecked(CheckErrors::IncorrectArgumentCount(2, 1)) => true,
+                _ => false
+            });
+            assert!(match db.check_contract_call_args(&contract_identifier, "burn", &[]).unwrap_err() {
+                InterpreterError::Unchecked(CheckErrors::NoSuchPublicFunction(_, _)) => true,
+                _ => false
+            });
+            assert!(match db.check_contract_call_args(&QualifiedContractIdentifier::local("bar").unwrap(), "transfer", &[])
+                    .unwrap_err() {
+                InterpreterError::Unchecked(CheckErrors::NoSuchContract(_)) => true,
+                _ => false
+            });
+        }));
+
+        conn.commit_block();
+    }
+
+    #[test]
+    pub fn test_set_data_var() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("counter").unwrap();
+
+        let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                    &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                    &NULL_HEADER_DB);
+
+        let contract = "(define-data-var count uint u0)
+                        (define-public (increment) (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+                        (define-read-only (get-count) (var-get count))";
+        conn.deploy_contract(&contract_identifier, contract, |_, _| false).unwrap();
+
+        let near_overflow = Value::UInt(u128::max_value());
+        conn.set_data_var(&contract_identifier, "count", near_overflow.clone()).unwrap();
+        assert_eq!(conn.as_transaction(|tx| tx.eval_read_only(&contract_identifier, "(get-count)")).unwrap(),
+                   near_overflow);
+
+        // the forced state is visible to contract calls
+        assert!(match conn.as_transaction(|tx| tx.run_contract_call(&contract_identifier.issuer.clone().into(), &contract_identifier,
+                                                                    "increment", &[], |_, _| false)).unwrap_err() {
+            Error::Interpreter(InterpreterError::Runtime(RuntimeErrorType::ArithmeticOverflow, _)) => true,
+            _ => false
+        });
+
+        assert!(match conn.set_data_var(&contract_identifier, "count", Value::Int(1)).unwrap_err() {
+            Error::Interpreter(InterpreterError::Unchecked(CheckErrors::TypeValueError(_, _))) => true,
+            _ => false
+        });
+        assert!(match conn.set_data_var(&contract_identifier, "total", Value::UInt(1)).unwrap_err() {
+            Error::Interpreter(InterpreterError::Unchecked(CheckErrors::NoSuchDataVariable(_))) => true,
+            _ => false
+        });
+        assert_eq!(conn.as_transaction(|tx| tx.eval_read_only(&contract_identifier, "(get-count)")).unwrap(),
+                   near_overflow);
+
+        conn.commit_block();
+    }
+
+    #[test]
+    pub fn test_get_contract_analysis() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let block = BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap();
+
+        let contract = "(define-data-var count int 0)
+                        (define-public (add (x int)) (ok (var-set count (+ x (var-get count)))))
+                        (define-read-only (get-count) (var-get count))";
+
+        let saved_analysis = {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &block, &NULL_HEADER_DB);
+            let saved_analysis = conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+                ct_analysis
+            });
+            conn.commit_block();
+            saved_analysis
+        };
+
+        let analysis = clarity_instance.get_contract_analysis(&block, &NULL_HEADER_DB, &contract_identifier)
+            .unwrap().unwrap();
+        assert_eq!(analysis.public_function_types, saved_analysis.public_function_types);
+        assert_eq!(analysis.read_only_function_types, saved_analysis.read_only_function_types);
+        assert_eq!(analysis.get_public_function_type("add"), saved_analysis.get_public_function_type("add"));
+        assert!(analysis.get_public_function_type("add").is_some());
+
+        assert!(clarity_instance.get_contract_analysis(&block, &NULL_HEADER_DB,
+                                                       &QualifiedContractIdentifier::local("bar").unwrap())
+                .unwrap().is_none());
+    }
+
+    #[test]
+    pub fn test_block_info_cache() {
+        use std::cell::Cell;
+        use chainstate::burn::VRFSeed;
+        use chainstate::stacks::StacksAddress;
+        use burnchains::BurnchainHeaderHash;
+
+        struct CountingHeadersDB { fetches: Cell<u32> }
+
+        impl HeadersDB for CountingHeadersDB {
+            fn get_stacks_block_header_hash_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<BlockHeaderHash> { None }
+            fn get_burn_header_hash_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<BurnchainHeaderHash> { None }
+            fn get_vrf_seed_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<VRFSeed> { None }
+            fn get_burn_block_time_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<u64> {
+                self.fetches.set(self.fetches.get() + 1);
+                Some(1234)
+            }
+            fn get_miner_address(&self, _id_bhh: &BlockHeaderHash) -> Option<StacksAddress> { None }
+        }
+
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let headers_db = CountingHeadersDB { fetches: Cell::new(0) };
+        let blocks: Vec<_> = (0..3).map(|i| BlockHeaderHash::from_bytes(&[i as u8; 32]).unwrap()).collect();
+
+        {
+            let conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                    &blocks[0], &headers_db);
+            conn.commit_block();
+        }
+
+        let program = "(list (get-block-info? time u0) (get-block-info? time u0) (get-block-info? time u0))";
+        let expected = Value::list_from(vec![Value::some(Value::UInt(1234)).unwrap(); 3]).unwrap();
+
+        {
+            let mut conn = clarity_instance.begin_block(&blocks[0], &blocks[1], &headers_db);
+            for _ in 0..2 {
+                assert_eq!(conn.as_transaction(|tx| tx.clarity_eval_raw(program)).unwrap(),
+                           expected);
+            }
+            conn.commit_block();
+        }
+        assert_eq!(headers_db.fetches.get(), 1);
+
+        // the cache does not outlive the block connection
+        {
+            let mut conn = clarity_instance.begin_block(&blocks[1], &blocks[2], &headers_db);
+            assert_eq!(conn.as_transaction(|tx| tx.clarity_eval_raw(program)).unwrap(),
+                       expected);
+            conn.commit_block();
+        }
+        assert_eq!(headers_db.fetches.get(), 2);
+    }
+
+    #[test]
+    pub fn test_block_info_bulk() {
+        use chainstate::burn::VRFSeed;
+        use chainstate::stacks::StacksAddress;
+        use burnchains::BurnchainHeaderHash;
+        use vm::types::BlockInfoProperty;
+
+        struct FixedHeadersDB {}
+
+        impl HeadersDB for FixedHeadersDB {
+            fn get_stacks_block_header_hash_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<BlockHeaderHash> {
+                Some(BlockHeaderHash([7; 32]))
+            }
+            fn get_burn_header_hash_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<BurnchainHeaderHash> { None }
+            fn get_vrf_seed_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<VRFSeed> { None }
+            fn get_burn_block_time_for_block(&self, _id_bhh: &BlockHeaderHash) -> Option<u64> { Some(1234) }
+            fn get_miner_address(&self, _id_bhh: &BlockHeaderHash) -> Option<StacksAddress> { None }
+        }
+
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let headers_db = FixedHeadersDB {};
+        let blocks: Vec<_> = (0..2).map(|i| BlockHeaderHash::from_bytes(&[i as u8; 32]).unwrap()).collect();
+
+        clarity_instance.begin_block(&TrieFileStorage::block_sentinel(), &blocks[0], &headers_db)
+            .commit_block();
+
+        let mut conn = clarity_instance.begin_block(&blocks[0], &blocks[1], &headers_db);
+        conn.as_transaction(|tx| tx.with_clarity_db_readonly(|db| {
+            let properties = [BlockInfoProperty::Time, BlockInfoProperty::HeaderHash];
+            let values = db.get_block_info_bulk(0, &properties).unwrap();
+
+            assert_eq!(values, vec![
+                Value::UInt(db.get_block_time(0) as u128),
+                Value::buff_from(db.get_block_header_hash(0).as_bytes().to_vec()).unwrap()]);
+            for (property, value) in properties.iter().zip(values.iter()) {
+                assert!(property.type_result().admits(value));
+            }
+
+            assert!(db.get_block_info_bulk(1, &properties).is_err());
+        }));
+        conn.commit_block();
+    }
+
+    #[test]
+    pub fn test_trait_compliance_precheck() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let trait_contract_id = QualifiedContractIdentifier::local("defun").unwrap();
+        let trait_identifier = TraitIdentifier {
+            name: "trait-1".into(),
+            contract_identifier: trait_contract_id.clone()
+        };
+
+        let trait_contract = "
+            (define-trait trait-1 (
+              (get-1 (uint) (response uint uint))
+              (get-2 (int) (response int int))
+              (get-3 () (response bool bool))))";
+
+        let conforming_contract = "
+            (define-read-only (get-1 (x uint)) (ok x))
+            (define-public (get-2 (x int)) (ok x))
+            (define-public (get-3) (ok true))";
+
+        let non_conforming_contract = "
+            (define-public (get-1 (x int)) (ok x))
+            (define-public (get-2 (x int)) (ok x))
+            (define-private (get-3) (ok true))";
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&trait_contract_id, &trait_contract).unwrap();
+                conn.initialize_smart_contract(
+                    &trait_contract_id, &ct_ast, &trait_contract, |_,_| false).unwrap();
+                conn.save_analysis(&trait_contract_id, &ct_analysis).unwrap();
+            });
+
+            let contract_id = QualifiedContractIdentifier::local("implem").unwrap();
+
+            let contract_ast = ast::build_ast(&contract_id, conforming_contract, &mut ()).unwrap();
+            conn.check_trait_compliance(&contract_ast, &trait_identifier).unwrap();
+
+            let contract_ast = ast::build_ast(&contract_id, non_conforming_contract, &mut ()).unwrap();
+            match conn.check_trait_compliance(&contract_ast, &trait_identifier).unwrap_err() {
+                Error::Analysis(e) => {
+                    assert_eq!(e.err, CheckErrors::IncompleteTraitImplementation(
+                        "trait-1".into(), vec!["get-3".into()], vec!["get-1".into()]));
+                },
+                e => panic!("Unexpected error: {}", e)
+            };
+
+            conn.commit_block();
+        }
+    }
+
     #[test]
     pub fn test_block_roll_back() {
         let marf = MarfedKV::temporary();
@@ -817,6 +1730,151 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_transaction_root_hashes() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+
+        let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                    &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                    &NULL_HEADER_DB);
+
+        let contract = "(define-data-var bar int 0)
+                        (define-public (set-bar (x int)) (begin (var-set bar x) (ok x)))";
+        conn.deploy_contract(&contract_identifier, contract, |_, _| false).unwrap();
+        let deployed_root = conn.get_root_hash();
+
+        let (result, first_root) = conn.as_transaction_with_root(
+            |tx| tx.run_contract_call(&sender, &contract_identifier, "set-bar", &[Value::Int(1)], |_, _| false));
+        result.unwrap();
+        assert_ne!(first_root, deployed_root);
+
+        let (result, second_root) = conn.as_transaction_with_root(
+            |tx| tx.run_contract_call(&sender, &contract_identifier, "set-bar", &[Value::Int(2)], |_, _| false));
+        result.unwrap();
+        assert_ne!(second_root, first_root);
+
+        // rolled back transactions leave the root unchanged
+        let (result, aborted_root) = conn.as_transaction_with_root(
+            |tx| tx.run_contract_call(&sender, &contract_identifier, "set-bar", &[Value::Int(3)], |_, _| true));
+        result.unwrap();
+        assert_eq!(aborted_root, second_root);
+        let (result, failed_root) = conn.as_transaction_with_root(
+            |tx| tx.run_contract_call(&sender, &contract_identifier, "set-bar", &[Value::Bool(true)], |_, _| false));
+        result.unwrap_err();
+        assert_eq!(failed_root, second_root);
+        {
+            let mut tx = conn.start_transaction_processing();
+            tx.run_contract_call(&sender, &contract_identifier, "set-bar", &[Value::Int(4)], |_, _| false).unwrap();
+        }
+        assert_eq!(conn.get_root_hash(), second_root);
+
+        conn.commit_block();
+    }
+
+    #[test]
+    pub fn test_run_contract_call_read_only() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            // `incr` would fail analysis, so the contract is initialized without it.
+            let contract = "
+            (define-data-var bar int 0)
+            (define-read-only (incr) (begin (var-set bar (+ 1 (var-get bar))) (var-get bar)))
+            (define-read-only (get-bar) (var-get bar))";
+
+            conn.as_transaction(|conn| {
+                let ct_ast = ast::build_ast(&contract_identifier, &contract, &mut ()).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+            });
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "get-bar", &[],
+                                                              |_, _| false)).unwrap().0,
+                Value::Int(0));
+
+            assert!(match conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "incr", &[],
+                                                                        |_, _| false)).unwrap_err() {
+                Error::Interpreter(InterpreterError::Unchecked(CheckErrors::WriteAttemptedInReadOnly)) => true,
+                _ => false
+            });
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call(&sender, &contract_identifier, "get-bar", &[],
+                                                              |_, _| false)).unwrap().0,
+                Value::Int(0));
+
+            assert!(match conn.as_transaction(|tx| tx.run_contract_call(&sender, &QualifiedContractIdentifier::local("bar").unwrap(),
+                                                                        "get-bar", &[], |_, _| false)).unwrap_err() {
+                Error::Interpreter(InterpreterError::Unchecked(CheckErrors::NoSuchContract(_))) => true,
+                _ => false
+            });
+
+            conn.commit_block();
+        }
+    }
+
+    #[test]
+    pub fn test_run_contract_call_with_nonce() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = StandardPrincipalData::transient().into();
+
+        {
+            let mut conn = clarity_instance.begin_block(&TrieFileStorage::block_sentinel(),
+                                                        &BlockHeaderHash::from_bytes(&[0 as u8; 32]).unwrap(),
+                                                        &NULL_HEADER_DB);
+
+            let contract = "
+            (define-data-var bar int 0)
+            (define-public (incr) (begin (var-set bar (+ 1 (var-get bar))) (ok (var-get bar))))";
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn.analyze_smart_contract(&contract_identifier, &contract).unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier, &ct_ast, &contract, |_,_| false).unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis).unwrap();
+            });
+
+            // a fresh nonce succeeds
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call_with_nonce(&sender, 0, &contract_identifier, "incr", &[],
+                                                                         |_, _| false)).unwrap().0,
+                Value::okay(Value::Int(1)).unwrap());
+
+            // a replayed nonce is rejected without executing the call
+            assert!(match conn.as_transaction(|tx| tx.run_contract_call_with_nonce(&sender, 0, &contract_identifier, "incr", &[],
+                                                                                   |_, _| false)).unwrap_err() {
+                Error::Interpreter(InterpreterError::Runtime(RuntimeErrorType::BadNonce(1, 0), _)) => true,
+                _ => false
+            });
+
+            // an aborted call does not consume its nonce
+            conn.as_transaction(|tx| tx.run_contract_call_with_nonce(&sender, 1, &contract_identifier, "incr", &[],
+                                                                     |_, _| true)).unwrap();
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.get_account_nonce(&sender)), 1);
+
+            assert_eq!(
+                conn.as_transaction(|tx| tx.run_contract_call_with_nonce(&sender, 1, &contract_identifier, "incr", &[],
+                                                                         |_, _| false)).unwrap().0,
+                Value::okay(Value::Int(2)).unwrap());
+            assert_eq!(conn.with_clarity_db_readonly(|db| db.get_account_nonce(&sender)), 2);
+
+            conn.commit_block();
+        }
+    }
+
     #[test]
     pub fn test_block_limit() {
         let marf = MarfedKV::temporary();
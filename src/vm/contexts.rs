@@ -1,11 +1,12 @@
 use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet};
 use std::fmt;
 use std::convert::TryInto;
+use std::rc::Rc;
 
 use vm::errors::{InterpreterError, CheckErrors, RuntimeErrorType, InterpreterResult as Result};
 use vm::types::{Value, AssetIdentifier, PrincipalData, TraitIdentifier, QualifiedContractIdentifier, TypeSignature};
 use vm::types::signatures::{FunctionSignature};
-use vm::callables::{DefinedFunction, FunctionIdentifier};
+use vm::callables::{DefinedFunction, FunctionIdentifier, CallableType};
 use vm::database::{ClarityDatabase};
 use vm::representations::{SymbolicExpression, ClarityName, ContractName};
 use vm::contracts::Contract;
@@ -17,9 +18,16 @@ use vm::{eval, is_reserved};
 use chainstate::burn::{VRFSeed, BlockHeaderHash};
 use chainstate::stacks::events::*;
 
+use net::{StacksMessageCodec, Error as NetError};
+use net::codec::{read_next, write_next};
+
+use std::io::{Read, Write};
+
 use serde::Serialize;
 
 pub const MAX_CONTEXT_DEPTH: u16 = 256;
+// default limit on the number of nested contract-call?s in a single transaction.
+pub const MAX_CONTRACT_CALL_DEPTH: u32 = 64;
 
 // TODO:
 //    hide the environment's instance variables.
@@ -29,7 +37,30 @@ pub struct Environment <'a, 'b> {
     pub contract_context: &'a ContractContext,
     pub call_stack: &'a mut CallStack,
     pub sender: Option<Value>,
-    pub caller: Option<Value>
+    pub caller: Option<Value>,
+    pub resolver: Option<Rc<dyn NameResolver>>,
+    outer_principals: Vec<PrincipalFrame>
+}
+
+/// The sender and caller of an environment. Environments nested by `as-contract` and
+///   `contract-call?` keep the frames of the environments they are nested in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrincipalFrame {
+    pub sender: Option<PrincipalData>,
+    pub caller: Option<PrincipalData>,
+}
+
+/// Resolves variables and functions that are not defined by the contract, e.g. to inject
+///   virtual globals into a sandboxed evaluation. The resolver is only consulted when neither
+///   the reserved names nor the contract's own definitions resolve the name.
+pub trait NameResolver {
+    fn resolve_variable(&self, _name: &str) -> Option<Value> {
+        None
+    }
+
+    fn resolve_function(&self, _name: &str) -> Option<CallableType> {
+        None
+    }
 }
 
 pub struct OwnedEnvironment <'a> {
@@ -76,6 +107,31 @@ pub struct GlobalContext<'a> {
     pub database: ClarityDatabase<'a>,
     read_only: Vec<bool>,
     pub cost_track: LimitedCostTracker,
+    contract_call_depth: u32,
+    max_contract_call_depth: u32,
+    #[cfg(any(test, feature = "testing"))]
+    contract_call_interceptor: Option<Box<dyn ContractCallInterceptor>>,
+}
+
+/// A test-only hook consulted by `contract-call?` in place of dispatching into the called
+///   contract, so that contracts can be unit tested against mocked dependencies which are
+///   never deployed. Intercepted calls are still subject to the contract-call depth limit
+///   and, for dynamic dispatch, to the trait checks.
+#[cfg(any(test, feature = "testing"))]
+pub trait ContractCallInterceptor {
+    /// Mock calling `function_name` of `contract_identifier` with the evaluated `args`,
+    ///   returning the mocked function's declared return type along with its result, or
+    ///   None to fall through to the real dispatch. The result must be admitted by the
+    ///   declared return type.
+    fn intercept(&mut self, contract_identifier: &QualifiedContractIdentifier, function_name: &str, args: &[Value]) -> Option<(TypeSignature, Value)>;
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl <F> ContractCallInterceptor for F
+where F: FnMut(&QualifiedContractIdentifier, &str, &[Value]) -> Option<(TypeSignature, Value)> {
+    fn intercept(&mut self, contract_identifier: &QualifiedContractIdentifier, function_name: &str, args: &[Value]) -> Option<(TypeSignature, Value)> {
+        self(contract_identifier, function_name, args)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -88,7 +144,23 @@ pub struct ContractContext {
     // tracks the names of NFTs, FTs, Maps, and Data Vars.
     //  used for ensuring that they never are defined twice.
     pub persisted_names: HashSet<ClarityName>,
-    pub data_size: u64
+    // the kind of each persisted name, for tooling. Not serialized: contexts
+    //  loaded from the database only know their persisted names.
+    #[serde(skip)]
+    persisted_kinds: HashMap<ClarityName, NameKind>,
+    pub data_size: u64,
+}
+
+/// The kind of definition a name is bound to in a contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Function,
+    Constant,
+    DataVar,
+    Map,
+    FungibleToken,
+    NonFungibleToken,
+    Trait,
 }
 
 pub struct LocalContext <'a> {
@@ -336,6 +408,191 @@ impl AssetMap {
             None => None
         }
     }
+
+    /// The transfers debited from `principal`: STX transferred, then STX burned, then
+    ///   fungible and non-fungible tokens sorted by asset identifier. The asset map does
+    ///   not record recipients, so transfers to `principal` are not included.
+    pub fn debits_for(&self, principal: &PrincipalData) -> Vec<AssetMapEntry> {
+        let mut entries = Vec::new();
+        if let Some(amount) = self.get_stx(principal) {
+            entries.push(AssetMapEntry::STX(amount));
+        }
+        if let Some(amount) = self.get_stx_burned(principal) {
+            entries.push(AssetMapEntry::Burn(amount));
+        }
+
+        let mut token_entries = Vec::new();
+        if let Some(assets) = self.token_map.get(principal) {
+            token_entries.extend(assets.iter()
+                                 .map(|(asset, amount)| (asset, AssetMapEntry::Token(*amount))));
+        }
+        if let Some(assets) = self.asset_map.get(principal) {
+            token_entries.extend(assets.iter()
+                                 .map(|(asset, transfers)| (asset, AssetMapEntry::Asset(transfers.clone()))));
+        }
+        token_entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries.extend(token_entries.into_iter().map(|(_, entry)| entry));
+
+        entries
+    }
+
+    /// The total amount of the fungible `asset` debited from `principal` (or of STX, with
+    ///   `AssetIdentifier::STX()` or `AssetIdentifier::STX_burned()`).
+    pub fn debited_for(&self, principal: &PrincipalData, asset: &AssetIdentifier) -> u128 {
+        if *asset == AssetIdentifier::STX() {
+            self.get_stx(principal)
+        } else if *asset == AssetIdentifier::STX_burned() {
+            self.get_stx_burned(principal)
+        } else {
+            self.get_fungible_tokens(principal, asset)
+        }.unwrap_or(0)
+    }
+}
+
+// The canonical encoding of an AssetMap is independent of the iteration order of its hash maps:
+//   every map is written as a u32 entry count followed by its entries sorted by key, and
+//   deserialization rejects entries that are not in strictly increasing key order.
+//   u128 amounts are written as 16 big-endian bytes, and non-fungible token values use the
+//   consensus serialization of Values.
+impl StacksMessageCodec for AssetMap {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> std::result::Result<(), NetError> {
+        serialize_amounts(fd, &self.stx_map)?;
+        serialize_amounts(fd, &self.burn_map)?;
+
+        let mut token_map: Vec<_> = self.token_map.iter().collect();
+        token_map.sort_by(|a, b| a.0.cmp(b.0));
+        write_next(fd, &(token_map.len() as u32))?;
+        for (principal, assets) in token_map.into_iter() {
+            write_next(fd, principal)?;
+            let mut assets: Vec<_> = assets.iter().collect();
+            assets.sort_by(|a, b| a.0.cmp(b.0));
+            write_next(fd, &(assets.len() as u32))?;
+            for (asset, amount) in assets.into_iter() {
+                serialize_asset_identifier(fd, asset)?;
+                fd.write_all(&amount.to_be_bytes()).map_err(NetError::WriteError)?;
+            }
+        }
+
+        let mut asset_map: Vec<_> = self.asset_map.iter().collect();
+        asset_map.sort_by(|a, b| a.0.cmp(b.0));
+        write_next(fd, &(asset_map.len() as u32))?;
+        for (principal, assets) in asset_map.into_iter() {
+            write_next(fd, principal)?;
+            let mut assets: Vec<_> = assets.iter().collect();
+            assets.sort_by(|a, b| a.0.cmp(b.0));
+            write_next(fd, &(assets.len() as u32))?;
+            for (asset, values) in assets.into_iter() {
+                serialize_asset_identifier(fd, asset)?;
+                write_next(fd, &(values.len() as u32))?;
+                for value in values.iter() {
+                    value.serialize_write(fd).map_err(NetError::WriteError)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> std::result::Result<AssetMap, NetError> {
+        let stx_map = deserialize_amounts(fd)?;
+        let burn_map = deserialize_amounts(fd)?;
+
+        let mut token_map = HashMap::new();
+        let mut last_principal = None;
+        let principal_count: u32 = read_next(fd)?;
+        for _ in 0..principal_count {
+            let principal: PrincipalData = read_next(fd)?;
+            check_canonical_order(&mut last_principal, &principal)?;
+            let mut assets = HashMap::new();
+            let mut last_asset = None;
+            let asset_count: u32 = read_next(fd)?;
+            for _ in 0..asset_count {
+                let asset = deserialize_asset_identifier(fd)?;
+                check_canonical_order(&mut last_asset, &asset)?;
+                assets.insert(asset, deserialize_u128(fd)?);
+            }
+            token_map.insert(principal, assets);
+        }
+
+        let mut asset_map = HashMap::new();
+        let mut last_principal = None;
+        let principal_count: u32 = read_next(fd)?;
+        for _ in 0..principal_count {
+            let principal: PrincipalData = read_next(fd)?;
+            check_canonical_order(&mut last_principal, &principal)?;
+            let mut assets = HashMap::new();
+            let mut last_asset = None;
+            let asset_count: u32 = read_next(fd)?;
+            for _ in 0..asset_count {
+                let asset = deserialize_asset_identifier(fd)?;
+                check_canonical_order(&mut last_asset, &asset)?;
+                let value_count: u32 = read_next(fd)?;
+                let mut values = Vec::new();
+                for _ in 0..value_count {
+                    let value = Value::deserialize_read(fd, None)
+                        .map_err(|e| NetError::DeserializeError(e.to_string()))?;
+                    values.push(value);
+                }
+                assets.insert(asset, values);
+            }
+            asset_map.insert(principal, assets);
+        }
+
+        Ok(AssetMap { stx_map, burn_map, token_map, asset_map })
+    }
+}
+
+fn check_canonical_order<T: Ord + Clone>(last: &mut Option<T>, next: &T) -> std::result::Result<(), NetError> {
+    if let Some(ref last) = last {
+        if last >= next {
+            return Err(NetError::DeserializeError("AssetMap entries are not in canonical order".to_string()))
+        }
+    }
+    last.replace(next.clone());
+    Ok(())
+}
+
+fn serialize_amounts<W: Write>(fd: &mut W, amounts: &HashMap<PrincipalData, u128>) -> std::result::Result<(), NetError> {
+    let mut amounts: Vec<_> = amounts.iter().collect();
+    amounts.sort_by(|a, b| a.0.cmp(b.0));
+    write_next(fd, &(amounts.len() as u32))?;
+    for (principal, amount) in amounts.into_iter() {
+        write_next(fd, principal)?;
+        fd.write_all(&amount.to_be_bytes()).map_err(NetError::WriteError)?;
+    }
+    Ok(())
+}
+
+fn deserialize_amounts<R: Read>(fd: &mut R) -> std::result::Result<HashMap<PrincipalData, u128>, NetError> {
+    let mut amounts = HashMap::new();
+    let mut last_principal = None;
+    let count: u32 = read_next(fd)?;
+    for _ in 0..count {
+        let principal: PrincipalData = read_next(fd)?;
+        check_canonical_order(&mut last_principal, &principal)?;
+        amounts.insert(principal, deserialize_u128(fd)?);
+    }
+    Ok(amounts)
+}
+
+fn deserialize_u128<R: Read>(fd: &mut R) -> std::result::Result<u128, NetError> {
+    let mut bytes = [0u8; 16];
+    fd.read_exact(&mut bytes).map_err(NetError::ReadError)?;
+    Ok(u128::from_be_bytes(bytes))
+}
+
+fn serialize_asset_identifier<W: Write>(fd: &mut W, asset: &AssetIdentifier) -> std::result::Result<(), NetError> {
+    write_next(fd, &PrincipalData::Contract(asset.contract_identifier.clone()))?;
+    write_next(fd, &asset.asset_name)
+}
+
+fn deserialize_asset_identifier<R: Read>(fd: &mut R) -> std::result::Result<AssetIdentifier, NetError> {
+    let contract_identifier = match read_next(fd)? {
+        PrincipalData::Contract(contract_identifier) => contract_identifier,
+        PrincipalData::Standard(_) => return Err(NetError::DeserializeError(
+            "Expected a contract principal in asset identifier".to_string()))
+    };
+    let asset_name = read_next(fd)?;
+    Ok(AssetIdentifier { contract_identifier, asset_name })
 }
 
 impl fmt::Display for AssetMap {
@@ -461,6 +718,11 @@ impl <'a> OwnedEnvironment <'a> {
                             |exec_env| exec_env.eval_read_only(contract, program))
     }
 
+    pub fn eval_read_only_parsed(&mut self, contract: &QualifiedContractIdentifier, parsed: &[SymbolicExpression]) -> Result<(Value, AssetMap, Vec<StacksTransactionEvent>)>  {
+        self.execute_in_env(Value::from(QualifiedContractIdentifier::transient().issuer),
+                            |exec_env| exec_env.eval_read_only_parsed(contract, parsed))
+    }
+
     pub fn begin(&mut self) {
         self.context.begin();
     }
@@ -541,24 +803,73 @@ impl <'a,'b> Environment <'a,'b> {
             contract_context,
             call_stack,
             sender,
-            caller
+            caller,
+            resolver: None,
+            outer_principals: Vec::new()
+        }
+    }
+
+    /// The principal `tx-sender` evaluates to in this environment.
+    pub fn sender(&self) -> Option<&PrincipalData> {
+        match self.sender {
+            Some(Value::Principal(ref sender)) => Some(sender),
+            _ => None
         }
     }
 
+    /// The principal `contract-caller` evaluates to in this environment.
+    pub fn caller(&self) -> Option<&PrincipalData> {
+        match self.caller {
+            Some(Value::Principal(ref caller)) => Some(caller),
+            _ => None
+        }
+    }
+
+    /// The senders and callers of the `as-contract` and `contract-call?` frames this
+    ///   environment is nested in, outermost first, ending with this environment's own.
+    pub fn sender_stack(&self) -> Vec<PrincipalFrame> {
+        let mut frames = self.outer_principals.clone();
+        frames.push(PrincipalFrame { sender: self.sender().cloned(), caller: self.caller().cloned() });
+        frames
+    }
+
+    /// Consult `resolver` for the names this environment (and the environments nested in it
+    ///   by function calls) fails to resolve. Top-level expressions of contracts initialized
+    ///   from this environment are evaluated without it.
+    pub fn set_resolver(&mut self, resolver: Rc<dyn NameResolver>) {
+        self.resolver = Some(resolver);
+    }
+
+    fn nested_in(mut self, parent_resolver: &Option<Rc<dyn NameResolver>>, outer_principals: Vec<PrincipalFrame>) -> Self {
+        self.resolver = parent_resolver.clone();
+        self.outer_principals = outer_principals;
+        self
+    }
+
     pub fn nest_as_principal <'c> (&'c mut self, sender: Value) -> Environment<'c,'b> {
+        let outer_principals = self.sender_stack();
         Environment::new(self.global_context, self.contract_context, self.call_stack,
                          Some(sender.clone()), Some(sender))
+            .nested_in(&self.resolver, outer_principals)
     }
 
     pub fn nest_with_caller <'c> (&'c mut self, caller: Value) -> Environment<'c,'b> {
+        let outer_principals = self.sender_stack();
         Environment::new(self.global_context, self.contract_context, self.call_stack,
                          self.sender.clone(), Some(caller))
+            .nested_in(&self.resolver, outer_principals)
     }
 
     pub fn eval_read_only(&mut self, contract_identifier: &QualifiedContractIdentifier, program: &str) -> Result<Value> {
         let parsed = ast::build_ast(contract_identifier, program, self)?
             .expressions;
 
+        self.eval_read_only_parsed(contract_identifier, &parsed)
+    }
+
+    /// Like `eval_read_only`, but evaluates an already-parsed program, so that
+    ///   the same query can be evaluated repeatedly without re-parsing it.
+    pub fn eval_read_only_parsed(&mut self, contract_identifier: &QualifiedContractIdentifier, parsed: &[SymbolicExpression]) -> Result<Value> {
         if parsed.len() < 1 {
             return Err(RuntimeErrorType::ParseError("Expected a program of at least length 1".to_string()).into())
         }
@@ -569,7 +880,8 @@ impl <'a,'b> Environment <'a,'b> {
 
         let result = {
             let mut nested_env = Environment::new(&mut self.global_context, &contract.contract_context,
-                                                  self.call_stack, self.sender.clone(), self.caller.clone());
+                                                  self.call_stack, self.sender.clone(), self.caller.clone())
+                .nested_in(&self.resolver, self.outer_principals.clone());
             let local_context = LocalContext::new();
             eval(&parsed[0], &mut nested_env, &local_context)
         };
@@ -648,7 +960,8 @@ impl <'a,'b> Environment <'a,'b> {
 
         let result = {
             let mut nested_env = Environment::new(&mut self.global_context, next_contract_context, self.call_stack,
-                                                  self.sender.clone(), self.caller.clone());
+                                                  self.sender.clone(), self.caller.clone())
+                .nested_in(&self.resolver, self.outer_principals.clone());
 
             function.execute_apply(args, &mut nested_env)
         };
@@ -827,9 +1140,45 @@ impl <'a> GlobalContext<'a> {
             read_only: Vec::new(),
             asset_maps: Vec::new(),
             event_batches: Vec::new(),
+            contract_call_depth: 0,
+            max_contract_call_depth: MAX_CONTRACT_CALL_DEPTH,
+            #[cfg(any(test, feature = "testing"))]
+            contract_call_interceptor: None,
         }
     }
 
+    pub fn set_max_contract_call_depth(&mut self, max_depth: u32) {
+        self.max_contract_call_depth = max_depth;
+    }
+
+    /// Track entry into a nested contract-call?, erroring if the call would exceed
+    ///   the maximum contract-call depth. Every successful call must be paired with `exit_contract_call`.
+    pub fn enter_contract_call(&mut self) -> Result<()> {
+        if self.contract_call_depth >= self.max_contract_call_depth {
+            Err(RuntimeErrorType::MaxContractCallDepthReached.into())
+        } else {
+            self.contract_call_depth += 1;
+            Ok(())
+        }
+    }
+
+    /// Install a hook which is consulted by every `contract-call?` before it is dispatched.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn set_contract_call_interceptor(&mut self, interceptor: Box<dyn ContractCallInterceptor>) {
+        self.contract_call_interceptor = Some(interceptor);
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    pub fn intercept_contract_call(&mut self, contract_identifier: &QualifiedContractIdentifier, function_name: &str, args: &[Value]) -> Option<(TypeSignature, Value)> {
+        self.contract_call_interceptor.as_mut()
+            .and_then(|interceptor| interceptor.intercept(contract_identifier, function_name, args))
+    }
+
+    pub fn exit_contract_call(&mut self) {
+        self.contract_call_depth = self.contract_call_depth.checked_sub(1)
+            .expect("BUG: exited more contract-calls than were entered");
+    }
+
     pub fn is_top_level(&self) -> bool {
         self.asset_maps.len() == 0
     }
@@ -979,7 +1328,8 @@ impl ContractContext {
             defined_traits: HashMap::new(),
             implemented_traits: HashSet::new(),
             persisted_names: HashSet::new(),
-            data_size: 0
+            persisted_kinds: HashMap::new(),
+            data_size: 0,
         }
     }
 
@@ -987,6 +1337,12 @@ impl ContractContext {
         self.variables.get(name)
     }
 
+    /// The value of a `define-constant`. Constants are the only contract-level
+    ///   variables: data vars are stored in the database.
+    pub fn get_constant(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+
     pub fn lookup_function(&self, name: &str) -> Option<DefinedFunction> {
         self.functions.get(name).cloned()
     }
@@ -1004,6 +1360,26 @@ impl ContractContext {
             self.variables.contains_key(name) || self.functions.contains_key(name) ||
             self.persisted_names.contains(name) || self.defined_traits.contains_key(name)
     }
+
+    pub fn add_persisted_name(&mut self, name: ClarityName, kind: NameKind) {
+        self.persisted_names.insert(name.clone());
+        self.persisted_kinds.insert(name, kind);
+    }
+
+    /// The kind of definition `name` is bound to in this contract, if any.
+    /// The kinds of data vars, maps and tokens are only known if this context
+    ///   was initialized in this process, rather than loaded from the database.
+    pub fn name_kind(&self, name: &str) -> Option<NameKind> {
+        if self.functions.contains_key(name) {
+            Some(NameKind::Function)
+        } else if self.variables.contains_key(name) {
+            Some(NameKind::Constant)
+        } else if self.defined_traits.contains_key(name) {
+            Some(NameKind::Trait)
+        } else {
+            self.persisted_kinds.get(name).cloned()
+        }
+    }
 }
 
 impl <'a> LocalContext <'a> {
@@ -1125,6 +1501,69 @@ mod test {
         assert_eq!(table[&p1][&t1], AssetMapEntry::Token(1));
     }
 
+    #[test]
+    fn test_asset_map_serialization() {
+        let a_contract_id = QualifiedContractIdentifier::local("a").unwrap();
+        let b_contract_id = QualifiedContractIdentifier::local("b").unwrap();
+
+        let p1 = PrincipalData::Contract(a_contract_id.clone());
+        let p2 = PrincipalData::Contract(b_contract_id.clone());
+        let p3 = PrincipalData::parse_standard_principal("SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR").unwrap().into();
+
+        let t1 = AssetIdentifier { contract_identifier: a_contract_id.clone(), asset_name: "a".into() };
+        let t2 = AssetIdentifier { contract_identifier: b_contract_id.clone(), asset_name: "b".into() };
+        let t3 = AssetIdentifier { contract_identifier: b_contract_id.clone(), asset_name: "c".into() };
+
+        let mut am1 = AssetMap::new();
+        am1.add_stx_transfer(&p1, 10).unwrap();
+        am1.add_stx_transfer(&p3, 20).unwrap();
+        am1.add_stx_burn(&p2, 5).unwrap();
+        am1.add_token_transfer(&p1, t1.clone(), 1).unwrap();
+        am1.add_token_transfer(&p1, t2.clone(), u128::max_value()).unwrap();
+        am1.add_token_transfer(&p3, t1.clone(), 3).unwrap();
+        am1.add_asset_transfer(&p2, t3.clone(), Value::Int(1));
+        am1.add_asset_transfer(&p2, t3.clone(), Value::Int(2));
+        am1.add_asset_transfer(&p3, t3.clone(), Value::buff_from(vec![1, 2]).unwrap());
+
+        // the same logical map, built in a different order
+        let mut am2 = AssetMap::new();
+        am2.add_asset_transfer(&p3, t3.clone(), Value::buff_from(vec![1, 2]).unwrap());
+        am2.add_asset_transfer(&p2, t3.clone(), Value::Int(1));
+        am2.add_asset_transfer(&p2, t3.clone(), Value::Int(2));
+        am2.add_token_transfer(&p3, t1.clone(), 3).unwrap();
+        am2.add_token_transfer(&p1, t2.clone(), u128::max_value()).unwrap();
+        am2.add_token_transfer(&p1, t1.clone(), 1).unwrap();
+        am2.add_stx_burn(&p2, 5).unwrap();
+        am2.add_stx_transfer(&p3, 20).unwrap();
+        am2.add_stx_transfer(&p1, 10).unwrap();
+
+        let mut bytes = vec![];
+        am1.consensus_serialize(&mut bytes).unwrap();
+        let mut other_bytes = vec![];
+        am2.consensus_serialize(&mut other_bytes).unwrap();
+        assert_eq!(bytes, other_bytes);
+
+        let decoded = AssetMap::consensus_deserialize(&mut &bytes[..]).unwrap();
+        let mut decoded_bytes = vec![];
+        decoded.consensus_serialize(&mut decoded_bytes).unwrap();
+        assert_eq!(bytes, decoded_bytes);
+
+        assert_eq!(decoded.get_stx(&p3), Some(20));
+        assert_eq!(decoded.get_stx_burned(&p2), Some(5));
+        assert_eq!(decoded.get_fungible_tokens(&p1, &t2), Some(u128::max_value()));
+        assert_eq!(decoded.get_nonfungible_tokens(&p2, &t3), Some(&vec![Value::Int(1), Value::Int(2)]));
+
+        // entries out of order are not a canonical encoding.
+        let mut unordered = vec![];
+        write_next(&mut unordered, &2u32).unwrap();
+        for principal in [&p2, &p1].iter() {
+            write_next(&mut unordered, *principal).unwrap();
+            unordered.extend_from_slice(&1u128.to_be_bytes());
+        }
+        unordered.extend_from_slice(&[0; 12]);
+        assert!(AssetMap::consensus_deserialize(&mut &unordered[..]).is_err());
+    }
+
     #[test]
     fn test_asset_map_combinations() {
         let a_contract_id = QualifiedContractIdentifier::local("a").unwrap();
@@ -107,6 +107,24 @@ impl LimitedCostTracker {
     pub fn get_total(&self) -> ExecutionCost {
         self.total.clone()
     }
+
+    /// Save the cost consumed so far, so that it can be restored if a simulated
+    ///   execution is discarded along with its database changes.
+    pub fn snapshot(&self) -> CostSnapshot {
+        CostSnapshot { total: self.total.clone(), memory: self.memory }
+    }
+
+    pub fn restore(&mut self, snapshot: CostSnapshot) {
+        self.total = snapshot.total;
+        self.memory = snapshot.memory;
+    }
+}
+
+/// The consumed cost and memory of a LimitedCostTracker at some point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostSnapshot {
+    total: ExecutionCost,
+    memory: u64
 }
 
 fn add_cost(s: &mut LimitedCostTracker, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
@@ -147,18 +165,18 @@ impl CostTracker for LimitedCostTracker {
     }
 }
 
-impl CostTracker for &mut LimitedCostTracker {
+impl <T: CostTracker> CostTracker for &mut T {
     fn add_cost(&mut self, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
-        add_cost(self, cost)
+        T::add_cost(self, cost)
     }
     fn add_memory(&mut self, memory: u64) -> std::result::Result<(), CostErrors> {
-        add_memory(self, memory)
+        T::add_memory(self, memory)
     }
     fn drop_memory(&mut self, memory: u64) {
-        drop_memory(self, memory)
+        T::drop_memory(self, memory)
     }
     fn reset_memory(&mut self) {
-        self.memory = 0;
+        T::reset_memory(self)
     }
 }
 
@@ -254,14 +272,46 @@ impl ExecutionCost {
         Ok(())
     }
 
+    /// Returns the cost consumed between an `earlier` total and this one, i.e. the
+    ///   difference in each dimension, saturating at zero.
+    pub fn saturating_sub(&self, earlier: &ExecutionCost) -> ExecutionCost {
+        Self {
+            runtime: self.runtime.saturating_sub(earlier.runtime),
+            write_length: self.write_length.saturating_sub(earlier.write_length),
+            write_count:  self.write_count.saturating_sub(earlier.write_count),
+            read_count:   self.read_count.saturating_sub(earlier.read_count),
+            read_length:  self.read_length.saturating_sub(earlier.read_length)
+        }
+    }
+
+    /// Returns the sum of the two costs, or None if any dimension overflows.
+    pub fn checked_add(&self, other: &ExecutionCost) -> Option<ExecutionCost> {
+        let mut sum = self.clone();
+        sum.add(other).ok()?;
+        Some(sum)
+    }
+
     /// Returns whether or not this cost exceeds any dimension of the
     ///  other cost.
     pub fn exceeds(&self, other: &ExecutionCost) -> bool {
-        self.runtime > other.runtime ||
-            self.write_length > other.write_length ||
-            self.write_count > other.write_count ||
-            self.read_count > other.read_count ||
-            self.read_length > other.read_length
+        self.exceeded_dimension(other).is_some()
+    }
+
+    /// Returns the first dimension in which this cost exceeds the other cost, if any.
+    pub fn exceeded_dimension(&self, other: &ExecutionCost) -> Option<CostDimension> {
+        if self.runtime > other.runtime {
+            Some(CostDimension::Runtime)
+        } else if self.write_length > other.write_length {
+            Some(CostDimension::WriteLength)
+        } else if self.write_count > other.write_count {
+            Some(CostDimension::WriteCount)
+        } else if self.read_count > other.read_count {
+            Some(CostDimension::ReadCount)
+        } else if self.read_length > other.read_length {
+            Some(CostDimension::ReadLength)
+        } else {
+            None
+        }
     }
 
     pub fn max_cost(first: ExecutionCost, second: ExecutionCost) -> ExecutionCost {
@@ -275,6 +325,56 @@ impl ExecutionCost {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostDimension {
+    Runtime,
+    WriteLength,
+    WriteCount,
+    ReadCount,
+    ReadLength,
+}
+
+/// Sums the costs of a block's transactions, refusing any transaction that
+///   would take the block's total past its limit.
+#[derive(Debug, Clone)]
+pub struct BlockCostAccumulator {
+    limit: ExecutionCost,
+    total: ExecutionCost,
+}
+
+impl BlockCostAccumulator {
+    pub fn new(limit: ExecutionCost) -> BlockCostAccumulator {
+        BlockCostAccumulator { limit, total: ExecutionCost::zero() }
+    }
+
+    pub fn total(&self) -> &ExecutionCost {
+        &self.total
+    }
+
+    pub fn limit(&self) -> &ExecutionCost {
+        &self.limit
+    }
+
+    /// Add a transaction's cost to the block total. If the new total would exceed the
+    ///   limit (or overflow), the total is left unchanged and the dimension exceeded is returned.
+    pub fn add_transaction(&mut self, cost: &ExecutionCost) -> std::result::Result<(), CostDimension> {
+        // an overflowing sum exceeds any limit, in the dimension that overflowed
+        let sum = |dimension, total: u64, cost: u64| total.checked_add(cost).ok_or(dimension);
+        let total = ExecutionCost {
+            runtime: sum(CostDimension::Runtime, self.total.runtime, cost.runtime)?,
+            write_length: sum(CostDimension::WriteLength, self.total.write_length, cost.write_length)?,
+            write_count: sum(CostDimension::WriteCount, self.total.write_count, cost.write_count)?,
+            read_count: sum(CostDimension::ReadCount, self.total.read_count, cost.read_count)?,
+            read_length: sum(CostDimension::ReadLength, self.total.read_length, cost.read_length)?,
+        };
+        if let Some(dimension) = total.exceeded_dimension(&self.limit) {
+            return Err(dimension)
+        }
+        self.total = total;
+        Ok(())
+    }
+}
+
 // ONLY WORKS IF INPUT IS u64
 fn int_log2(input: u64) -> Option<u64> {
     63_u32.checked_sub(input.leading_zeros())
@@ -357,6 +457,22 @@ mod unit_tests {
             Err(CostErrors::CostOverflow));
     }
 
+    #[test]
+    fn test_snapshot_restore() {
+        let mut tracker = LimitedCostTracker::new_max_limit();
+        tracker.add_cost(ExecutionCost::runtime(10)).unwrap();
+        tracker.add_memory(5).unwrap();
+
+        let snapshot = tracker.snapshot();
+        tracker.add_cost(ExecutionCost::runtime(100)).unwrap();
+        tracker.add_memory(50).unwrap();
+        assert_eq!(tracker.get_total(), ExecutionCost::runtime(110));
+
+        tracker.restore(snapshot.clone());
+        assert_eq!(tracker.get_total(), ExecutionCost::runtime(10));
+        assert_eq!(tracker.snapshot(), snapshot);
+    }
+
     #[test]
     fn test_simple_log2s() {
         let inputs = [
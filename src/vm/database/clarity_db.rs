@@ -1,10 +1,14 @@
 use std::collections::{VecDeque, HashMap};
 use std::convert::TryFrom;
+use std::cell::RefCell;
+use std::rc::Rc;
 use rusqlite::OptionalExtension;
 
 use vm::contracts::Contract;
 use vm::errors::{Error, InterpreterError, RuntimeErrorType, CheckErrors, InterpreterResult as Result, IncomparableError};
 use vm::types::{Value, OptionalData, TypeSignature, TupleTypeSignature, PrincipalData, StandardPrincipalData, QualifiedContractIdentifier, NONE};
+use vm::types::{FunctionType, FixedFunction, BlockInfoProperty, BuffData};
+use vm::analysis::{AnalysisDatabase, ContractAnalysis};
 
 use chainstate::stacks::index::proofs::TrieMerkleProof;
 use chainstate::stacks::db::{StacksHeaderInfo, MinerPaymentSchedule};
@@ -48,6 +52,99 @@ pub enum StoreType {
 pub struct ClarityDatabase<'a> {
     pub store: RollbackWrapper<'a>,
     headers_db: &'a dyn HeadersDB,
+    observer: Option<Box<dyn DbObserver>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DbAccessKind {
+    Read,
+    Write
+}
+
+/// Observes the data reads and writes of a ClarityDatabase, e.g. to keep an audit trail of
+///   the state a transaction touched. `contract` is the contract owning the key, if any
+///   (account balances and nonces are not owned by a contract), and `value_size` is the
+///   serialized size of the value read or written, or None for a read of a missing key.
+/// The savepoint hooks mirror the database's `begin`, `commit` and `roll_back`.
+pub trait DbObserver {
+    fn on_access(&mut self, kind: DbAccessKind, contract: Option<&str>, key: &str, value_size: Option<u64>);
+    fn on_begin(&mut self) {}
+    fn on_commit(&mut self) {}
+    fn on_roll_back(&mut self) {}
+}
+
+impl <O: DbObserver> DbObserver for Rc<RefCell<O>> {
+    fn on_access(&mut self, kind: DbAccessKind, contract: Option<&str>, key: &str, value_size: Option<u64>) {
+        self.borrow_mut().on_access(kind, contract, key, value_size)
+    }
+    fn on_begin(&mut self) {
+        self.borrow_mut().on_begin()
+    }
+    fn on_commit(&mut self) {
+        self.borrow_mut().on_commit()
+    }
+    fn on_roll_back(&mut self) {
+        self.borrow_mut().on_roll_back()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbAuditEntry {
+    pub kind: DbAccessKind,
+    pub contract: Option<String>,
+    pub key: String,
+    pub value_size: Option<u64>,
+    pub rolled_back: bool
+}
+
+/// A DbObserver recording every access. Entries made inside a savepoint that is later
+///   rolled back are kept, but marked as `rolled_back`.
+#[derive(Debug, Default)]
+pub struct DbAuditLog {
+    entries: Vec<DbAuditEntry>,
+    savepoints: Vec<usize>
+}
+
+impl DbAuditLog {
+    pub fn new() -> DbAuditLog {
+        DbAuditLog::default()
+    }
+
+    pub fn entries(&self) -> &[DbAuditEntry] {
+        &self.entries
+    }
+}
+
+impl DbObserver for DbAuditLog {
+    fn on_access(&mut self, kind: DbAccessKind, contract: Option<&str>, key: &str, value_size: Option<u64>) {
+        self.entries.push(DbAuditEntry {
+            kind, contract: contract.map(|c| c.to_string()), key: key.to_string(), value_size, rolled_back: false });
+    }
+
+    fn on_begin(&mut self) {
+        self.savepoints.push(self.entries.len());
+    }
+
+    fn on_commit(&mut self) {
+        self.savepoints.pop();
+    }
+
+    fn on_roll_back(&mut self) {
+        if let Some(start) = self.savepoints.pop() {
+            for entry in self.entries[start..].iter_mut() {
+                entry.rolled_back = true;
+            }
+        }
+    }
+}
+
+// the contract component of keys built by make_key_for_trip and make_key_for_quad
+fn contract_of_key(key: &str) -> Option<&str> {
+    if key.starts_with("vm::") {
+        key["vm::".len()..].split("::").next()
+    } else {
+        None
+    }
 }
 
 pub trait HeadersDB {
@@ -119,6 +216,64 @@ impl HeadersDB for &dyn HeadersDB {
     }
 }
 
+/// Memoizes the lookups of a wrapped HeadersDB, so that repeated `get-block-info?` reads
+///   of the same block only hit the underlying database once. Entries are keyed on the
+///   index block hash, but the cache is only meant to live as long as a single block connection.
+pub struct CachedHeadersDB<'a> {
+    headers_db: &'a dyn HeadersDB,
+    stacks_block_header_hashes: RefCell<HashMap<BlockHeaderHash, Option<BlockHeaderHash>>>,
+    burn_header_hashes: RefCell<HashMap<BlockHeaderHash, Option<BurnchainHeaderHash>>>,
+    vrf_seeds: RefCell<HashMap<BlockHeaderHash, Option<VRFSeed>>>,
+    burn_block_times: RefCell<HashMap<BlockHeaderHash, Option<u64>>>,
+    miner_addresses: RefCell<HashMap<BlockHeaderHash, Option<StacksAddress>>>,
+}
+
+fn memoized<T: Clone, F>(cache: &RefCell<HashMap<BlockHeaderHash, Option<T>>>, id_bhh: &BlockHeaderHash, fetch: F) -> Option<T>
+where F: FnOnce() -> Option<T> {
+    if let Some(cached) = cache.borrow().get(id_bhh) {
+        return cached.clone()
+    }
+    let fetched = fetch();
+    cache.borrow_mut().insert(*id_bhh, fetched.clone());
+    fetched
+}
+
+impl <'a> CachedHeadersDB <'a> {
+    pub fn new(headers_db: &'a dyn HeadersDB) -> CachedHeadersDB<'a> {
+        CachedHeadersDB {
+            headers_db,
+            stacks_block_header_hashes: RefCell::new(HashMap::new()),
+            burn_header_hashes: RefCell::new(HashMap::new()),
+            vrf_seeds: RefCell::new(HashMap::new()),
+            burn_block_times: RefCell::new(HashMap::new()),
+            miner_addresses: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl HeadersDB for CachedHeadersDB<'_> {
+    fn get_stacks_block_header_hash_for_block(&self, id_bhh: &BlockHeaderHash) -> Option<BlockHeaderHash> {
+        memoized(&self.stacks_block_header_hashes, id_bhh,
+                 || self.headers_db.get_stacks_block_header_hash_for_block(id_bhh))
+    }
+    fn get_burn_header_hash_for_block(&self, id_bhh: &BlockHeaderHash) -> Option<BurnchainHeaderHash> {
+        memoized(&self.burn_header_hashes, id_bhh,
+                 || self.headers_db.get_burn_header_hash_for_block(id_bhh))
+    }
+    fn get_vrf_seed_for_block(&self, id_bhh: &BlockHeaderHash) -> Option<VRFSeed> {
+        memoized(&self.vrf_seeds, id_bhh,
+                 || self.headers_db.get_vrf_seed_for_block(id_bhh))
+    }
+    fn get_burn_block_time_for_block(&self, id_bhh: &BlockHeaderHash) -> Option<u64> {
+        memoized(&self.burn_block_times, id_bhh,
+                 || self.headers_db.get_burn_block_time_for_block(id_bhh))
+    }
+    fn get_miner_address(&self, id_bhh: &BlockHeaderHash) -> Option<StacksAddress> {
+        memoized(&self.miner_addresses, id_bhh,
+                 || self.headers_db.get_miner_address(id_bhh))
+    }
+}
+
 pub struct NullHeadersDB {}
 
 pub const NULL_HEADER_DB: NullHeadersDB = NullHeadersDB {};
@@ -145,27 +300,67 @@ impl <'a> ClarityDatabase <'a> {
     pub fn new(store: &'a mut dyn ClarityBackingStore, headers_db: &'a dyn HeadersDB) -> ClarityDatabase<'a> {
         ClarityDatabase {
             store: RollbackWrapper::new(store),
-            headers_db
+            headers_db,
+            observer: None
         }
     }
 
     pub fn new_with_rollback_wrapper(store: RollbackWrapper<'a>, headers_db: &'a dyn HeadersDB) -> ClarityDatabase<'a> {
-        ClarityDatabase { store, headers_db }
+        ClarityDatabase { store, headers_db, observer: None }
     }
 
     pub fn initialize(&mut self) {
     }
 
+    /// Install an observer notified of every data read and write, and of savepoints.
+    pub fn set_observer(&mut self, observer: Box<dyn DbObserver>) {
+        self.observer = Some(observer);
+    }
+
+    pub fn take_observer(&mut self) -> Option<Box<dyn DbObserver>> {
+        self.observer.take()
+    }
+
+    fn observe(&mut self, kind: DbAccessKind, key: &str, value_size: Option<u64>) {
+        if let Some(ref mut observer) = self.observer {
+            observer.on_access(kind, contract_of_key(key), key, value_size);
+        }
+    }
+
     pub fn begin(&mut self) {
         self.store.nest();
+        if let Some(ref mut observer) = self.observer {
+            observer.on_begin();
+        }
     }
 
     pub fn commit(&mut self) {
         self.store.commit();
+        if let Some(ref mut observer) = self.observer {
+            observer.on_commit();
+        }
     }
 
     pub fn roll_back(&mut self) {
         self.store.rollback();
+        if let Some(ref mut observer) = self.observer {
+            observer.on_roll_back();
+        }
+    }
+
+    /// Run `to_do` inside a nested savepoint: if it returns Ok, the savepoint's edits are
+    ///   committed into the enclosing context, otherwise only the savepoint's edits are rolled back.
+    /// Savepoints may be nested arbitrarily.
+    pub fn with_savepoint<F, R, E>(&mut self, to_do: F) -> std::result::Result<R, E>
+    where F: FnOnce(&mut Self) -> std::result::Result<R, E> {
+        self.begin();
+        let result = to_do(self);
+        if result.is_ok() {
+            self.commit();
+        } else {
+            self.roll_back();
+        }
+        result
     }
 
     pub fn set_block_hash(&mut self, bhh: BlockHeaderHash) -> Result<BlockHeaderHash> {
@@ -173,15 +368,27 @@ impl <'a> ClarityDatabase <'a> {
     }
 
     pub fn put <T: ClaritySerializable> (&mut self, key: &str, value: &T) {
-        self.store.put(&key, &value.serialize());
+        let serialized = value.serialize();
+        self.observe(DbAccessKind::Write, key, Some(serialized.len() as u64));
+        self.store.put(&key, &serialized);
     }
 
-    fn get <T> (&mut self, key: &str) -> Option<T> where T: ClarityDeserializable<T> {
-        self.store.get::<T>(key)
+    fn get <T> (&mut self, key: &str) -> Option<T> where T: ClarityDeserializable<T> + ClaritySerializable {
+        let result = self.store.get::<T>(key);
+        if self.observer.is_some() {
+            let value_size = result.as_ref().map(|x| x.serialize().len() as u64);
+            self.observe(DbAccessKind::Read, key, value_size);
+        }
+        result
     }
 
     pub fn get_value (&mut self, key: &str, expected: &TypeSignature) -> Option<Value> {
-        self.store.get_value(key, expected)
+        let result = self.store.get_value(key, expected);
+        if self.observer.is_some() {
+            let value_size = result.as_ref().map(|x| x.serialize().len() as u64);
+            self.observe(DbAccessKind::Read, key, value_size);
+        }
+        result
     }
 
     pub fn get_with_proof <T> (&mut self, key: &str) -> Option<(T, TrieMerkleProof)> where T: ClarityDeserializable<T> {
@@ -200,6 +407,18 @@ impl <'a> ClarityDatabase <'a> {
         format!("vm::{}::{}::{}::{}", contract_identifier, data as u8, var_name, key_value)
     }
 
+    /// whether `key` is one which can be enumerated by prefix, i.e., one that a
+    ///   backing store with a key index needs to record.
+    pub fn is_enumerable_key(key: &str) -> bool {
+        let mut segments = key.splitn(4, "::");
+        match (segments.next(), segments.nth(1)) {
+            (Some("vm"), Some(store_type)) =>
+                store_type == (StoreType::DataMap as u8).to_string() ||
+                store_type == (StoreType::NonFungibleToken as u8).to_string(),
+            _ => false
+        }
+    }
+
     pub fn insert_contract_hash(&mut self, contract_identifier: &QualifiedContractIdentifier, contract_content: &str) -> Result<()> {
         let hash = Sha512Trunc256Sum::from_data(contract_content.as_bytes());
         self.store.prepare_for_contract_metadata(contract_identifier, hash);
@@ -276,6 +495,42 @@ impl <'a> ClarityDatabase <'a> {
         Ok(data)
     }
 
+    /// Checks the arguments of a call to a public or read-only function against the
+    ///   parameter types recorded in the contract's stored analysis, without executing it.
+    ///   Argument errors are returned as `Error::Unchecked`.
+    pub fn check_contract_call_args(&mut self, contract_identifier: &QualifiedContractIdentifier, function_name: &str, args: &[Value]) -> Result<()> {
+        let analysis: ContractAnalysis = self.fetch_metadata(contract_identifier, AnalysisDatabase::storage_key())?
+            .ok_or_else(|| CheckErrors::NoSuchContract(contract_identifier.to_string()))?;
+
+        let function_type = analysis.get_public_function_type(function_name)
+            .or_else(|| analysis.get_read_only_function_type(function_name))
+            .ok_or_else(|| CheckErrors::NoSuchPublicFunction(contract_identifier.to_string(), function_name.to_string()))?;
+
+        let expected_args = match function_type {
+            FunctionType::Fixed(FixedFunction { args, .. }) => args,
+            // defined functions are always analyzed to fixed signatures
+            _ => return Err(CheckErrors::NoSuchPublicFunction(contract_identifier.to_string(), function_name.to_string()).into())
+        };
+
+        if expected_args.len() != args.len() {
+            return Err(CheckErrors::IncorrectArgumentCount(expected_args.len(), args.len()).into())
+        }
+
+        for (index, (expected, value)) in expected_args.iter().zip(args.iter()).enumerate() {
+            let admitted = match (&expected.signature, value) {
+                // trait compliance of the passed contract is checked when the call executes
+                (TypeSignature::TraitReferenceType(_), Value::Principal(PrincipalData::Contract(_))) => true,
+                (expected_type, _) => expected_type.admits_type(&TypeSignature::type_of(value))
+            };
+            if !admitted {
+                return Err(CheckErrors::ArgumentTypeError {
+                    index, expected: expected.signature.clone(), found: value.clone() }.into())
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn destroy(self) -> RollbackWrapper<'a> {
         self.store
     }
@@ -325,6 +580,64 @@ impl <'a> ClarityDatabase <'a> {
             .expect("Failed to get block data.")
             .into()
     }
+
+    /// Fetches the `get-block-info?` property of the block at `block_height`. As with the other
+    ///   block getters, the caller is responsible for ensuring that `block_height` is less than
+    ///   the current block height.
+    pub fn get_block_info(&mut self, block_height: u32, property: &BlockInfoProperty) -> Value {
+        let id_bhh = self.get_index_block_header_hash(block_height);
+        self.get_block_info_for(&id_bhh, property)
+    }
+
+    fn get_block_info_for(&self, id_bhh: &BlockHeaderHash, property: &BlockInfoProperty) -> Value {
+        match property {
+            BlockInfoProperty::Time => {
+                let block_time = self.headers_db.get_burn_block_time_for_block(id_bhh)
+                    .expect("Failed to get block data.");
+                Value::UInt(block_time as u128)
+            },
+            BlockInfoProperty::VrfSeed => {
+                let vrf_seed = self.headers_db.get_vrf_seed_for_block(id_bhh)
+                    .expect("Failed to get block data.");
+                Value::Buffer(BuffData { data: vrf_seed.as_bytes().to_vec() })
+            },
+            BlockInfoProperty::HeaderHash => {
+                let header_hash = self.headers_db.get_stacks_block_header_hash_for_block(id_bhh)
+                    .expect("Failed to get block data.");
+                Value::Buffer(BuffData { data: header_hash.as_bytes().to_vec() })
+            },
+            BlockInfoProperty::BurnchainHeaderHash => {
+                let burnchain_header_hash = self.headers_db.get_burn_header_hash_for_block(id_bhh)
+                    .expect("Failed to get block data.");
+                Value::Buffer(BuffData { data: burnchain_header_hash.as_bytes().to_vec() })
+            },
+            BlockInfoProperty::IdentityHeaderHash => {
+                Value::Buffer(BuffData { data: id_bhh.as_bytes().to_vec() })
+            },
+            BlockInfoProperty::MinerAddress => {
+                let miner_address: StandardPrincipalData = self.headers_db.get_miner_address(id_bhh)
+                    .expect("Failed to get block data.")
+                    .into();
+                Value::from(miner_address)
+            },
+        }
+    }
+
+    /// Fetches several `get-block-info?` properties of the block at `block_height`, resolving
+    ///   the block's index header hash only once. Each value has the type given by the
+    ///   property's `type_result()`.
+    pub fn get_block_info_bulk(&mut self, block_height: u32, properties: &[BlockInfoProperty]) -> Result<Vec<Value>> {
+        if block_height >= self.get_current_block_height() {
+            return Err(RuntimeErrorType::BadBlockHeight(block_height.to_string()).into())
+        }
+
+        let id_bhh = self.get_index_block_header_hash(block_height);
+        let values = properties.iter()
+            .map(|property| self.get_block_info_for(&id_bhh, property))
+            .collect();
+
+        Ok(values)
+    }
 }
 
 // this is used so that things like load_map, load_var, load_nft, etc.
@@ -421,6 +734,32 @@ impl <'a> ClarityDatabase <'a> {
         }
     }
 
+    /// Enumerate every entry of a data map as of the current chain tip, returning the
+    ///   decoded (key, value) pairs in order of their serialized keys.
+    /// This is _not_ available to contract code (map iteration has no consensus-defined cost
+    ///   or ordering), and is only intended for host-side, read-only inspection.
+    /// Fails with `KeyIndexUnavailable` if the backing store does not keep a complete
+    ///   key index (see `MarfedKV::enable_key_index`).
+    pub fn map_entries(&mut self, contract_identifier: &QualifiedContractIdentifier, map_name: &str) -> Result<Vec<(Value, Value)>> {
+        let map_descriptor = self.load_map(contract_identifier, map_name)?;
+        let stored_type = TypeSignature::new_option(map_descriptor.value_type)?;
+
+        let prefix = ClarityDatabase::make_key_for_quad(contract_identifier, StoreType::DataMap, map_name, "".to_string());
+        let keys = self.store.get_keys_with_prefix(&prefix)
+            .ok_or(InterpreterError::KeyIndexUnavailable)?;
+        let mut entries = Vec::new();
+        for key in keys {
+            // deleted entries are stored as none, and keys written in other
+            //   forks (or later blocks) will not be set at this chain tip.
+            if let Some(Value::Optional(OptionalData { data: Some(value) })) = self.get_value(&key, &stored_type) {
+                let key_value = Value::deserialize(&key[prefix.len()..], &map_descriptor.key_type);
+                entries.push((key_value, *value));
+            }
+        }
+
+        Ok(entries)
+    }
+
     pub fn set_entry(&mut self, contract_identifier: &QualifiedContractIdentifier, map_name: &str, key: Value, value: Value) -> Result<Value> {
         self.inner_set_entry(contract_identifier, map_name, key, value, false)
     }
@@ -537,6 +876,21 @@ impl <'a> ClarityDatabase <'a> {
         }
     }
 
+    /// The number of tokens minted so far, or None if the token's supply is not tracked.
+    /// Supply is only tracked for tokens which declare a maximum supply.
+    pub fn get_ft_supply(&mut self, contract_identifier: &QualifiedContractIdentifier, token_name: &str) -> Result<Option<u128>> {
+        self.load_ft(contract_identifier, token_name)?;
+
+        let key = ClarityDatabase::make_key_for_trip(contract_identifier, StoreType::CirculatingSupply, token_name);
+        Ok(self.get(&key))
+    }
+
+    /// The maximum supply declared by `define-fungible-token`, if any.
+    pub fn get_ft_cap(&mut self, contract_identifier: &QualifiedContractIdentifier, token_name: &str) -> Result<Option<u128>> {
+        let descriptor = self.load_ft(contract_identifier, token_name)?;
+        Ok(descriptor.total_supply)
+    }
+
     pub fn get_ft_balance(&mut self, contract_identifier: &QualifiedContractIdentifier, token_name: &str, principal: &PrincipalData) -> Result<u128> {
         self.load_ft(contract_identifier, token_name)?;
 
@@ -573,6 +927,29 @@ impl <'a> ClarityDatabase <'a> {
         Ok(descriptor.key_type)
     }
 
+    /// Enumerate the token ids of a non-fungible token which are owned by `owner` as of the
+    ///   current chain tip, in order of their serialized ids.
+    /// Like `map_entries`, this is only intended for host-side, read-only inspection, and
+    ///   fails with `KeyIndexUnavailable` unless the backing store keeps a complete key index.
+    pub fn nft_tokens_owned(&mut self, contract_identifier: &QualifiedContractIdentifier, asset_name: &str, owner: &PrincipalData) -> Result<Vec<Value>> {
+        let descriptor = self.load_nft(contract_identifier, asset_name)?;
+
+        let prefix = ClarityDatabase::make_key_for_quad(contract_identifier, StoreType::NonFungibleToken, asset_name, "".to_string());
+        let keys = self.store.get_keys_with_prefix(&prefix)
+            .ok_or(InterpreterError::KeyIndexUnavailable)?;
+        let mut tokens = Vec::new();
+        for key in keys {
+            // keys written in other forks (or later blocks) will not be set at this chain tip.
+            if let Some(current_owner) = self.get::<PrincipalData>(&key) {
+                if &current_owner == owner {
+                    tokens.push(Value::deserialize(&key[prefix.len()..], &descriptor.key_type));
+                }
+            }
+        }
+
+        Ok(tokens)
+    }
+
     pub fn set_nft_owner(&mut self, contract_identifier: &QualifiedContractIdentifier, asset_name: &str, asset: &Value, principal: &PrincipalData) -> Result<()> {
         let descriptor = self.load_nft(contract_identifier, asset_name)?;
         if !descriptor.key_type.admits(asset) {
@@ -2,7 +2,7 @@ use super::{MarfedKV, ClarityBackingStore, ClarityDeserializable};
 use vm::Value;
 use vm::errors::{ InterpreterResult as Result };
 use chainstate::{ burn::BlockHeaderHash, stacks::index::proofs::TrieMerkleProof };
-use std::collections::{HashMap};
+use std::collections::{HashMap, BTreeSet};
 use util::hash::{Sha512Trunc256Sum};
 use vm::types::{QualifiedContractIdentifier, TypeSignature};
 use std::{cmp::Eq, hash::Hash, clone::Clone};
@@ -321,6 +321,21 @@ impl <'a> RollbackWrapper <'a> {
         }
     }
 
+    /// returns the keys which begin with `prefix` and may be set, either
+    ///   in the edit log or in the underlying store, or `None` if the
+    ///   underlying store cannot enumerate its keys.
+    pub fn get_keys_with_prefix(&mut self, prefix: &str) -> Option<Vec<String>> {
+        self.stack.last()
+            .expect("ERROR: Clarity VM attempted GET on non-nested context.");
+        let mut keys: BTreeSet<String> = self.store.get_keys_with_prefix(prefix)?
+            .into_iter()
+            .collect();
+        keys.extend(self.lookup_map.keys()
+                    .filter(|key| key.starts_with(prefix))
+                    .cloned());
+        Some(keys.into_iter().collect())
+    }
+
     pub fn has_metadata_entry(&mut self, contract: &QualifiedContractIdentifier, key: &str) -> bool {
         match self.get_metadata(contract, key) {
             Ok(Some(_)) => true,
@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::collections::HashMap;
 
 use vm::types::{QualifiedContractIdentifier};
 use vm::errors::{InterpreterError, CheckErrors, InterpreterResult as Result, IncomparableError, RuntimeErrorType};
@@ -26,7 +27,9 @@ pub struct MarfedKV {
     marf: MARF,
     // Since the MARF only stores 32 bytes of value,
     //   we need another storage
-    side_store: SqliteConnection
+    side_store: SqliteConnection,
+    // whether enumerable keys are recorded in the side store's key index
+    key_index: bool
 }
 
 pub struct MemoryBackingStore {
@@ -46,6 +49,14 @@ pub trait ClarityBackingStore {
         self.get(key).is_some()
     }
 
+    /// returns the keys which begin with `prefix` and _may_ be set in the committed
+    ///   datastore. callers must still `get()` each key to check whether it is set
+    ///   as of the current chain tip. only keys for which `ClarityDatabase::is_enumerable_key`
+    ///   holds need to be returned, and stores which cannot enumerate them return `None`.
+    fn get_keys_with_prefix(&mut self, _prefix: &str) -> Option<Vec<String>> {
+        None
+    }
+
     /// change the current MARF context to service reads from a different chain_tip
     ///   used to implement time-shifted evaluation.
     /// returns the previous block header hash on success
@@ -148,7 +159,10 @@ impl MarfedKV {
             None => TrieFileStorage::block_sentinel()
         };
 
-        Ok( MarfedKV { marf, chain_tip, side_store } )
+        // once enabled, the key index must be maintained by every later writer.
+        let key_index = side_store.has_key_index();
+
+        Ok( MarfedKV { marf, chain_tip, side_store, key_index } )
     }
 
     // used by benchmarks
@@ -166,7 +180,17 @@ impl MarfedKV {
 
         let chain_tip = TrieFileStorage::block_sentinel();
 
-        MarfedKV { marf, chain_tip, side_store }
+        MarfedKV { marf, chain_tip, side_store, key_index: false }
+    }
+
+    /// Start recording enumerable keys (data map entries and nft owners) in the side store, so that
+    ///   they can be listed with `get_keys_with_prefix`. The index is persisted, and is only
+    ///   complete if it is enabled before any data is written, so this fails with
+    ///   `KeyIndexUnavailable` on a store which already holds data.
+    pub fn enable_key_index(&mut self) -> Result<()> {
+        self.side_store.create_key_index()?;
+        self.key_index = true;
+        Ok(())
     }
 
     pub fn as_clarity_db<'a>(&'a mut self, headers_db: &'a dyn HeadersDB) -> ClarityDatabase<'a> {
@@ -244,6 +268,9 @@ impl MarfedKV {
     pub fn put(&mut self, key: &str, value: &str) {
         let marf_value = MARFValue::from_value(value);
         self.side_store.put(&marf_value.to_hex(), value);
+        if self.key_index && ClarityDatabase::is_enumerable_key(key) {
+            self.side_store.index_key(key);
+        }
 
         self.marf.insert(key, marf_value)
             .expect("ERROR: Unexpected MARF Failure")
@@ -331,12 +358,25 @@ impl ClarityBackingStore for MarfedKV {
             })
     }
 
+    fn get_keys_with_prefix(&mut self, prefix: &str) -> Option<Vec<String>> {
+        // the MARF only stores hashed keys, so we rely on the
+        //   side store's key index for enumeration.
+        if self.key_index {
+            Some(self.side_store.get_indexed_keys_with_prefix(prefix))
+        } else {
+            None
+        }
+    }
+
     fn put_all(&mut self, mut items: Vec<(String, String)>) {
         let mut keys = Vec::new();
         let mut values = Vec::new();
         for (key, value) in items.drain(..) {
             let marf_value = MARFValue::from_value(&value);
             self.side_store.put(&marf_value.to_hex(), &value);
+            if self.key_index && ClarityDatabase::is_enumerable_key(&key) {
+                self.side_store.index_key(&key);
+            }
             keys.push(key);
             values.push(marf_value);
         }
@@ -405,9 +445,120 @@ impl ClarityBackingStore for MemoryBackingStore {
         0
     }
 
+    fn get_keys_with_prefix(&mut self, prefix: &str) -> Option<Vec<String>> {
+        Some(self.side_store.get_keys_with_prefix(prefix))
+    }
+
     fn put_all(&mut self, mut items: Vec<(String, String)>) {
         for (key, value) in items.drain(..) {
             self.side_store.put(&key, &value);
         }
     }
 }
+
+/// The SpeculativeKV struct overlays an in-memory K/V store on top of a MarfedKV's
+///   chain tip. Reads of keys which have not been written fall through to the MARF, and
+///   writes (including contract metadata) only ever land in the overlay, so dropping the
+///   SpeculativeKV discards every change made through it.
+pub struct SpeculativeKV {
+    base: MarfedKV,
+    fork_tip: BlockHeaderHash,
+    fork_height: u32,
+    // set while the base is servicing reads from a block other than the fork tip,
+    //   during which the overlay is bypassed.
+    time_shifted: bool,
+    data: HashMap<String, String>,
+    metadata: HashMap<(QualifiedContractIdentifier, String), String>
+}
+
+impl SpeculativeKV {
+    pub fn new(mut base: MarfedKV, fork_tip: &BlockHeaderHash) -> SpeculativeKV {
+        base.set_chain_tip(fork_tip);
+        let fork_height = base.get_current_block_height();
+        SpeculativeKV {
+            base, fork_height,
+            fork_tip: *fork_tip,
+            time_shifted: false,
+            data: HashMap::new(),
+            metadata: HashMap::new()
+        }
+    }
+
+    /// Discard the overlay, recovering the underlying MarfedKV.
+    pub fn into_inner(self) -> MarfedKV {
+        self.base
+    }
+}
+
+impl ClarityBackingStore for SpeculativeKV {
+    fn get_side_store(&mut self) -> &mut SqliteConnection {
+        self.base.get_side_store()
+    }
+
+    fn set_block_hash(&mut self, bhh: BlockHeaderHash) -> Result<BlockHeaderHash> {
+        let result = self.base.set_block_hash(bhh)?;
+        self.time_shifted = bhh != self.fork_tip;
+        Ok(result)
+    }
+
+    fn get_current_block_height(&mut self) -> u32 {
+        self.base.get_current_block_height()
+    }
+
+    fn get_block_at_height(&mut self, block_height: u32) -> Option<BlockHeaderHash> {
+        self.base.get_block_at_height(block_height)
+    }
+
+    fn get_open_chain_tip(&mut self) -> BlockHeaderHash {
+        self.fork_tip
+    }
+
+    fn get_open_chain_tip_height(&mut self) -> u32 {
+        self.fork_height
+    }
+
+    fn get_with_proof(&mut self, key: &str) -> Option<(String, TrieMerkleProof)> {
+        match self.data.get(key) {
+            Some(value) if !self.time_shifted => Some((value.clone(), TrieMerkleProof(vec![]))),
+            _ => self.base.get_with_proof(key)
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<String> {
+        match self.data.get(key) {
+            Some(value) if !self.time_shifted => Some(value.clone()),
+            _ => self.base.get(key)
+        }
+    }
+
+    fn get_keys_with_prefix(&mut self, prefix: &str) -> Option<Vec<String>> {
+        let mut keys = self.base.get_keys_with_prefix(prefix)?;
+        if !self.time_shifted {
+            keys.extend(self.data.keys()
+                        .filter(|key| key.starts_with(prefix) && !keys.contains(key))
+                        .cloned()
+                        .collect::<Vec<_>>());
+        }
+        Some(keys)
+    }
+
+    fn put_all(&mut self, mut items: Vec<(String, String)>) {
+        for (key, value) in items.drain(..) {
+            self.data.insert(key, value);
+        }
+    }
+
+    fn insert_metadata(&mut self, contract: &QualifiedContractIdentifier, key: &str, value: &str) {
+        self.metadata.insert((contract.clone(), key.to_string()), value.to_string());
+    }
+
+    fn get_metadata(&mut self, contract: &QualifiedContractIdentifier, key: &str) -> Result<Option<String>> {
+        if !self.time_shifted {
+            if let Some(value) = self.metadata.get(&(contract.clone(), key.to_string())) {
+                return Ok(Some(value.clone()))
+            }
+        }
+        let (bhh, _) = self.get_contract_hash(contract)?;
+        Ok(self.base.get_side_store().get_metadata(&bhh, &contract.to_string(), key))
+    }
+}
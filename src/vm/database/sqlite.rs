@@ -32,6 +32,16 @@ fn sqlite_has_entry(conn: &Connection, key: &str) -> bool {
     sqlite_get(conn, key).is_some()
 }
 
+fn sqlite_keys_with_prefix(conn: &Connection, table: &str, prefix: &str) -> Vec<String> {
+    let params: [&dyn ToSql; 2] = [&(prefix.len() as i64), &prefix];
+    let mut stmt = conn.prepare(&format!("SELECT key FROM {} WHERE substr(key, 1, ?) = ? ORDER BY key", table))
+        .expect(SQL_FAIL_MESSAGE);
+    let rows = stmt.query_map(&params, |row| row.get(0))
+        .expect(SQL_FAIL_MESSAGE);
+    rows.map(|row| row.expect(SQL_FAIL_MESSAGE))
+        .collect()
+}
+
 impl SqliteConnection {
     pub fn put(&mut self, key: &str, value: &str) {
         sqlite_put(&self.conn, key, value)
@@ -41,6 +51,54 @@ impl SqliteConnection {
         sqlite_get(&self.conn, key)
     }
 
+    /// returns every key stored in the data table which begins with `prefix`
+    pub fn get_keys_with_prefix(&mut self, prefix: &str) -> Vec<String> {
+        sqlite_keys_with_prefix(&self.conn, "data_table", prefix)
+    }
+
+    /// whether written keys are being recorded in the key table (see `create_key_index`)
+    pub fn has_key_index(&self) -> bool {
+        let sql = "SELECT sql FROM sqlite_master WHERE name=?";
+        self.conn.query_row(sql, &["key_table"], |row| row.get::<_, String>(0))
+            .optional()
+            .expect(SQL_FAIL_MESSAGE)
+            .is_some()
+    }
+
+    /// create the key table. the index is only complete if it exists before any data
+    ///   is written, so this fails on a store which already holds data.
+    pub fn create_key_index(&mut self) -> Result<()> {
+        if self.has_key_index() {
+            return Ok(())
+        }
+
+        let has_data = self.conn.query_row("SELECT key FROM data_table LIMIT 1", NO_PARAMS, |row| row.get::<_, String>(0))
+            .optional()
+            .expect(SQL_FAIL_MESSAGE)
+            .is_some();
+        if has_data {
+            return Err(InterpreterError::KeyIndexUnavailable.into())
+        }
+
+        self.conn.execute("CREATE TABLE key_table
+                      (key TEXT PRIMARY KEY)", NO_PARAMS)
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError{ err: x }))?;
+        Ok(())
+    }
+
+    /// record that `key` has been written to. the key table is only an index:
+    ///   it may contain keys which are not set at a given chain tip.
+    pub fn index_key(&mut self, key: &str) {
+        let params: [&dyn ToSql; 1] = [&key];
+        self.conn.execute("INSERT OR IGNORE INTO key_table (key) VALUES (?)", &params)
+            .expect(SQL_FAIL_MESSAGE);
+    }
+
+    /// returns every indexed key which begins with `prefix`
+    pub fn get_indexed_keys_with_prefix(&mut self, prefix: &str) -> Vec<String> {
+        sqlite_keys_with_prefix(&self.conn, "key_table", prefix)
+    }
+
     pub fn insert_metadata(&mut self, bhh: &BlockHeaderHash, contract_hash: &str, key: &str, value: &str) {
         let key = format!("clr-meta::{}::{}", contract_hash, key);
         let params: [&dyn ToSql; 3] = [&bhh, &key, &value.to_string()];
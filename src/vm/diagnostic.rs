@@ -3,11 +3,13 @@ use std::fmt;
 
 /// In a near future, we can go further in our static analysis and provide different levels 
 /// of diagnostics, such as warnings, hints, best practices, etc.
-#[derive(Debug)]
+/// Warnings are advisory: they never fail the parsing or analysis of a contract.
+#[derive(Debug, Clone)]
 #[derive(Serialize, Deserialize)]
 #[derive(PartialEq)]
 pub enum Level {
     Error,
+    Warning,
 }
 
 pub trait DiagnosableError {
@@ -15,7 +17,7 @@ pub trait DiagnosableError {
     fn suggestion(&self) -> Option<String>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[derive(Serialize, Deserialize)]
 #[derive(PartialEq)]
 pub struct Diagnostic {
@@ -35,6 +37,15 @@ impl Diagnostic {
             suggestion: error.suggestion(),
         }
     }
+
+    pub fn warning(message: String, spans: Vec<Span>) -> Diagnostic {
+        Diagnostic {
+            spans,
+            level: Level::Warning,
+            message,
+            suggestion: None,
+        }
+    }
 }
 
 impl fmt::Display for Diagnostic {
@@ -53,3 +64,44 @@ impl fmt::Display for Diagnostic {
         write!(f, "\n")
     }
 }
+
+/// Collects the diagnostics reported by the AST and analysis passes,
+///   so that tools can present every problem found in a contract together.
+#[derive(Debug, Default)]
+pub struct DiagnosticSink {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticSink {
+    pub fn new() -> DiagnosticSink {
+        DiagnosticSink { diagnostics: vec![] }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Record the diagnostic of a failed result, if any, returning the successful value.
+    pub fn capture<T, E: Into<Diagnostic>>(&mut self, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.push(e.into());
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// The collected diagnostics, sorted by the position of their first span.
+    ///   Diagnostics without a span come first.
+    pub fn into_sorted(mut self) -> Vec<Diagnostic> {
+        self.diagnostics.sort_by_key(|diagnostic| {
+            diagnostic.spans.first().map(|span| (span.start_line, span.start_column))
+        });
+        self.diagnostics
+    }
+}
@@ -259,7 +259,7 @@ const LESS_API: SimpleFunctionAPI = SimpleFunctionAPI {
 
 fn make_for_simple_native(api: &SimpleFunctionAPI, function: &NativeFunctions, name: String) -> FunctionAPI {
     let (input_type, output_type) = {
-        if let TypedNativeFunction::Simple(SimpleNativeFunction(function_type)) = TypedNativeFunction::type_native_function(&function) {
+        if let TypedNativeFunction::Simple(SimpleNativeFunction(function_type)) = TypedNativeFunction::<()>::type_native_function(&function) {
             let input_type = match function_type {
                 FunctionType::Variadic(ref in_type, _) => {
                     format!("{}, ...", in_type)
@@ -43,6 +43,8 @@ pub enum InterpreterError {
     MarfFailure(IncomparableError<MarfError>),
     FailureConstructingTupleWithType,
     FailureConstructingListWithType,
+    // the backing store does not keep a complete index of its keys
+    KeyIndexUnavailable,
 }
 
 
@@ -61,6 +63,7 @@ pub enum RuntimeErrorType {
     ASTError(ParseError),
     MaxStackDepthReached,
     MaxContextDepthReached,
+    MaxContractCallDepthReached,
     ListDimensionTooHigh,
     BadTypeConstruction,
     ValueTooLarge,
@@ -76,6 +79,8 @@ pub enum RuntimeErrorType {
     UnknownBlockHeaderHash(BlockHeaderHash),
     BadBlockHash(Vec<u8>),
     UnwrapFailure,
+    BadNonce(u64, u64),
+    UnsupportedRadix(u32),
 }
 
 #[derive(Debug, PartialEq)]
@@ -4,7 +4,8 @@ use std::cmp;
 use vm::functions::tuples;
 use vm::functions::tuples::TupleDefinitionType::{Implicit, Explicit};
 
-use vm::types::{Value, OptionalData, BuffData, PrincipalData, BlockInfoProperty, TypeSignature, BUFF_32};
+use vm::types::{Value, OptionalData, BuffData, PrincipalData, BlockInfoProperty, TypeSignature, BUFF_32,
+                QualifiedContractIdentifier};
 use vm::representations::{SymbolicExpression, SymbolicExpressionType};
 use vm::errors::{CheckErrors, InterpreterError, RuntimeErrorType, InterpreterResult as Result,
                  check_argument_count, check_arguments_at_least};
@@ -97,10 +98,13 @@ pub fn special_contract_call(args: &[SymbolicExpression],
         env.contract_context.contract_identifier.clone()));
     let mut nested_env = env.nest_with_caller(contract_principal);
 
-    let result = nested_env.execute_contract(&contract_identifier, 
-                                             function_name, 
-                                             &rest_args,
-                                             false)?;
+    nested_env.global_context.enter_contract_call()?;
+    let result = dispatch_contract_call(&mut nested_env,
+                                        &contract_identifier,
+                                        function_name,
+                                        &rest_args);
+    nested_env.global_context.exit_contract_call();
+    let result = result?;
     
     // Ensure that the expected type from the trait spec admits
     // the type of the value returned by the dynamic dispatch.
@@ -113,6 +117,44 @@ pub fn special_contract_call(args: &[SymbolicExpression],
     Ok(result)
 }
 
+fn dispatch_contract_call(env: &mut Environment, contract_identifier: &QualifiedContractIdentifier,
+                          function_name: &str, args: &[SymbolicExpression]) -> Result<Value> {
+    #[cfg(any(test, feature = "testing"))]
+    {
+        if let Some(result) = intercept_contract_call(env, contract_identifier, function_name, args)? {
+            return Ok(result)
+        }
+    }
+    env.execute_contract(contract_identifier, function_name, args, false)
+}
+
+// in test builds, the call is first offered to the contract-call interceptor, if one is installed.
+#[cfg(any(test, feature = "testing"))]
+fn intercept_contract_call(env: &mut Environment, contract_identifier: &QualifiedContractIdentifier,
+                           function_name: &str, args: &[SymbolicExpression]) -> Result<Option<Value>> {
+    let arg_values: Result<Vec<Value>> = args.iter()
+        .map(|arg| {
+            let value = arg.match_atom_value()
+                .ok_or_else(|| InterpreterError::InterpreterError(format!("Passed non-value expression to contract-call? of {}!",
+                                                                          function_name)))?;
+            Ok(value.clone())
+        })
+        .collect();
+    let arg_values = arg_values?;
+
+    match env.global_context.intercept_contract_call(contract_identifier, function_name, &arg_values) {
+        Some((returns_type_signature, result)) => {
+            runtime_cost!(cost_functions::USER_FUNCTION_APPLICATION, env, arg_values.len())?;
+            let actual_returns = TypeSignature::type_of(&result);
+            if !returns_type_signature.admits_type(&actual_returns) {
+                return Err(CheckErrors::ReturnTypesMustMatch(returns_type_signature, actual_returns).into())
+            }
+            Ok(Some(result))
+        },
+        None => Ok(None)
+    }
+}
+
 pub fn special_fetch_variable(args: &[SymbolicExpression],
                               env: &mut Environment,
                               _context: &LocalContext) -> Result<Value> {
@@ -346,32 +388,7 @@ pub fn special_get_block_info(args: &[SymbolicExpression],
         return Ok(Value::none())
     }
 
-    let result = match block_info_prop {
-        BlockInfoProperty::Time => {
-            let block_time = env.global_context.database.get_block_time(height_value);
-            Value::UInt(block_time as u128)
-        },
-        BlockInfoProperty::VrfSeed => {
-            let vrf_seed = env.global_context.database.get_block_vrf_seed(height_value);
-            Value::Buffer(BuffData { data: vrf_seed.as_bytes().to_vec() })
-        },
-        BlockInfoProperty::HeaderHash => {
-            let header_hash = env.global_context.database.get_block_header_hash(height_value);
-            Value::Buffer(BuffData { data: header_hash.as_bytes().to_vec() })
-        },
-        BlockInfoProperty::BurnchainHeaderHash => {
-            let burnchain_header_hash = env.global_context.database.get_burnchain_block_header_hash(height_value);
-            Value::Buffer(BuffData { data: burnchain_header_hash.as_bytes().to_vec() })
-        },
-        BlockInfoProperty::IdentityHeaderHash => {
-            let id_header_hash = env.global_context.database.get_index_block_header_hash(height_value);
-            Value::Buffer(BuffData { data: id_header_hash.as_bytes().to_vec() })            
-        },
-        BlockInfoProperty::MinerAddress => {
-            let miner_address = env.global_context.database.get_miner_address(height_value);
-            Value::from(miner_address)
-        },
-    };
+    let result = env.global_context.database.get_block_info(height_value, &block_info_prop);
     
     Ok(Value::some(result)?)
 }
@@ -200,7 +200,7 @@ macro_rules! native_hash_func {
                 _ => Err(CheckErrors::UnionTypeValueError(vec![TypeSignature::IntType, TypeSignature::UIntType, TypeSignature::max_buffer()], input))
             }?;
             let hash = <$module>::from_data(&bytes);
-            Value::buff_from(hash.as_bytes().to_vec())
+            Value::buff_from_slice(hash.as_bytes())
         }
     }
 }
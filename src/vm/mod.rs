@@ -28,7 +28,7 @@ pub mod tests;
 
 pub use vm::types::Value;
 use vm::callables::CallableType;
-use vm::contexts::{ContractContext, LocalContext, Environment, CallStack};
+use vm::contexts::{ContractContext, LocalContext, Environment, CallStack, NameKind};
 use vm::contexts::{GlobalContext};
 use vm::functions::define::DefineResult;
 use vm::errors::{Error, InterpreterError, RuntimeErrorType, CheckErrors, InterpreterResult as Result};
@@ -58,6 +58,9 @@ fn lookup_variable(name: &str, context: &LocalContext, env: &mut Environment) ->
             }  else if let Some(value) = context.callable_contracts.get(name) {
                 let contract_identifier = &value.0;
                 Ok(Value::Principal(PrincipalData::Contract(contract_identifier.clone())))
+            } else if let Some(value) = env.resolver.as_ref().and_then(|resolver| resolver.resolve_variable(name)) {
+                runtime_cost!(cost_functions::LOOKUP_VARIABLE_SIZE, env, value.size())?;
+                Ok(value)
             } else {
                 Err(CheckErrors::UndefinedVariable(name.to_string()).into())
             }
@@ -71,9 +74,12 @@ pub fn lookup_function(name: &str, env: &mut Environment)-> Result<CallableType>
     if let Some(result) = functions::lookup_reserved_functions(name) {
         Ok(result)
     } else {
-        let user_function = env.contract_context.lookup_function(name).ok_or(
-            CheckErrors::UndefinedFunction(name.to_string()))?;
-        Ok(CallableType::UserFunction(user_function))
+        if let Some(user_function) = env.contract_context.lookup_function(name) {
+            Ok(CallableType::UserFunction(user_function))
+        } else {
+            env.resolver.as_ref().and_then(|resolver| resolver.resolve_function(name))
+                .ok_or(CheckErrors::UndefinedFunction(name.to_string()).into())
+        }
     }
 }
 
@@ -216,7 +222,7 @@ fn eval_all (expressions: &[SymbolicExpression],
                 },
                 DefineResult::PersistedVariable(name, value_type, value) => {
                     runtime_cost!(cost_functions::CREATE_VAR, global_context, value_type.size())?;
-                    contract_context.persisted_names.insert(name.clone());
+                    contract_context.add_persisted_name(name.clone(), NameKind::DataVar);
 
                     global_context.add_memory(value_type.type_size()
                                               .expect("type size should be realizable") as u64)?;
@@ -230,7 +236,7 @@ fn eval_all (expressions: &[SymbolicExpression],
                     runtime_cost!(cost_functions::CREATE_MAP, global_context,
                                   u64::from(key_type.size()).cost_overflow_add(
                                       u64::from(value_type.size()))?)?;
-                    contract_context.persisted_names.insert(name.clone());
+                    contract_context.add_persisted_name(name.clone(), NameKind::Map);
 
                     global_context.add_memory(key_type.type_size()
                                               .expect("type size should be realizable") as u64)?;
@@ -241,7 +247,7 @@ fn eval_all (expressions: &[SymbolicExpression],
                 },
                 DefineResult::FungibleToken(name, total_supply) => {
                     runtime_cost!(cost_functions::CREATE_FT, global_context, 0)?;
-                    contract_context.persisted_names.insert(name.clone());
+                    contract_context.add_persisted_name(name.clone(), NameKind::FungibleToken);
 
                     global_context.add_memory(TypeSignature::UIntType.type_size()
                                               .expect("type size should be realizable") as u64)?;
@@ -250,7 +256,7 @@ fn eval_all (expressions: &[SymbolicExpression],
                 },
                 DefineResult::NonFungibleAsset(name, asset_type) => {
                     runtime_cost!(cost_functions::CREATE_NFT, global_context, asset_type.size())?;
-                    contract_context.persisted_names.insert(name.clone());
+                    contract_context.add_persisted_name(name.clone(), NameKind::NonFungibleToken);
 
                     global_context.add_memory(asset_type.type_size()
                                               .expect("type size should be realizable") as u64)?;
@@ -302,6 +308,55 @@ pub fn execute(program: &str) -> Result<Option<Value>> {
     })
 }
 
+/// A REPL session: like `execute`, but the contract context and the backing store are
+///   kept across calls to `eval`, so that names defined by one line remain visible to the
+///   following lines. Each line is evaluated in its own database transaction, which is rolled
+///   back if the line errors. Definitions made before the error in a failed line are kept.
+pub struct ReplSession {
+    contract_context: ContractContext,
+    store: MemoryBackingStore
+}
+
+impl ReplSession {
+    pub fn new() -> ReplSession {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let mut store = MemoryBackingStore::new();
+        {
+            // the metadata of persisted definitions (e.g., data var types) is stored
+            //   against the contract, so the session's contract needs a commitment.
+            let mut db = store.as_clarity_db();
+            db.begin();
+            db.insert_contract_hash(&contract_identifier, "")
+                .expect("FATAL: failed to initialize REPL contract");
+            db.commit();
+        }
+        ReplSession {
+            contract_context: ContractContext::new(contract_identifier),
+            store
+        }
+    }
+
+    pub fn eval(&mut self, line: &str) -> Result<Option<Value>> {
+        let contract_context = &mut self.contract_context;
+        let mut global_context = GlobalContext::new(self.store.as_clarity_db(), LimitedCostTracker::new_max_limit());
+        global_context.execute(|g| {
+            let parsed = ast::build_ast(&contract_context.contract_identifier, line, &mut ())?
+                .expressions;
+            eval_all(&parsed, contract_context, g)
+        })
+    }
+
+    pub fn contract_context(&self) -> &ContractContext {
+        &self.contract_context
+    }
+}
+
+impl Default for ReplSession {
+    fn default() -> ReplSession {
+        ReplSession::new()
+    }
+}
+
 
 #[cfg(test)]
 mod test {
@@ -313,7 +368,7 @@ mod test {
     use vm::eval;
     use vm::costs::LimitedCostTracker;
     use vm::execute;
-    use vm::errors::RuntimeErrorType;
+    use vm::errors::{RuntimeErrorType, CheckErrors};
 
     #[test]
     fn test_simple_user_function() {
@@ -352,4 +407,110 @@ mod test {
         let mut env = Environment::new(&mut global_context, &contract_context, &mut call_stack, None, None);
         assert_eq!(Ok(Value::Int(64)), eval(&content[0], &mut env, &context));
     }
+
+    #[test]
+    fn test_clone_cost() {
+        use vm::costs::cost_functions;
+
+        let values = [
+            Value::Int(1),
+            Value::buff_from(vec![0; 1000]).unwrap(),
+            Value::list_from(vec![Value::some(Value::UInt(1)).unwrap(); 10]).unwrap(),
+        ];
+
+        let context = LocalContext::new();
+        let lookup_depth_cost = cost_functions::LOOKUP_VARIABLE_DEPTH.compute_cost(context.depth() as u64)
+            .unwrap().runtime;
+
+        for value in values.iter() {
+            let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+            contract_context.variables.insert("a".into(), value.clone());
+
+            let mut marf = MemoryBackingStore::new();
+            let mut global_context = GlobalContext::new(marf.as_clarity_db(), LimitedCostTracker::new_max_limit());
+            let mut call_stack = CallStack::new();
+            let mut env = Environment::new(&mut global_context, &contract_context, &mut call_stack, None, None);
+
+            let before = env.global_context.cost_track.get_total().runtime;
+            assert_eq!(&eval(&SymbolicExpression::atom("a".into()), &mut env, &context).unwrap(), value);
+            let after = env.global_context.cost_track.get_total().runtime;
+
+            assert_eq!(after - before, lookup_depth_cost + value.clone_cost());
+        }
+    }
+
+    #[test]
+    fn test_recursion_check() {
+        //
+        //  test program, which the DefinitionSorter would have rejected:
+        //  (define (recurse) (recurse))
+        //  (recurse)
+        //
+        let content = SymbolicExpression::list(
+            Box::new([ SymbolicExpression::atom("recurse".into()) ]));
+
+        let user_function = DefinedFunction::new(vec![],
+                                                 content.clone(),
+                                                 DefineType::Private,
+                                                 &"recurse".into(),
+                                                 &"");
+
+        let context = LocalContext::new();
+        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        contract_context.functions.insert("recurse".into(), user_function);
+
+        let mut marf = MemoryBackingStore::new();
+        let mut global_context = GlobalContext::new(marf.as_clarity_db(), LimitedCostTracker::new_max_limit());
+
+        {
+            let mut call_stack = CallStack::new();
+            let mut env = Environment::new(&mut global_context, &contract_context, &mut call_stack, None, None);
+            assert_eq!(eval(&content, &mut env, &context).unwrap_err(),
+                       CheckErrors::CircularReference(vec![":recurse".to_string()]).into());
+        }
+    }
+
+    #[test]
+    fn test_repl_session() {
+        use vm::ReplSession;
+
+        let mut session = ReplSession::new();
+        assert_eq!(session.eval("(define-private (double (x int)) (* 2 x))").unwrap(), None);
+        assert_eq!(session.eval("(define-data-var counter int 1)").unwrap(), None);
+        assert_eq!(session.eval("(var-set counter (double (var-get counter)))").unwrap(),
+                   Some(Value::Bool(true)));
+        assert_eq!(session.eval("(double (var-get counter))").unwrap(), Some(Value::Int(4)));
+
+        // a failed line is rolled back
+        assert!(session.eval("(begin (var-set counter 10) (unwrap-panic none))").is_err());
+        assert_eq!(session.eval("(var-get counter)").unwrap(), Some(Value::Int(2)));
+
+        // each session has its own context
+        assert!(ReplSession::new().eval("(double 1)").is_err());
+    }
+
+    #[test]
+    fn test_name_kind() {
+        use vm::ReplSession;
+        use vm::contexts::NameKind;
+
+        let mut session = ReplSession::new();
+        session.eval("(define-read-only (get-one) 1)
+                      (define-constant two 2)
+                      (define-data-var counter int 0)
+                      (define-map scores ((id int)) ((score int)))
+                      (define-fungible-token stacks)
+                      (define-non-fungible-token names int)
+                      (define-trait getter ((get-one () (response int int))))").unwrap();
+
+        let context = session.contract_context();
+        assert_eq!(context.name_kind("get-one"), Some(NameKind::Function));
+        assert_eq!(context.name_kind("two"), Some(NameKind::Constant));
+        assert_eq!(context.name_kind("counter"), Some(NameKind::DataVar));
+        assert_eq!(context.name_kind("scores"), Some(NameKind::Map));
+        assert_eq!(context.name_kind("stacks"), Some(NameKind::FungibleToken));
+        assert_eq!(context.name_kind("names"), Some(NameKind::NonFungibleToken));
+        assert_eq!(context.name_kind("getter"), Some(NameKind::Trait));
+        assert_eq!(context.name_kind("undefined"), None);
+    }
 }
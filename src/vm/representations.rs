@@ -5,6 +5,7 @@ use std::convert::TryFrom;
 use regex::{Regex};
 use vm::types::{Value, TraitIdentifier, QualifiedContractIdentifier};
 use vm::errors::{RuntimeErrorType};
+use vm::ast::parser::{CONTRACT_MIN_NAME_LENGTH, CONTRACT_MAX_NAME_LENGTH};
 
 pub const MAX_STRING_LEN: u8 = 128;
 
@@ -71,6 +72,81 @@ guarded_string!(ClarityName, "ClarityName", Regex::new("^[a-zA-Z]([a-zA-Z0-9]|[-
 guarded_string!(ContractName, "ContractName", Regex::new("^[a-zA-Z]([a-zA-Z0-9]|[-_])*$|^__transient$"));
 guarded_string!(UrlString, "UrlString", Regex::new(r#"^[a-zA-Z0-9._~:/?#\[\]@!$&'()*+,;%=-]*$"#));
 
+/// The specific rule a candidate ClarityName or ContractName violates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    TooShort(usize, usize),
+    TooLong(usize, usize),
+    StartsWithDigit,
+    IllegalFirstCharacter(char),
+    IllegalCharacter(char),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "name is empty"),
+            NameError::TooShort(min, found) => write!(f, "name must be at least {} characters long, but is {}", min, found),
+            NameError::TooLong(max, found) => write!(f, "name must be at most {} characters long, but is {}", max, found),
+            NameError::StartsWithDigit => write!(f, "name must not start with a digit"),
+            NameError::IllegalFirstCharacter(c) => write!(f, "name must start with a letter, not '{}'", c),
+            NameError::IllegalCharacter(c) => write!(f, "name contains illegal character '{}'", c),
+        }
+    }
+}
+
+fn check_name_length(value: &str, min_len: usize, max_len: usize) -> Result<(), NameError> {
+    let len = value.len();
+    if len == 0 {
+        Err(NameError::Empty)
+    } else if len < min_len {
+        Err(NameError::TooShort(min_len, len))
+    } else if len > max_len {
+        Err(NameError::TooLong(max_len, len))
+    } else {
+        Ok(())
+    }
+}
+
+// Names must start with an ASCII letter, and every following character
+//   must satisfy `is_legal`.
+fn check_name_characters(value: &str, is_legal: fn(char) -> bool) -> Result<(), NameError> {
+    let mut chars = value.chars();
+    match chars.next() {
+        None => return Err(NameError::Empty),
+        Some(c) if c.is_ascii_digit() => return Err(NameError::StartsWithDigit),
+        Some(c) if !c.is_ascii_alphabetic() => return Err(NameError::IllegalFirstCharacter(c)),
+        Some(_) => {}
+    }
+    match chars.find(|c| !is_legal(*c)) {
+        Some(c) => Err(NameError::IllegalCharacter(c)),
+        None => Ok(())
+    }
+}
+
+impl ClarityName {
+    /// Checks whether `value` is a legal ClarityName, reporting the first
+    ///   rule it violates if it is not.
+    pub fn validate(value: &str) -> Result<(), NameError> {
+        check_name_length(value, 1, MAX_STRING_LEN as usize)?;
+        match value {
+            "-" | "+" | "=" | "/" | "*" | "<" | ">" | "<=" | ">=" => Ok(()),
+            _ => check_name_characters(value, |c| c.is_ascii_alphanumeric() || "-_!?+<>=/*".contains(c))
+        }
+    }
+}
+
+impl ContractName {
+    /// Checks whether `value` is a legal user-supplied ContractName, reporting the
+    ///   first rule it violates if it is not. Unlike `try_from`, this also enforces
+    ///   the contract name length bounds the parser applies.
+    pub fn validate(value: &str) -> Result<(), NameError> {
+        check_name_length(value, CONTRACT_MIN_NAME_LENGTH, CONTRACT_MAX_NAME_LENGTH)?;
+        check_name_characters(value, |c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum PreSymbolicExpressionType {
     AtomValue(Value),
@@ -157,6 +233,21 @@ impl PreSymbolicExpression {
     pub fn set_span(&mut self, _start_line: u32, _start_column: u32, _end_line: u32, _end_column: u32) {
     }
 
+    #[cfg(feature = "developer-mode")]
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn span(&self) -> &Span {
+        &ZERO_SPAN
+    }
+
+    /// The identifier assigned by the ExpressionIdentifier pass: see `SymbolicExpression::id`.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     pub fn sugared_contract_identifier(val: ContractName) -> PreSymbolicExpression {
         PreSymbolicExpression {
             pre_expr: PreSymbolicExpressionType::SugaredContractIdentifier(val),
@@ -245,6 +336,14 @@ impl PreSymbolicExpression {
         }
     }
 
+    pub fn match_tuple(&self) -> Option<&[PreSymbolicExpression]> {
+        if let PreSymbolicExpressionType::Tuple(ref list) = self.pre_expr {
+            Some(list)
+        } else {
+            None
+        }
+    }
+
     pub fn match_field_identifier(&self) -> Option<&TraitIdentifier> {
         if let PreSymbolicExpressionType::FieldIdentifier(ref value) = self.pre_expr {
             Some(value)
@@ -332,6 +431,25 @@ impl SymbolicExpression {
     #[cfg(not(feature = "developer-mode"))]
     pub fn set_span(&mut self, _start_line: u32, _start_column: u32, _end_line: u32, _end_column: u32) {
     }
+
+    #[cfg(feature = "developer-mode")]
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn span(&self) -> &Span {
+        &ZERO_SPAN
+    }
+
+    /// The identifier assigned by the ExpressionIdentifier pass. Identifiers are assigned
+    ///   in document order (a list is numbered before its elements), starting at 1, so
+    ///   they are deterministic for identical input. Because they are positional, an edit
+    ///   renumbers every expression which follows it. Expressions which have not been
+    ///   through the pass have id 0.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
     
     pub fn atom_value(val: Value) -> SymbolicExpression {
         SymbolicExpression {
@@ -463,6 +581,16 @@ pub struct Span {
     pub end_column: u32
 }
 
+// spans are only tracked in developer-mode, otherwise
+//   the span accessors return this zero span.
+#[cfg(not(feature = "developer-mode"))]
+static ZERO_SPAN: Span = Span {
+    start_line: 0,
+    start_column: 0,
+    end_line: 0,
+    end_column: 0
+};
+
 impl Span {
     pub fn zero() -> Span {
         Span {
@@ -472,4 +600,10 @@ impl Span {
             end_column: 0
         }
     }
+
+    /// Whether `other` lies entirely within this span.
+    pub fn contains(&self, other: &Span) -> bool {
+        (self.start_line, self.start_column) <= (other.start_line, other.start_column) &&
+            (other.end_line, other.end_column) <= (self.end_line, self.end_column)
+    }
 }
@@ -493,6 +493,40 @@ fn total_supply(owned_env: &mut OwnedEnvironment) {
     });
 }
 
+fn test_ft_supply(owned_env: &mut OwnedEnvironment) {
+    let contract = "(define-fungible-token capped u10)
+         (define-fungible-token uncapped)
+         (define-public (mint (amount uint) (commit bool))
+            (begin (unwrap-panic (ft-mint? capped amount tx-sender))
+                   (unwrap-panic (ft-mint? uncapped (* u2 amount) tx-sender))
+                   (if commit (ok amount) (err amount))))";
+
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+    let p1_principal = match p1 {
+        Value::Principal(PrincipalData::Standard(ref data)) => data.clone(),
+        _ => panic!()
+    };
+
+    let token_contract_id = QualifiedContractIdentifier::new(p1_principal.clone(), "tokens".into());
+    owned_env.initialize_contract(token_contract_id.clone(), contract).unwrap();
+
+    for (amount, commit) in &[(3, true), (4, false), (2, true)] {
+        execute_transaction(owned_env, p1.clone(), &token_contract_id, "mint",
+                            &symbols_from_values(vec![Value::UInt(*amount), Value::Bool(*commit)])).unwrap();
+    }
+
+    owned_env.execute_in_env(p1.clone(), |env| {
+        let db = &mut env.global_context.database;
+        assert_eq!(db.get_ft_supply(&token_contract_id, "capped").unwrap(), Some(5));
+        assert_eq!(db.get_ft_cap(&token_contract_id, "capped").unwrap(), Some(10));
+        assert_eq!(db.get_ft_supply(&token_contract_id, "uncapped").unwrap(), None);
+        assert_eq!(db.get_ft_cap(&token_contract_id, "uncapped").unwrap(), None);
+        assert_eq!(db.get_ft_supply(&token_contract_id, "stackaroos").unwrap_err(),
+                   CheckErrors::NoSuchFT("stackaroos".to_string()).into());
+        Ok(())
+    }).unwrap();
+}
+
 fn test_overlapping_nfts(owned_env: &mut OwnedEnvironment) {
     let tokens_contract = FIRST_CLASS_TOKENS;
     let names_contract = ASSET_NAMES;
@@ -696,10 +730,40 @@ fn test_simple_naming_system(owned_env: &mut OwnedEnvironment) {
 }
 
 
+#[test]
+fn test_asset_map_debits_for_principal() {
+    let p1 = PrincipalData::parse_standard_principal("SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR").unwrap().into();
+    let p2 = PrincipalData::parse_standard_principal("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G").unwrap().into();
+    let p3 = PrincipalData::parse_standard_principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap().into();
+    let contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
+    let tokens = AssetIdentifier { contract_identifier: contract_id.clone(), asset_name: "stackaroos".into() };
+    let names = AssetIdentifier { contract_identifier: contract_id.clone(), asset_name: "names".into() };
+
+    // p1 pays p2 and p3 in tokens and STX, p2 passes some tokens and a name on to p3.
+    let mut asset_map = AssetMap::new();
+    asset_map.add_token_transfer(&p1, tokens.clone(), 100).unwrap();
+    asset_map.add_token_transfer(&p1, tokens.clone(), 50).unwrap();
+    asset_map.add_stx_transfer(&p1, 7).unwrap();
+    asset_map.add_token_transfer(&p2, tokens.clone(), 30).unwrap();
+    asset_map.add_asset_transfer(&p2, names.clone(), Value::Int(1));
+
+    assert_eq!(asset_map.debits_for(&p1), vec![AssetMapEntry::STX(7), AssetMapEntry::Token(150)]);
+    // names sorts before stackaroos
+    assert_eq!(asset_map.debits_for(&p2), vec![AssetMapEntry::Asset(vec![Value::Int(1)]), AssetMapEntry::Token(30)]);
+    assert_eq!(asset_map.debits_for(&p3), vec![]);
+
+    // p2 also received tokens from p1, but only debits are recorded
+    assert_eq!(asset_map.debited_for(&p1, &tokens), 150);
+    assert_eq!(asset_map.debited_for(&p1, &AssetIdentifier::STX()), 7);
+    assert_eq!(asset_map.debited_for(&p1, &AssetIdentifier::STX_burned()), 0);
+    assert_eq!(asset_map.debited_for(&p2, &tokens), 30);
+    assert_eq!(asset_map.debited_for(&p3, &tokens), 0);
+}
+
 #[test]
 fn test_all() {
     let to_test = [test_overlapping_nfts, test_simple_token_system,
-                   test_simple_naming_system, total_supply, test_native_stx_ops];
+                   test_simple_naming_system, total_supply, test_ft_supply, test_native_stx_ops];
     for test in to_test.iter() {
         with_memory_environment(test, true);
         with_marfed_environment(test, true);
@@ -507,6 +507,44 @@ fn test_simple_contract_call(owned_env: &mut OwnedEnvironment) {
     }
 }
 
+fn test_eval_read_only_parsed(owned_env: &mut OwnedEnvironment) {
+    let contract_identifier = QualifiedContractIdentifier::local("factorial-contract").unwrap();
+    let mut env = owned_env.get_exec_environment(Some(get_principal()));
+    env.initialize_contract(contract_identifier.clone(), FACTORIAL_CONTRACT).unwrap();
+
+    let queries = ["(get current (unwrap! (map-get? factorials {id 8008}) false))",
+                   "(get index (unwrap! (map-get? factorials {id 1337}) false))",
+                   "(map-get? factorials {id 1})"];
+    let parsed: Vec<_> = queries.iter()
+        .map(|query| ast::parse(&contract_identifier, query).unwrap())
+        .collect();
+
+    for _ in 0..3 {
+        env.execute_contract(&contract_identifier, "compute", &symbols_from_values(vec![Value::Int(8008)]), false).unwrap();
+        for (query, parsed_query) in queries.iter().zip(parsed.iter()) {
+            assert_eq!(env.eval_read_only(&contract_identifier, query).unwrap(),
+                       env.eval_read_only_parsed(&contract_identifier, parsed_query).unwrap());
+        }
+    }
+
+    assert!(env.eval_read_only_parsed(&contract_identifier, &[]).is_err());
+}
+
+fn test_get_constant(owned_env: &mut OwnedEnvironment) {
+    let contract = "(define-constant foo u42)
+                    (define-constant pair (tuple (a 1) (b u2)))
+                    (define-data-var bar uint u1)";
+    let contract_identifier = QualifiedContractIdentifier::local("constants").unwrap();
+    let mut env = owned_env.get_exec_environment(None);
+    env.initialize_contract(contract_identifier.clone(), contract).unwrap();
+
+    let contract_context = env.global_context.database.get_contract(&contract_identifier)
+        .unwrap().contract_context;
+    assert_eq!(contract_context.get_constant("foo"), Some(&Value::UInt(42)));
+    assert_eq!(contract_context.get_constant("pair"), Some(&execute("(tuple (a 1) (b u2))")));
+    assert_eq!(contract_context.get_constant("bar"), None);
+}
+
 fn test_aborts(owned_env: &mut OwnedEnvironment) {
     let contract_1 ="
 (define-map data ((id int)) ((value int)))
@@ -782,6 +820,231 @@ fn test_cc_trait_stack_depth() {
         }, false);
 }
 
+#[test]
+fn test_contract_call_depth() {
+    let contract_three = "(define-public (three) (ok 3))";
+    let contract_two = "(define-public (two) (contract-call? .c-three three))";
+    let contract_one = "(define-public (one) (contract-call? .c-two two))";
+
+    with_memory_environment(
+        |owned_env| {
+            let mut env = owned_env.get_exec_environment(None);
+            for (name, contract) in &[("c-three", contract_three), ("c-two", contract_two), ("c-one", contract_one)] {
+                env.initialize_contract(QualifiedContractIdentifier::local(name).unwrap(), contract).unwrap();
+            }
+            env.global_context.set_max_contract_call_depth(2);
+
+            assert_eq!(env.eval_raw("(contract-call? .c-two two)").unwrap(),
+                       Value::okay(Value::Int(3)).unwrap());
+            assert_eq!(env.eval_raw("(contract-call? .c-one one)").unwrap_err(),
+                       RuntimeErrorType::MaxContractCallDepthReached.into());
+            // the depth is unwound after the failed call
+            assert_eq!(env.eval_raw("(contract-call? .c-two two)").unwrap(),
+                       Value::okay(Value::Int(3)).unwrap());
+        }, false);
+}
+
+#[test]
+fn test_cross_contract_reentry() {
+    let contract_bravo = "(define-public (g (n uint)) (contract-call? .alpha entry2))";
+    let contract_alpha = "(define-private (p (n uint)) (if (> n u0) (contract-call? .bravo g n) (ok u0)))
+                          (define-public (entry) (p u1))
+                          (define-public (entry2) (p u0))";
+
+    with_memory_environment(
+        |owned_env| {
+            let mut env = owned_env.get_exec_environment(None);
+            let alpha = QualifiedContractIdentifier::local("alpha").unwrap();
+            env.initialize_contract(QualifiedContractIdentifier::local("bravo").unwrap(), contract_bravo).unwrap();
+            env.initialize_contract(alpha.clone(), contract_alpha).unwrap();
+
+            assert_eq!(env.eval_raw("(contract-call? .alpha entry2)").unwrap(),
+                       Value::okay(Value::UInt(0)).unwrap());
+            // re-entering alpha's private function through bravo is a cycle,
+            //   even though alpha's own definitions are acyclic.
+            assert_eq!(env.eval_raw("(contract-call? .alpha entry)").unwrap_err(),
+                       CheckErrors::CircularReference(vec![format!("{}:p", alpha)]).into());
+        }, false);
+}
+
+#[test]
+fn test_contract_call_interceptor() {
+    let contract = "(define-public (price-of (id uint)) (contract-call? .oracle get-price id))
+                    (define-public (volume-of (id uint)) (contract-call? .oracle get-volume id))
+                    (define-public (rank-of (id uint)) (contract-call? .oracle get-rank id))";
+
+    with_memory_environment(
+        |owned_env| {
+            let mut env = owned_env.get_exec_environment(None);
+            env.initialize_contract(QualifiedContractIdentifier::local("market").unwrap(), contract).unwrap();
+
+            let response_type = TypeSignature::new_response(TypeSignature::UIntType, TypeSignature::UIntType).unwrap();
+            let mocked_type = response_type.clone();
+            env.global_context.set_contract_call_interceptor(Box::new(
+                move |contract_identifier: &QualifiedContractIdentifier, function_name: &str, args: &[Value]| {
+                    if contract_identifier.name.as_str() != "oracle" {
+                        return None
+                    }
+                    match (function_name, args) {
+                        ("get-price", [Value::UInt(id)]) =>
+                            Some((mocked_type.clone(), Value::okay(Value::UInt(100 * id)).unwrap())),
+                        // a mock whose result does not match its declared return type
+                        ("get-rank", _) =>
+                            Some((mocked_type.clone(), Value::okay(Value::Int(1)).unwrap())),
+                        _ => None
+                    }
+                }));
+
+            assert_eq!(env.eval_raw("(contract-call? .market price-of u2)").unwrap(),
+                       Value::okay(Value::UInt(200)).unwrap());
+            assert_eq!(env.eval_raw("(contract-call? .market rank-of u2)").unwrap_err(),
+                       CheckErrors::ReturnTypesMustMatch(
+                           response_type,
+                           TypeSignature::new_response(TypeSignature::IntType, TypeSignature::NoType).unwrap()).into());
+            // calls the interceptor declines fall through to the (undeployed) contract
+            assert!(env.eval_raw("(contract-call? .market volume-of u2)").is_err());
+        }, false);
+}
+
+#[test]
+fn test_sender_stack() {
+    use std::rc::Rc;
+    use vm::callables::CallableType;
+    use vm::contexts::{NameResolver, LocalContext};
+    use vm::types::TupleData;
+
+    // reports the principals of the environment it is called in
+    fn principals(_args: &[SymbolicExpression], env: &mut Environment, _context: &LocalContext) -> Result<Value, Error> {
+        let frames = env.sender_stack();
+        assert_eq!(frames.last().unwrap().caller.as_ref(), env.caller());
+        Ok(Value::from(TupleData::from_data(vec![
+            ("sender".into(), Value::from(env.sender().cloned().unwrap())),
+            ("caller".into(), Value::from(env.caller().cloned().unwrap())),
+            ("depth".into(), Value::UInt(frames.len() as u128))]).unwrap()))
+    }
+
+    struct Inspector;
+    impl NameResolver for Inspector {
+        fn resolve_function(&self, name: &str) -> Option<CallableType> {
+            match name {
+                "principals" => Some(CallableType::SpecialFunction("principals", &principals)),
+                _ => None
+            }
+        }
+    }
+
+    let callee = "(define-public (whoami) (ok (principals)))";
+    let proxy = "(define-public (call) (contract-call? .callee whoami))
+                 (define-public (call-as-contract) (as-contract (contract-call? .callee whoami)))";
+
+    with_memory_environment(
+        |owned_env| {
+            let callee_identifier = QualifiedContractIdentifier::local("callee").unwrap();
+            let proxy_identifier = QualifiedContractIdentifier::local("proxy").unwrap();
+            let sender = Value::from(callee_identifier.issuer.clone());
+            let proxy_principal = Value::from(proxy_identifier.clone());
+
+            let mut env = owned_env.get_exec_environment(Some(sender.clone()));
+            env.set_resolver(Rc::new(Inspector));
+            env.initialize_contract(callee_identifier.clone(), callee).unwrap();
+            env.initialize_contract(proxy_identifier.clone(), proxy).unwrap();
+            assert_eq!(env.sender(), Some(&callee_identifier.issuer.clone().into()));
+            assert_eq!(env.sender_stack().len(), 1);
+
+            let expected = |sender: &Value, caller: &Value, depth: u128| Value::okay(Value::from(TupleData::from_data(vec![
+                ("sender".into(), sender.clone()),
+                ("caller".into(), caller.clone()),
+                ("depth".into(), Value::UInt(depth))]).unwrap())).unwrap();
+
+            // the caller of the called contract is the originating contract
+            assert_eq!(env.execute_contract(&proxy_identifier, "call", &[], false).unwrap(),
+                       expected(&sender, &proxy_principal, 2));
+            assert_eq!(env.execute_contract(&proxy_identifier, "call-as-contract", &[], false).unwrap(),
+                       expected(&proxy_principal, &proxy_principal, 3));
+        }, false);
+}
+
+#[test]
+fn test_db_observer() {
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use vm::database::{DbAuditLog, DbAccessKind};
+
+    let contract = "(define-data-var n int 0)
+                    (define-public (set (v int)) (begin (var-set n v) (ok v)))
+                    (define-public (set-and-fail (v int)) (begin (var-set n v) (err v)))";
+
+    with_memory_environment(
+        |owned_env| {
+            let contract_identifier = QualifiedContractIdentifier::local("counter").unwrap();
+            let mut env = owned_env.get_exec_environment(None);
+            env.initialize_contract(contract_identifier.clone(), contract).unwrap();
+
+            let log = Rc::new(RefCell::new(DbAuditLog::new()));
+            env.global_context.database.set_observer(Box::new(log.clone()));
+
+            env.eval_raw("(contract-call? .counter set 5)").unwrap();
+            env.eval_raw("(contract-call? .counter set-and-fail 6)").unwrap();
+
+            let writes: Vec<_> = log.borrow().entries().iter()
+                .filter(|entry| entry.kind == DbAccessKind::Write)
+                .cloned()
+                .collect();
+            assert_eq!(writes.len(), 2);
+            for write in writes.iter() {
+                assert_eq!(write.contract, Some(contract_identifier.to_string()));
+                assert!(write.key.starts_with(&format!("vm::{}::", contract_identifier)));
+                assert!(write.key.ends_with("::n"));
+                assert!(write.value_size.is_some());
+            }
+            // the failed call's write is kept in the log, but marked as rolled back
+            assert!(!writes[0].rolled_back);
+            assert!(writes[1].rolled_back);
+        }, false);
+}
+
+#[test]
+fn test_name_resolver() {
+    use std::rc::Rc;
+    use vm::contexts::NameResolver;
+
+    struct Playground;
+    impl NameResolver for Playground {
+        fn resolve_variable(&self, name: &str) -> Option<Value> {
+            match name {
+                "fee-rate" => Some(Value::UInt(7)),
+                "tx-sender" => Some(Value::UInt(0)),
+                _ => None
+            }
+        }
+    }
+
+    let contract = "(define-read-only (get-fee (amount uint)) (* fee-rate amount))
+                    (define-read-only (get-sender) tx-sender)";
+
+    with_memory_environment(
+        |owned_env| {
+            let contract_identifier = QualifiedContractIdentifier::local("playground").unwrap();
+            let sender = Value::from(contract_identifier.issuer.clone());
+            let mut env = owned_env.get_exec_environment(Some(sender.clone()));
+            env.initialize_contract(contract_identifier.clone(), contract).unwrap();
+
+            assert_eq!(env.execute_contract(&contract_identifier, "get-fee", &symbols_from_values(vec![Value::UInt(3)]), false)
+                       .unwrap_err(),
+                       CheckErrors::UndefinedVariable("fee-rate".to_string()).into());
+
+            env.set_resolver(Rc::new(Playground));
+            assert_eq!(env.execute_contract(&contract_identifier, "get-fee", &symbols_from_values(vec![Value::UInt(3)]), false)
+                       .unwrap(),
+                       Value::UInt(21));
+            assert_eq!(env.eval_read_only(&contract_identifier, "(get-fee u2)").unwrap(), Value::UInt(14));
+
+            // reserved names are resolved before the resolver is consulted
+            assert_eq!(env.execute_contract(&contract_identifier, "get-sender", &[], false).unwrap(),
+                       sender);
+        }, false);
+}
+
 #[test]
 fn test_all() {
     let to_test = [ test_factorial_contract,
@@ -789,7 +1052,9 @@ fn test_all() {
                     test_contract_caller,
                     test_fully_qualified_contract_call,
                     test_simple_naming_system,
-                    test_simple_contract_call ];
+                    test_simple_contract_call,
+                    test_eval_read_only_parsed,
+                    test_get_constant ];
     for test in to_test.iter() {
         with_memory_environment(test, false);
         with_marfed_environment(test, false);
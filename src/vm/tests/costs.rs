@@ -10,7 +10,7 @@ use vm::tests::{with_memory_environment, with_marfed_environment, symbols_from_v
                 execute, is_err_code, is_committed};
 
 use vm::contexts::{Environment};
-use vm::costs::{ExecutionCost};
+use vm::costs::{ExecutionCost, BlockCostAccumulator, CostDimension};
 use vm::database::{ClarityDatabase, MarfedKV, MemoryBackingStore,
                    NULL_HEADER_DB};
 use chainstate::stacks::events::StacksTransactionEvent;
@@ -157,3 +157,30 @@ fn test_all() {
         assert!(cost.exceeds(&baseline));
     }
 }
+
+#[test]
+fn test_block_cost_accumulator() {
+    let tx_cost = |runtime, read_count, write_length| ExecutionCost {
+        runtime, read_count, write_length, write_count: 1, read_length: 10 };
+    let limit = ExecutionCost { runtime: 1000, read_count: 10, write_length: 100, write_count: 10, read_length: 100 };
+
+    let mut block = BlockCostAccumulator::new(limit.clone());
+    block.add_transaction(&tx_cost(300, 4, 20)).unwrap();
+    block.add_transaction(&tx_cost(300, 4, 20)).unwrap();
+    assert_eq!(block.total(), &ExecutionCost { runtime: 600, read_count: 8, write_length: 40, write_count: 2, read_length: 20 });
+
+    // the third transaction fits the runtime budget, but not the read count budget
+    let third = tx_cost(300, 4, 20);
+    assert_eq!(block.add_transaction(&third), Err(CostDimension::ReadCount));
+    assert!(block.total().checked_add(&third).unwrap().exceeds(&limit));
+    // the refused transaction is not counted
+    assert_eq!(block.total().read_count, 8);
+    block.add_transaction(&tx_cost(300, 2, 20)).unwrap();
+    assert_eq!(block.total().runtime, 900);
+
+    let mut overflowing = BlockCostAccumulator::new(ExecutionCost::max_value());
+    overflowing.add_transaction(&ExecutionCost { write_count: u64::max_value(), ..ExecutionCost::zero() }).unwrap();
+    assert_eq!(overflowing.add_transaction(&ExecutionCost { write_count: 1, ..ExecutionCost::zero() }),
+               Err(CostDimension::WriteCount));
+    assert_eq!(ExecutionCost::max_value().checked_add(&ExecutionCost::runtime(1)), None);
+}
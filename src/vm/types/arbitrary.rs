@@ -0,0 +1,136 @@
+// Random generation of well-formed values of a given type, for property-based tests.
+
+use std::convert::TryFrom;
+
+use rand::Rng;
+
+use vm::representations::ContractName;
+use vm::types::{Value, TypeSignature, TupleData, PrincipalData, StandardPrincipalData,
+                QualifiedContractIdentifier};
+
+impl Value {
+    /// Generate a random value admitted by `type_sig`. Optionals and lists nested more than
+    ///   `max_depth` levels deep are generated as `none` and empty lists. Since type signatures
+    ///   are bounded by MAX_VALUE_SIZE and MAX_TYPE_DEPTH, so are the generated values, and
+    ///   every value is built through the checked constructors.
+    /// Panics on `NoType`, which has no values.
+    pub fn arbitrary_of_type<R: Rng>(rng: &mut R, type_sig: &TypeSignature, max_depth: u8) -> Value {
+        let inner_depth = max_depth.saturating_sub(1);
+        match type_sig {
+            TypeSignature::NoType => panic!("NoType has no values"),
+            TypeSignature::IntType => Value::Int(rng.gen()),
+            TypeSignature::UIntType => Value::UInt(rng.gen()),
+            TypeSignature::BoolType => Value::Bool(rng.gen()),
+            TypeSignature::BufferType(max_len) => {
+                let len = rng.gen_range(0, u32::from(max_len) + 1);
+                let data = (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+                Value::buff_from(data)
+                    .expect("generated buffer failed the constructor checks")
+            },
+            TypeSignature::PrincipalType => Value::from(arbitrary_principal(rng)),
+            TypeSignature::TraitReferenceType(_) => Value::from(
+                PrincipalData::Contract(arbitrary_contract_identifier(rng))),
+            TypeSignature::ListType(list_type) => {
+                let entry_type = list_type.get_list_item_type();
+                let len = if max_depth == 0 || entry_type.is_no_type() {
+                    0
+                } else {
+                    rng.gen_range(0, list_type.get_max_len() + 1)
+                };
+                let items = (0..len)
+                    .map(|_| Value::arbitrary_of_type(rng, entry_type, inner_depth))
+                    .collect();
+                Value::list_with_type(items, list_type.clone())
+                    .expect("generated list failed the constructor checks")
+            },
+            TypeSignature::TupleType(tuple_type) => {
+                let fields = tuple_type.get_type_map().iter()
+                    .map(|(name, field_type)| (name.clone(), Value::arbitrary_of_type(rng, field_type, inner_depth)))
+                    .collect();
+                Value::from(TupleData::from_data_typed(fields, tuple_type)
+                    .expect("generated tuple failed the constructor checks"))
+            },
+            TypeSignature::OptionalType(inner_type) => {
+                if max_depth == 0 || inner_type.is_no_type() || rng.gen() {
+                    Value::none()
+                } else {
+                    Value::some(Value::arbitrary_of_type(rng, inner_type, inner_depth))
+                        .expect("generated optional failed the constructor checks")
+                }
+            },
+            TypeSignature::ResponseType(inner_types) => {
+                let (ref ok_type, ref err_type) = **inner_types;
+                let committed = if ok_type.is_no_type() {
+                    false
+                } else if err_type.is_no_type() {
+                    true
+                } else {
+                    rng.gen()
+                };
+                let result = if committed {
+                    Value::okay(Value::arbitrary_of_type(rng, ok_type, inner_depth))
+                } else {
+                    Value::error(Value::arbitrary_of_type(rng, err_type, inner_depth))
+                };
+                result.expect("generated response failed the constructor checks")
+            }
+        }
+    }
+}
+
+fn arbitrary_standard_principal<R: Rng>(rng: &mut R) -> StandardPrincipalData {
+    // c32 versions are 5 bits
+    StandardPrincipalData(rng.gen_range(0, 32), rng.gen())
+}
+
+fn arbitrary_contract_identifier<R: Rng>(rng: &mut R) -> QualifiedContractIdentifier {
+    let name = ContractName::try_from(format!("contract-{}", rng.gen::<u16>()))
+        .expect("generated contract name is invalid");
+    QualifiedContractIdentifier::new(arbitrary_standard_principal(rng), name)
+}
+
+fn arbitrary_principal<R: Rng>(rng: &mut R) -> PrincipalData {
+    if rng.gen() {
+        PrincipalData::Standard(arbitrary_standard_principal(rng))
+    } else {
+        PrincipalData::Contract(arbitrary_contract_identifier(rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use vm::database::ClaritySerializable;
+    use vm::types::{Value, TypeSignature, MAX_TYPE_DEPTH};
+
+    #[test]
+    fn test_arbitrary_values_round_trip() {
+        let type_sig: TypeSignature = "(tuple (id uint) (owner principal) (memo (optional (buff 8)))
+                                             (history (list 5 (response (tuple (amount int) (ok bool)) uint)))
+                                             (nested (optional (optional (list 3 int)))))".into();
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let value = Value::arbitrary_of_type(&mut rng, &type_sig, MAX_TYPE_DEPTH);
+            assert!(type_sig.admits(&value));
+            let round_tripped = Value::try_deserialize_hex(&value.serialize(), &type_sig).unwrap();
+            assert_eq!(round_tripped, value);
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_values_depth() {
+        let type_sig: TypeSignature = "(optional (list 4 (optional int)))".into();
+        let mut rng = thread_rng();
+        for _ in 0..20 {
+            assert_eq!(Value::arbitrary_of_type(&mut rng, &type_sig, 0), Value::none());
+            match Value::arbitrary_of_type(&mut rng, &type_sig, 1) {
+                Value::Optional(data) => match data.data {
+                    Some(list) => assert_eq!(*list, Value::list_from(vec![]).unwrap()),
+                    None => {}
+                },
+                _ => panic!("expected an optional")
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Terse constructors for building (possibly nested) Clarity values in tests and fixtures.
+//   Each builder wraps the corresponding checked constructor on `Value`, and the wrapping
+//   builders accept either a `Value` or a `Result<Value>`, so construction errors from
+//   inner values propagate outwards, e.g.:
+//
+//     clarity_tuple![("a", some(clarity_list![int(1), int(2)])), ("b", ok(uint(2)))]
+
+use std::convert::TryFrom;
+
+use vm::representations::ClarityName;
+use vm::errors::InterpreterResult as Result;
+use vm::types::{Value, TupleData};
+
+/// A value, or the result of constructing one.
+pub trait IntoValue {
+    fn into_value(self) -> Result<Value>;
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Result<Value> {
+        Ok(self)
+    }
+}
+
+impl IntoValue for Result<Value> {
+    fn into_value(self) -> Result<Value> {
+        self
+    }
+}
+
+pub fn int(value: i128) -> Value {
+    Value::Int(value)
+}
+
+pub fn uint(value: u128) -> Value {
+    Value::UInt(value)
+}
+
+pub fn none() -> Value {
+    Value::none()
+}
+
+pub fn buff<B: Into<Vec<u8>>>(data: B) -> Result<Value> {
+    Value::buff_from(data.into())
+}
+
+pub fn some<V: IntoValue>(value: V) -> Result<Value> {
+    Value::some(value.into_value()?)
+}
+
+pub fn ok<V: IntoValue>(value: V) -> Result<Value> {
+    Value::okay(value.into_value()?)
+}
+
+pub fn err<V: IntoValue>(value: V) -> Result<Value> {
+    Value::error(value.into_value()?)
+}
+
+/// Construct a list value from (possibly failed) items. Used by `clarity_list!`.
+pub fn list_from(items: Vec<Result<Value>>) -> Result<Value> {
+    let items = items.into_iter().collect::<Result<Vec<_>>>()?;
+    Value::list_from(items)
+}
+
+/// Construct a tuple value from named (possibly failed) items. Used by `clarity_tuple!`.
+pub fn tuple_from(items: Vec<(&str, Result<Value>)>) -> Result<Value> {
+    let mut data = Vec::with_capacity(items.len());
+    for (name, value) in items.into_iter() {
+        data.push((ClarityName::try_from(name.to_string())?, value?));
+    }
+    Ok(Value::from(TupleData::from_data(data)?))
+}
+
+#[macro_export]
+macro_rules! clarity_list {
+    ($($item:expr),* $(,)?) => {
+        $crate::vm::types::builder::list_from(
+            vec![$($crate::vm::types::builder::IntoValue::into_value($item)),*])
+    };
+}
+
+#[macro_export]
+macro_rules! clarity_tuple {
+    ($(($name:expr, $value:expr)),* $(,)?) => {
+        $crate::vm::types::builder::tuple_from(
+            vec![$(($name, $crate::vm::types::builder::IntoValue::into_value($value))),*])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::errors::{Error, CheckErrors};
+    use vm::types::MAX_TYPE_DEPTH;
+
+    #[test]
+    fn test_build_nested_value() {
+        let built = clarity_tuple![
+            ("a", some(clarity_list![int(1), int(2)])),
+            ("b", ok(uint(2))),
+            ("c", err(buff("abc"))),
+            ("d", none()),
+        ].unwrap();
+
+        let expected = Value::from(TupleData::from_data(vec![
+            ("a".into(), Value::some(Value::list_from(vec![Value::Int(1), Value::Int(2)]).unwrap()).unwrap()),
+            ("b".into(), Value::okay(Value::UInt(2)).unwrap()),
+            ("c".into(), Value::error(Value::buff_from(vec![97, 98, 99]).unwrap()).unwrap()),
+            ("d".into(), Value::none()),
+        ]).unwrap());
+
+        assert_eq!(built, expected);
+        assert_eq!(clarity_list![].unwrap(), Value::list_from(vec![]).unwrap());
+    }
+
+    #[test]
+    fn test_build_errors_propagate() {
+        // mixed item types cannot form a list
+        assert!(clarity_list![int(1), uint(2)].is_err());
+        // an error in an inner value propagates through the wrapping builders
+        assert!(clarity_tuple![("a", ok(clarity_list![int(1), none()]))].is_err());
+        assert!(clarity_tuple![("not a name", int(1))].is_err());
+
+        let mut deep = Ok(int(1));
+        for _ in 0..MAX_TYPE_DEPTH {
+            deep = some(deep);
+        }
+        assert!(match deep.unwrap_err() {
+            Error::Unchecked(CheckErrors::TypeSignatureTooDeep) => true,
+            _ => false
+        });
+    }
+}
@@ -1,5 +1,8 @@
 pub mod serialization;
 pub mod signatures;
+pub mod builder;
+#[cfg(any(test, feature = "testing"))]
+pub mod arbitrary;
 
 use std::{fmt, cmp};
 use std::convert::{TryInto, TryFrom};
@@ -8,7 +11,10 @@ use std::collections::BTreeMap;
 use address::c32;
 use vm::representations::{ClarityName, ContractName, SymbolicExpression, SymbolicExpressionType};
 use vm::errors::{RuntimeErrorType, CheckErrors, InterpreterResult as Result, IncomparableError, InterpreterError};
+use vm::costs::cost_functions;
 use util::hash;
+use chainstate::stacks::{C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_MAINNET_MULTISIG,
+                         C32_ADDRESS_VERSION_TESTNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_MULTISIG};
 
 pub use vm::types::signatures::{
     TupleTypeSignature, AssetIdentifier, FixedFunction, FunctionSignature,
@@ -100,12 +106,27 @@ impl fmt::Display for QualifiedContractIdentifier {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
 pub enum PrincipalData {
     Standard(StandardPrincipalData),
     Contract(QualifiedContractIdentifier),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => write!(f, "testnet"),
+        }
+    }
+}
+
 pub enum ContractIdentifier {
     Relative(ContractName),
     Qualified(QualifiedContractIdentifier)
@@ -290,10 +311,234 @@ impl Value {
         }
     }
 
+    pub fn type_signature(&self) -> TypeSignature {
+        TypeSignature::type_of(self)
+    }
+
+    /// Extract the principal held by this value. If `network` is given, the principal
+    ///   must also belong to that network.
+    pub fn expect_principal(self, network: Option<Network>) -> Result<PrincipalData> {
+        let principal = match self {
+            Value::Principal(ref principal) => principal.clone(),
+            _ => return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, self).into())
+        };
+        match network {
+            Some(expected) if principal.network() != Some(expected) => {
+                Err(CheckErrors::PrincipalNetworkMismatch(expected.to_string(), self).into())
+            },
+            _ => Ok(principal)
+        }
+    }
+
+    /// Extract a buffer of exactly 20 bytes, such as a hash160.
+    /// Errors with TypeValueError if this is not a buffer, or the buffer has a different length.
+    pub fn as_buff20(&self) -> Result<[u8; 20]> {
+        let mut bytes = [0; 20];
+        self.copy_exact_buff(&mut bytes, &BUFF_20)?;
+        Ok(bytes)
+    }
+
+    /// Extract a buffer of exactly 32 bytes, such as a sha256 or block hash.
+    /// Errors with TypeValueError if this is not a buffer, or the buffer has a different length.
+    pub fn as_buff32(&self) -> Result<[u8; 32]> {
+        let mut bytes = [0; 32];
+        self.copy_exact_buff(&mut bytes, &BUFF_32)?;
+        Ok(bytes)
+    }
+
+    fn copy_exact_buff(&self, bytes: &mut [u8], expected: &TypeSignature) -> Result<()> {
+        match self {
+            Value::Buffer(BuffData { ref data }) if data.len() == bytes.len() => {
+                bytes.copy_from_slice(data);
+                Ok(())
+            },
+            _ => Err(CheckErrors::TypeValueError(expected.clone(), self.clone()).into())
+        }
+    }
+
+    /// Reinterpret the two's-complement bits of an int as a uint, e.g., -1 becomes u340282366920938463463374607431768211455.
+    pub fn int_to_uint_bits(&self) -> Result<Value> {
+        match self {
+            Value::Int(value) => Ok(Value::UInt(*value as u128)),
+            _ => Err(CheckErrors::TypeValueError(TypeSignature::IntType, self.clone()).into())
+        }
+    }
+
+    /// Reinterpret the bits of a uint as a two's-complement int: the inverse of `int_to_uint_bits`.
+    pub fn uint_to_int_bits(&self) -> Result<Value> {
+        match self {
+            Value::UInt(value) => Ok(Value::Int(*value as i128)),
+            _ => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, self.clone()).into())
+        }
+    }
+
+    /// Whether two values have the same shape, ignoring the values themselves: responses must
+    ///   be in the same branch and optionals must both be some or both be none, with compatible
+    ///   contents. The unused branch of a response (and the contents of none) is typed `NoType`,
+    ///   which acts as a wildcard, e.g., `(ok 1)` and `(ok 2)` are compatible, but `(ok 1)` and
+    ///   `(err u1)` are not. Lists are compared by the shape of their entry types, so
+    ///   `(list (ok 1))` and `(list (err u1))` are not compatible, while an empty list is
+    ///   compatible with any list. Other values are compatible if their types have a least supertype.
+    /// This is distinct from `==`, which compares the values.
+    pub fn responses_compatible(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Response(a), Value::Response(b)) =>
+                a.committed == b.committed && a.data.responses_compatible(&b.data),
+            (Value::Optional(a), Value::Optional(b)) => match (&a.data, &b.data) {
+                (Some(a), Some(b)) => a.responses_compatible(b),
+                (None, None) => true,
+                _ => false
+            },
+            (Value::Tuple(a), Value::Tuple(b)) =>
+                a.data_map.len() == b.data_map.len() &&
+                a.data_map.iter().zip(b.data_map.iter())
+                    .all(|((a_name, a_value), (b_name, b_value))| a_name == b_name && a_value.responses_compatible(b_value)),
+            (Value::List(_), Value::List(_)) =>
+                Value::response_types_compatible(&TypeSignature::type_of(self), &TypeSignature::type_of(other)),
+            _ => TypeSignature::least_supertype(&TypeSignature::type_of(self), &TypeSignature::type_of(other)).is_ok()
+        }
+    }
+
+    // The type level counterpart of `responses_compatible`, for list entries: a `NoType` response
+    //   branch (or optional content) only matches another `NoType`, as it means no value in the
+    //   list took that branch.
+    fn response_types_compatible(a: &TypeSignature, b: &TypeSignature) -> bool {
+        let branches_compatible = |a: &TypeSignature, b: &TypeSignature| match (a, b) {
+            (TypeSignature::NoType, TypeSignature::NoType) => true,
+            (TypeSignature::NoType, _) | (_, TypeSignature::NoType) => false,
+            (a, b) => Value::response_types_compatible(a, b)
+        };
+        match (a, b) {
+            (TypeSignature::ResponseType(a), TypeSignature::ResponseType(b)) =>
+                branches_compatible(&a.0, &b.0) && branches_compatible(&a.1, &b.1),
+            (TypeSignature::OptionalType(a), TypeSignature::OptionalType(b)) => branches_compatible(a, b),
+            (TypeSignature::TupleType(a), TypeSignature::TupleType(b)) =>
+                a.get_type_map().len() == b.get_type_map().len() &&
+                a.get_type_map().iter().zip(b.get_type_map().iter())
+                    .all(|((a_name, a_type), (b_name, b_type))| a_name == b_name && Value::response_types_compatible(a_type, b_type)),
+            (TypeSignature::ListType(a), TypeSignature::ListType(b)) => match (a.get_list_item_type(), b.get_list_item_type()) {
+                (TypeSignature::NoType, _) | (_, TypeSignature::NoType) => true,
+                (a_entry, b_entry) => Value::response_types_compatible(a_entry, b_entry)
+            },
+            _ => TypeSignature::least_supertype(a, b).is_ok()
+        }
+    }
+
+    /// Format an integer value in radix 2, 8, 10 or 16, e.g., 255 becomes `0xff` in radix 16
+    ///   and u5 becomes `u0b101` in radix 2. Lists, tuples, optionals and responses are formatted
+    ///   like `Display`, with their integer leaves in the requested radix.
+    /// Errors with TypeValueError if this is neither an integer nor a container.
+    pub fn format_radix(&self, radix: u32) -> Result<String> {
+        let prefix = match radix {
+            2 => "0b",
+            8 => "0o",
+            10 => "",
+            16 => "0x",
+            _ => return Err(RuntimeErrorType::UnsupportedRadix(radix).into())
+        };
+        match self {
+            Value::Int(_) | Value::UInt(_) | Value::List(_) | Value::Tuple(_) |
+            Value::Optional(_) | Value::Response(_) => Ok(self.format_radix_inner(radix, prefix)),
+            _ => Err(CheckErrors::TypeValueError(TypeSignature::IntType, self.clone()).into())
+        }
+    }
+
+    fn format_radix_inner(&self, radix: u32, prefix: &str) -> String {
+        let format_digits = |value: u128| match radix {
+            2 => format!("{}{:b}", prefix, value),
+            8 => format!("{}{:o}", prefix, value),
+            16 => format!("{}{:x}", prefix, value),
+            _ => format!("{}", value)
+        };
+        match self {
+            Value::Int(int) => {
+                // wrapping_abs leaves i128::min_value() unchanged, which is 2^127 as a u128.
+                let digits = format_digits(int.wrapping_abs() as u128);
+                if *int < 0 { format!("-{}", digits) } else { digits }
+            },
+            Value::UInt(int) => format!("u{}", format_digits(*int)),
+            Value::List(list_data) => {
+                let items: Vec<_> = list_data.data.iter()
+                    .map(|v| v.format_radix_inner(radix, prefix))
+                    .collect();
+                format!("({})", items.join(" "))
+            },
+            Value::Tuple(data) => {
+                let mut result = "(tuple".to_string();
+                for (name, value) in data.data_map.iter() {
+                    result.push_str(&format!(" ({} {})", &**name, value.format_radix_inner(radix, prefix)));
+                }
+                result.push(')');
+                result
+            },
+            Value::Optional(OptionalData { data: Some(ref x) }) => format!("(some {})", x.format_radix_inner(radix, prefix)),
+            Value::Response(ResponseData { committed, ref data }) => {
+                let inner = data.format_radix_inner(radix, prefix);
+                if *committed { format!("(ok {})", inner) } else { format!("(err {})", inner) }
+            },
+            _ => format!("{}", self)
+        }
+    }
+
+    /// If this is a response, returns whether it is an `ok` along with the wrapped value.
+    pub fn as_response(&self) -> Option<(bool, &Value)> {
+        match self {
+            Value::Response(ref response) => Some((response.committed, &response.data)),
+            _ => None
+        }
+    }
+
+    /// Converts a response into a Rust result: `(ok v)` becomes `Ok(v)` and `(err e)` becomes `Err(e)`.
+    /// Errors with ExpectedResponseValue if this is not a response.
+    pub fn into_result(self) -> Result<std::result::Result<Value, Value>> {
+        match self {
+            Value::Response(response) => {
+                if response.committed {
+                    Ok(Ok(*response.data))
+                } else {
+                    Ok(Err(*response.data))
+                }
+            },
+            _ => Err(CheckErrors::ExpectedResponseValue(self).into())
+        }
+    }
+
+    /// Like `==`, but lists and tuples must also have the same declared type signature,
+    ///   e.g., `(list)`s with different element types are _not_ equal.
+    pub fn eq_with_type(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::List(a), Value::List(b)) => {
+                a.type_signature == b.type_signature &&
+                    a.data.len() == b.data.len() &&
+                    a.data.iter().zip(b.data.iter()).all(|(x, y)| x.eq_with_type(y))
+            },
+            (Value::Tuple(a), Value::Tuple(b)) => {
+                a.type_signature == b.type_signature &&
+                    a.data_map.len() == b.data_map.len() &&
+                    a.data_map.iter().zip(b.data_map.iter())
+                    .all(|((x_name, x), (y_name, y))| x_name == y_name && x.eq_with_type(y))
+            },
+            (Value::Optional(OptionalData { data: Some(a) }), Value::Optional(OptionalData { data: Some(b) })) => {
+                a.eq_with_type(b)
+            },
+            (Value::Response(a), Value::Response(b)) => {
+                a.committed == b.committed && a.data.eq_with_type(&b.data)
+            },
+            _ => self == other
+        }
+    }
+
     pub fn size(&self) -> u32 {
         TypeSignature::type_of(self).size()
     }
 
+    /// The runtime cost the VM charges for cloning this value out of a variable binding.
+    pub fn clone_cost(&self) -> u64 {
+        cost_functions::LOOKUP_VARIABLE_SIZE.compute_cost(self.size() as u64)
+            .expect("FATAL: cost of cloning a value overflowed")
+            .runtime
+    }
+
     pub fn depth(&self) -> u8 {
         TypeSignature::type_of(self).depth()
     }
@@ -340,9 +585,49 @@ impl Value {
         Ok(Value::Buffer(BuffData { data: buff_data }))
     }
 
+    /// Copy a borrowed slice into a buffer value, checking the buffer size.
+    pub fn buff_from_slice(buff_data: &[u8]) -> Result<Value> {
+        Value::buff_from(buff_data.to_vec())
+    }
+
+    /// Construct a buffer value without checking its size. This is only for
+    ///   paths where the length is _already_ known to be a valid BufferLength
+    ///   (e.g., deserializing a buffer whose length prefix has been checked):
+    ///   an oversized buffer constructed this way breaks the type system's invariants.
+    pub fn buff_from_unchecked(buff_data: Vec<u8>) -> Value {
+        Value::Buffer(BuffData { data: buff_data })
+    }
+
     pub fn buff_from_byte(byte: u8) -> Value {
         Value::Buffer(BuffData { data: vec![byte] })
     }
+
+    /// Left-pad a buffer with zero bytes to `len` bytes, e.g., to widen a big-endian number
+    ///   to a fixed-width buffer. Buffers which are already at least `len` bytes are unchanged.
+    pub fn buff_pad_left(&self, len: u32) -> Result<Value> {
+        let target_type = TypeSignature::BufferType(BufferLength::try_from(len)?);
+        match self {
+            Value::Buffer(buff) => {
+                let padding = (len as usize).saturating_sub(buff.data.len());
+                let mut data = vec![0; padding];
+                data.extend_from_slice(&buff.data);
+                Ok(Value::Buffer(BuffData { data }))
+            },
+            _ => Err(CheckErrors::TypeValueError(target_type, self.clone()).into())
+        }
+    }
+
+    /// Truncate a buffer to its first `len` bytes. Buffers of at most `len` bytes are unchanged.
+    pub fn buff_truncate(&self, len: u32) -> Result<Value> {
+        let target_type = TypeSignature::BufferType(BufferLength::try_from(len)?);
+        match self {
+            Value::Buffer(buff) => {
+                let end = cmp::min(len as usize, buff.data.len());
+                Ok(Value::Buffer(BuffData { data: buff.data[..end].to_vec() }))
+            },
+            _ => Err(CheckErrors::TypeValueError(target_type, self.clone()).into())
+        }
+    }
 }
 
 impl BuffData {
@@ -357,6 +642,47 @@ impl ListData {
     }
 }
 
+/// Builds a list value one element at a time, checking each element as it is pushed
+///   rather than after the whole list has been allocated.
+pub struct ListBuilder {
+    data: Vec<Value>,
+    type_signature: ListTypeData
+}
+
+impl ListBuilder {
+    /// Errors if a list of `max_len` elements of `element_type` would be too large or too deep.
+    pub fn new(element_type: TypeSignature, max_len: u32) -> Result<ListBuilder> {
+        let type_signature = ListTypeData::new_list(element_type, max_len)?;
+        Ok(ListBuilder { data: Vec::new(), type_signature })
+    }
+
+    /// Errors with ConstructedListTooLarge if the list is already at its maximum length, or
+    ///   with TypeValueError if the element is not admitted by the element type.
+    pub fn push(&mut self, value: Value) -> Result<()> {
+        if self.data.len() >= self.type_signature.get_max_len() as usize {
+            return Err(CheckErrors::ConstructedListTooLarge.into())
+        }
+        let element_type = self.type_signature.get_list_item_type();
+        if !element_type.admits(&value) {
+            return Err(CheckErrors::TypeValueError(element_type.clone(), value).into())
+        }
+        self.data.push(value);
+        Ok(())
+    }
+
+    pub fn len(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn build(self) -> Value {
+        Value::List(ListData { data: self.data, type_signature: self.type_signature })
+    }
+}
+
 impl fmt::Display for OptionalData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.data {
@@ -422,6 +748,16 @@ impl PrincipalData {
         }
     }
 
+    /// The network of this principal, as given by its address version byte,
+    ///   or None if the version byte is not a mainnet or testnet address version.
+    pub fn network(&self) -> Option<Network> {
+        match self.version() {
+            C32_ADDRESS_VERSION_MAINNET_SINGLESIG | C32_ADDRESS_VERSION_MAINNET_MULTISIG => Some(Network::Mainnet),
+            C32_ADDRESS_VERSION_TESTNET_SINGLESIG | C32_ADDRESS_VERSION_TESTNET_MULTISIG => Some(Network::Testnet),
+            _ => None
+        }
+    }
+
     pub fn parse(literal: &str) -> Result<PrincipalData> {
         // be permissive about leading single-quote
         let literal = if literal.starts_with("'") {
@@ -461,6 +797,20 @@ impl StandardPrincipalData {
         c32::c32_address(self.0, &self.1[..])
             .unwrap_or_else(|_| "INVALID_C32_ADD".to_string())
     }
+
+    /// The equivalent principal on the given network: the same hash bytes, with the
+    ///   single-sig or multi-sig version byte of that network. Principals whose version
+    ///   byte is not one of the standard address versions are returned unchanged.
+    pub fn to_network(&self, network: Network) -> StandardPrincipalData {
+        let version = match (self.0, network) {
+            (C32_ADDRESS_VERSION_MAINNET_SINGLESIG, Network::Testnet) => C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            (C32_ADDRESS_VERSION_MAINNET_MULTISIG, Network::Testnet) => C32_ADDRESS_VERSION_TESTNET_MULTISIG,
+            (C32_ADDRESS_VERSION_TESTNET_SINGLESIG, Network::Mainnet) => C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+            (C32_ADDRESS_VERSION_TESTNET_MULTISIG, Network::Mainnet) => C32_ADDRESS_VERSION_MAINNET_MULTISIG,
+            (version, _) => version
+        };
+        StandardPrincipalData(version, self.1)
+    }
 }
 
 impl fmt::Display for StandardPrincipalData {
@@ -540,6 +890,11 @@ impl TupleData {
         let mut data_map = BTreeMap::new();
         for (name, value) in data.drain(..) {
             let type_info = TypeSignature::type_of(&value);
+            // fail on the first field that is too deep, rather than typing
+            //   all the fields before TupleTypeSignature checks their depth.
+            if type_info.depth() + 1 > MAX_TYPE_DEPTH {
+                return Err(CheckErrors::TypeSignatureTooDeep.into());
+            }
             if type_map.contains_key(&name) {
                 return Err(CheckErrors::NameAlreadyUsed(name.into()).into());
             } else {
@@ -573,6 +928,26 @@ impl TupleData {
         self.data_map.remove(name)
             .ok_or_else(|| CheckErrors::NoSuchTupleField(name.to_string(), self.type_signature.clone()).into())
     }
+
+    /// Build a tuple of only the `names` fields, cloning just those values. The fields keep
+    ///   their types in this tuple's type signature.
+    pub fn project(&self, names: &[&str]) -> Result<TupleData> {
+        let mut type_map = BTreeMap::new();
+        let mut data_map = BTreeMap::new();
+        for name in names.iter() {
+            let (name, value) = self.data_map.get_key_value(*name)
+                .ok_or_else(|| CheckErrors::NoSuchTupleField(name.to_string(), self.type_signature.clone()))?;
+            if data_map.contains_key(name) {
+                return Err(CheckErrors::NameAlreadyUsed(name.to_string()).into());
+            }
+            let field_type = self.type_signature.field_type(name)
+                .ok_or(InterpreterError::FailureConstructingTupleWithType)?;
+            type_map.insert(name.clone(), field_type.clone());
+            data_map.insert(name.clone(), value.clone());
+        }
+
+        Self::new(TupleTypeSignature::try_from(type_map)?, data_map)
+    }
 }
 
 impl fmt::Display for TupleData {
@@ -663,6 +1038,263 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_type_signature() {
+        let values = vec![
+            Value::Int(1),
+            Value::UInt(1),
+            Value::Bool(true),
+            Value::buff_from(vec![1, 2, 3]).unwrap(),
+            Value::list_from(vec![Value::Int(1), Value::Int(2)]).unwrap(),
+            Value::from(PrincipalData::parse_standard_principal("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G").unwrap()),
+            Value::from(TupleData::from_data(vec![("a".into(), Value::Int(2))]).unwrap()),
+            Value::some(Value::Int(1)).unwrap(),
+            Value::none(),
+            Value::okay(Value::UInt(1)).unwrap(),
+            Value::error(Value::Bool(false)).unwrap(),
+        ];
+
+        for value in values.iter() {
+            assert_eq!(value.type_signature(), TypeSignature::type_of(value));
+        }
+        assert_eq!(Value::Int(1).type_signature(), TypeSignature::IntType);
+    }
+
+    #[test]
+    fn test_principal_to_network() {
+        for (address, testnet_prefix) in [("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7", "ST"),
+                                          ("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G", "SN")].iter() {
+            let mainnet = PrincipalData::parse_standard_principal(address).unwrap();
+            let testnet = mainnet.to_network(Network::Testnet);
+            assert!(testnet.to_address().starts_with(testnet_prefix));
+            assert_eq!(testnet.1, mainnet.1);
+            assert_eq!(PrincipalData::from(testnet.clone()).network(), Some(Network::Testnet));
+
+            assert_eq!(testnet.to_network(Network::Testnet), testnet);
+            let recovered = testnet.to_network(Network::Mainnet);
+            assert_eq!(recovered, mainnet);
+            assert_eq!(&recovered.to_address(), address);
+        }
+
+        let transient = StandardPrincipalData::transient();
+        assert_eq!(transient.to_network(Network::Mainnet), transient);
+    }
+
+    #[test]
+    fn test_as_exact_buffs() {
+        use vm::errors::Error;
+
+        let hash160 = Value::buff_from(vec![1; 20]).unwrap();
+        let sha256 = Value::buff_from((0..32).collect::<Vec<u8>>()).unwrap();
+        assert_eq!(hash160.as_buff20().unwrap(), [1; 20]);
+        assert_eq!(sha256.as_buff32().unwrap().to_vec(), (0..32).collect::<Vec<u8>>());
+
+        assert_eq!(hash160.as_buff32().unwrap_err(),
+                   Error::Unchecked(CheckErrors::TypeValueError(BUFF_32, hash160.clone())));
+        assert_eq!(sha256.as_buff20().unwrap_err(),
+                   Error::Unchecked(CheckErrors::TypeValueError(BUFF_20, sha256.clone())));
+        assert_eq!(Value::buff_from(vec![1; 19]).unwrap().as_buff20().unwrap_err(),
+                   Error::Unchecked(CheckErrors::TypeValueError(BUFF_20, Value::buff_from(vec![1; 19]).unwrap())));
+        assert_eq!(Value::Int(1).as_buff32().unwrap_err(),
+                   Error::Unchecked(CheckErrors::TypeValueError(BUFF_32, Value::Int(1))));
+    }
+
+    #[test]
+    fn test_expect_principal() {
+        use vm::errors::Error;
+
+        let mainnet = PrincipalData::parse("SP000000000000000000002Q6VF78").unwrap();
+        let testnet = PrincipalData::parse("ST000000000000000000002AMW42H.contract").unwrap();
+
+        assert_eq!(Value::from(mainnet.clone()).expect_principal(Some(Network::Mainnet)).unwrap(), mainnet);
+        assert_eq!(Value::from(mainnet.clone()).expect_principal(None).unwrap(), mainnet);
+        assert_eq!(Value::from(testnet.clone()).expect_principal(Some(Network::Testnet)).unwrap(), testnet);
+
+        match Value::from(testnet.clone()).expect_principal(Some(Network::Mainnet)).unwrap_err() {
+            Error::Unchecked(CheckErrors::PrincipalNetworkMismatch(network, value)) => {
+                assert_eq!(network, "mainnet");
+                assert_eq!(value, Value::from(testnet));
+            },
+            e => panic!("Unexpected error: {:?}", e)
+        }
+
+        match Value::Int(1).expect_principal(None).unwrap_err() {
+            Error::Unchecked(CheckErrors::TypeValueError(TypeSignature::PrincipalType, value)) => {
+                assert_eq!(value, Value::Int(1));
+            },
+            e => panic!("Unexpected error: {:?}", e)
+        }
+    }
+
+    #[test]
+    fn test_buff_from_variants() {
+        let data = vec![0xde, 0xad, 0xbe, 0xef];
+        let checked = Value::buff_from(data.clone()).unwrap();
+        assert_eq!(Value::buff_from_unchecked(data.clone()), checked);
+        assert_eq!(Value::buff_from_slice(&data).unwrap(), checked);
+        assert_eq!(Value::buff_from_unchecked(vec![]), Value::buff_from(vec![]).unwrap());
+
+        let too_large = vec![0; MAX_VALUE_SIZE as usize + 1];
+        assert!(Value::buff_from_slice(&too_large).is_err());
+    }
+
+    #[test]
+    fn test_int_uint_bits() {
+        let pairs = [(Value::Int(-1), Value::UInt(u128::max_value())),
+                     (Value::Int(0), Value::UInt(0)),
+                     (Value::Int(i128::min_value()), Value::UInt(1 << 127)),
+                     (Value::Int(i128::max_value()), Value::UInt((1 << 127) - 1))];
+        for (int, uint) in pairs.iter() {
+            assert_eq!(&int.int_to_uint_bits().unwrap(), uint);
+            assert_eq!(&uint.uint_to_int_bits().unwrap(), int);
+        }
+
+        assert_eq!(Value::UInt(1).int_to_uint_bits().unwrap_err(),
+                   CheckErrors::TypeValueError(TypeSignature::IntType, Value::UInt(1)).into());
+        assert_eq!(Value::Bool(true).uint_to_int_bits().unwrap_err(),
+                   CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Bool(true)).into());
+    }
+
+    #[test]
+    fn test_buff_pad_and_truncate() {
+        let short = Value::buff_from(vec![1, 2, 3]).unwrap();
+        let mut expected = vec![0; 29];
+        expected.extend_from_slice(&[1, 2, 3]);
+        let padded = short.buff_pad_left(32).unwrap();
+        assert_eq!(padded, Value::buff_from(expected).unwrap());
+        assert_eq!(TypeSignature::type_of(&padded), BUFF_32);
+        assert_eq!(short.buff_pad_left(2).unwrap(), short);
+
+        let long = Value::buff_from((0..40).collect()).unwrap();
+        assert_eq!(long.buff_truncate(32).unwrap(), Value::buff_from((0..32).collect()).unwrap());
+        assert_eq!(short.buff_truncate(32).unwrap(), short);
+        assert_eq!(long.buff_truncate(0).unwrap(), Value::buff_from(vec![]).unwrap());
+
+        assert_eq!(short.buff_pad_left(MAX_VALUE_SIZE + 1).unwrap_err(), CheckErrors::ValueTooLarge.into());
+        assert_eq!(short.buff_truncate(MAX_VALUE_SIZE + 1).unwrap_err(), CheckErrors::ValueTooLarge.into());
+        assert_eq!(Value::Int(1).buff_pad_left(32).unwrap_err(),
+                   CheckErrors::TypeValueError(BUFF_32, Value::Int(1)).into());
+        assert_eq!(Value::Int(1).buff_truncate(32).unwrap_err(),
+                   CheckErrors::TypeValueError(BUFF_32, Value::Int(1)).into());
+    }
+
+    #[test]
+    fn test_list_builder() {
+        let mut builder = ListBuilder::new(TypeSignature::IntType, 3).unwrap();
+        assert!(builder.is_empty());
+        builder.push(Value::Int(1)).unwrap();
+        builder.push(Value::Int(2)).unwrap();
+        assert_eq!(builder.push(Value::UInt(3)).unwrap_err(),
+                   CheckErrors::TypeValueError(TypeSignature::IntType, Value::UInt(3)).into());
+        assert_eq!(builder.len(), 2);
+        assert!(!builder.is_empty());
+        builder.push(Value::Int(3)).unwrap();
+        assert_eq!(builder.push(Value::Int(4)).unwrap_err(),
+                   CheckErrors::ConstructedListTooLarge.into());
+        assert_eq!(builder.build(),
+                   Value::list_with_type(vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+                                         ListTypeData::new_list(TypeSignature::IntType, 3).unwrap()).unwrap());
+
+        assert_eq!(ListBuilder::new(TypeSignature::IntType, MAX_VALUE_SIZE).err().unwrap(),
+                   CheckErrors::ValueTooLarge.into());
+    }
+
+    #[test]
+    fn test_responses_compatible() {
+        let ok_1 = Value::okay(Value::Int(1)).unwrap();
+        let ok_2 = Value::okay(Value::Int(2)).unwrap();
+        let err_u1 = Value::error(Value::UInt(1)).unwrap();
+
+        assert!(ok_1.responses_compatible(&ok_2));
+        assert!(ok_1 != ok_2);
+        assert!(!ok_1.responses_compatible(&err_u1));
+        assert!(!ok_1.responses_compatible(&Value::okay(Value::UInt(1)).unwrap()));
+
+        assert!(Value::some(ok_1.clone()).unwrap().responses_compatible(&Value::some(ok_2.clone()).unwrap()));
+        assert!(!Value::some(ok_1.clone()).unwrap().responses_compatible(&Value::some(err_u1.clone()).unwrap()));
+        assert!(!Value::some(ok_1.clone()).unwrap().responses_compatible(&Value::none()));
+        assert!(Value::none().responses_compatible(&Value::none()));
+
+        let tuple = |v: Value| Value::from(TupleData::from_data(vec![("a".into(), v)]).unwrap());
+        assert!(tuple(ok_1.clone()).responses_compatible(&tuple(ok_2.clone())));
+        assert!(!tuple(ok_1.clone()).responses_compatible(&tuple(err_u1.clone())));
+
+        assert!(Value::list_from(vec![ok_1.clone()]).unwrap()
+                .responses_compatible(&Value::list_from(vec![ok_2.clone(), ok_1.clone()]).unwrap()));
+        assert!(!Value::list_from(vec![ok_1.clone()]).unwrap()
+                .responses_compatible(&Value::list_from(vec![err_u1.clone()]).unwrap()));
+        assert!(!Value::list_from(vec![ok_1.clone()]).unwrap()
+                .responses_compatible(&Value::list_from(vec![ok_2.clone(), err_u1.clone()]).unwrap()));
+        assert!(!Value::list_from(vec![Value::some(ok_1.clone()).unwrap()]).unwrap()
+                .responses_compatible(&Value::list_from(vec![Value::some(err_u1.clone()).unwrap()]).unwrap()));
+        assert!(Value::list_from(vec![]).unwrap()
+                .responses_compatible(&Value::list_from(vec![err_u1.clone()]).unwrap()));
+        assert!(!Value::Int(1).responses_compatible(&Value::UInt(1)));
+    }
+
+    #[test]
+    fn test_format_radix() {
+        let cases = [(2, "0b11111111", "-0b101", "u0b1010"),
+                     (8, "0o377", "-0o5", "u0o12"),
+                     (10, "255", "-5", "u10"),
+                     (16, "0xff", "-0x5", "u0xa")];
+        for (radix, positive, negative, uint) in cases.iter() {
+            assert_eq!(&Value::Int(255).format_radix(*radix).unwrap(), positive);
+            assert_eq!(&Value::Int(-5).format_radix(*radix).unwrap(), negative);
+            assert_eq!(&Value::UInt(10).format_radix(*radix).unwrap(), uint);
+        }
+
+        assert_eq!(Value::Int(i128::min_value()).format_radix(16).unwrap(),
+                   "-0x80000000000000000000000000000000");
+
+        let list = Value::list_from(vec![Value::Int(10), Value::Int(-16)]).unwrap();
+        assert_eq!(list.format_radix(16).unwrap(), "(0xa -0x10)");
+        let response = Value::okay(Value::some(Value::UInt(255)).unwrap()).unwrap();
+        assert_eq!(response.format_radix(16).unwrap(), "(ok (some u0xff))");
+        assert_eq!(format!("{}", Value::Int(255)), "255");
+
+        assert_eq!(Value::Int(1).format_radix(3).unwrap_err(),
+                   RuntimeErrorType::UnsupportedRadix(3).into());
+        assert_eq!(Value::Bool(true).format_radix(16).unwrap_err(),
+                   CheckErrors::TypeValueError(TypeSignature::IntType, Value::Bool(true)).into());
+    }
+
+    #[test]
+    fn test_response_accessors() {
+        let ok = Value::okay(Value::Int(1)).unwrap();
+        assert_eq!(ok.as_response(), Some((true, &Value::Int(1))));
+        assert_eq!(ok.into_result().unwrap(), Ok(Value::Int(1)));
+
+        let err = Value::error(Value::UInt(2)).unwrap();
+        assert_eq!(err.as_response(), Some((false, &Value::UInt(2))));
+        assert_eq!(err.into_result().unwrap(), Err(Value::UInt(2)));
+
+        let not_response = Value::some(Value::Int(1)).unwrap();
+        assert_eq!(not_response.as_response(), None);
+        assert_eq!(not_response.clone().into_result().unwrap_err(),
+                   CheckErrors::ExpectedResponseValue(not_response).into());
+    }
+
+    #[test]
+    fn test_eq_with_type() {
+        let int_list = Value::list_with_type(vec![], ListTypeData::new_list(TypeSignature::IntType, 5).unwrap()).unwrap();
+        let bool_list = Value::list_with_type(vec![], ListTypeData::new_list(TypeSignature::BoolType, 5).unwrap()).unwrap();
+
+        assert_eq!(int_list, bool_list);
+        assert!(!int_list.eq_with_type(&bool_list));
+        assert!(int_list.eq_with_type(&int_list.clone()));
+
+        // nested containers are compared with their types, too
+        let wrap = |v: Value| Value::okay(
+            Value::from(TupleData::from_data(vec![("a".into(), Value::some(v).unwrap())]).unwrap())).unwrap();
+        assert_eq!(wrap(int_list.clone()), wrap(bool_list.clone()));
+        assert!(!wrap(int_list.clone()).eq_with_type(&wrap(bool_list)));
+        assert!(wrap(int_list.clone()).eq_with_type(&wrap(int_list)));
+
+        assert!(Value::Int(1).eq_with_type(&Value::Int(1)));
+        assert!(!Value::Int(1).eq_with_type(&Value::UInt(1)));
+    }
+
     #[test]
     fn simple_size_test() {
         assert_eq!(Value::Int(10).size(), 16);
@@ -676,6 +1308,50 @@ mod test {
         t.get("abcd").unwrap_err();
     }
 
+    #[test]
+    fn test_tuple_field_depth_guard() {
+        let mut deepest_field = Value::Int(1);
+        for _ in 0..(MAX_TYPE_DEPTH - 2) {
+            deepest_field = Value::some(deepest_field).unwrap();
+        }
+        assert_eq!(deepest_field.depth(), MAX_TYPE_DEPTH - 1);
+        let too_deep_field = Value::some(deepest_field.clone()).unwrap();
+
+        TupleData::from_data(vec![("a".into(), deepest_field.clone()), ("b".into(), Value::Int(1))]).unwrap();
+
+        // the construction fails at the too-deep field, before reaching later fields
+        assert_eq!(TupleData::from_data(vec![("a".into(), too_deep_field.clone()),
+                                             ("a".into(), deepest_field.clone())]),
+                   Err(CheckErrors::TypeSignatureTooDeep.into()));
+        assert_eq!(TupleData::from_data(vec![("a".into(), Value::Int(1)), ("b".into(), too_deep_field)]),
+                   Err(CheckErrors::TypeSignatureTooDeep.into()));
+    }
+
+    #[test]
+    fn test_tuple_project() {
+        let list_type: TypeSignature = "(list 5 int)".into();
+        let tuple_type = TupleTypeSignature::try_from(vec![
+            ("id".into(), TypeSignature::UIntType),
+            ("name".into(), TypeSignature::BufferType(10u32.try_into().unwrap())),
+            ("scores".into(), list_type.clone())]).unwrap();
+        let t = TupleData::from_data_typed(vec![
+            ("id".into(), Value::UInt(1)),
+            ("name".into(), Value::buff_from(vec![0x61]).unwrap()),
+            ("scores".into(), Value::list_from(vec![Value::Int(3)]).unwrap())], &tuple_type).unwrap();
+
+        let projected = t.project(&["scores", "id"]).unwrap();
+        assert_eq!(projected.len(), 2);
+        assert_eq!(projected.get("id"), Ok(&Value::UInt(1)));
+        projected.get("name").unwrap_err();
+        // the fields keep their declared types
+        assert_eq!(projected.type_signature.field_type("scores"), Some(&list_type));
+        assert_eq!(projected.type_signature.get_type_map().len(), 2);
+
+        assert_eq!(t.project(&["id", "owner"]).unwrap_err(),
+                   CheckErrors::NoSuchTupleField("owner".to_string(), tuple_type).into());
+        t.project(&["id", "id"]).unwrap_err();
+    }
+
     #[test]
     fn test_some_displays() {
         assert_eq!(&format!("{}", Value::list_from(vec![Value::Int(10), Value::Int(5)]).unwrap()),
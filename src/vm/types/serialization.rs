@@ -25,12 +25,15 @@ use std::io::{Write, Read};
 /// Any IOErrrors from the supplied buffer will manifest as IOError variants,
 ///   except for EOF -- if the deserialization code experiences an EOF, it is caught
 ///   and rethrown as DeserializationError
+/// A type prefix byte missing from the TypePrefix registry is reported as UnknownTypePrefix,
+///   as it was most likely written by a newer Clarity version.
 #[derive(Debug, PartialEq)]
 pub enum SerializationError {
     IOError(IncomparableError<std::io::Error>),
     BadTypeError(CheckErrors),
     DeserializationError(String),
     DeserializeExpected(TypeSignature),
+    UnknownTypePrefix(u8),
 }
 
 
@@ -41,6 +44,7 @@ impl std::fmt::Display for SerializationError {
             SerializationError::BadTypeError(e) => write!(f, "Deserialization error, bad type, caused by: {}", e),
             SerializationError::DeserializationError(e) => write!(f, "Deserialization error: {}", e),
             SerializationError::DeserializeExpected(e) => write!(f, "Deserialization expected the type of the input to be: {}", e),
+            SerializationError::UnknownTypePrefix(b) => write!(f, "Deserialization error: unknown value type byte 0x{:02x} -- the value was serialized by a newer Clarity version", b),
         }
     }
 }
@@ -75,6 +79,9 @@ impl From<CheckErrors> for SerializationError {
     }
 }
 
+// The registry of value type prefix bytes: a prefix is the variant's position, so new
+//   variants must only ever be appended, and a byte past the last variant is reported as
+//   an UnknownTypePrefix rather than a malformed value.
 define_u8_enum!(TypePrefix {
     Int,
     UInt,
@@ -208,7 +215,7 @@ impl PrincipalData {
         r.read_exact(&mut header)?;
 
         let prefix = TypePrefix::from_u8(header[0])
-            .ok_or_else(|| "Bad principal prefix")?;
+            .ok_or_else(|| SerializationError::UnknownTypePrefix(header[0]))?;
 
         match prefix {
             TypePrefix::PrincipalStandard => {
@@ -263,7 +270,7 @@ impl Value {
         r.read_exact(&mut header)?;
 
         let prefix = TypePrefix::from_u8(header[0])
-            .ok_or_else(|| "Bad type prefix")?;
+            .ok_or_else(|| SerializationError::UnknownTypePrefix(header[0]))?;
 
         match prefix {
             TypePrefix::Int => {
@@ -300,8 +307,8 @@ impl Value {
 
                 r.read_exact(&mut data[..])?;
 
-                // can safely unwrap, because the buffer length was _already_ checked.
-                Ok(Value::buff_from(data).unwrap())
+                // the buffer length was _already_ checked.
+                Ok(Value::buff_from_unchecked(data))
             },
             TypePrefix::BoolTrue => {
                 check_match!(expected_type, TypeSignature::BoolType)?;
@@ -487,20 +494,38 @@ impl Value {
                     item.serialize_write(w)?;
                 }
             },
-            Tuple(data) => {
-                w.write_all(&u32::try_from(data.data_map.len())
-                            .unwrap()
-                            .to_be_bytes())?;
-                for (key, value) in data.data_map.iter() {
-                    key.serialize_write(w)?;
-                    value.serialize_write(w)?;
-                }
-            }
+            Tuple(data) => data.serialize_fields_write(w)?
         };
 
         Ok(())
     }
 
+    /// The number of bytes `serialize_write` writes for this value, computed without serializing it.
+    pub fn serialized_size(&self) -> u64 {
+        use super::Value::*;
+        use super::PrincipalData::*;
+
+        // every value starts with its type prefix byte
+        let payload_size = match self {
+            Int(_) | UInt(_) => 16,
+            Buffer(value) => 4 + value.data.len() as u64,
+            // version byte + hash bytes
+            Principal(Standard(_)) => 1 + 20,
+            // issuer + length-prefixed name
+            Principal(Contract(contract_identifier)) => 1 + 20 + 1 + contract_identifier.name.len() as u64,
+            Response(response) => response.data.serialized_size(),
+            Bool(_) => 0,
+            Optional(OptionalData{ data: None }) => 0,
+            Optional(OptionalData{ data: Some(value) }) => value.serialized_size(),
+            List(data) => 4 + data.data.iter().map(|item| item.serialized_size()).sum::<u64>(),
+            Tuple(data) => 4 + data.data_map.iter()
+                .map(|(key, value)| 1 + key.len() as u64 + value.serialized_size())
+                .sum::<u64>()
+        };
+
+        1 + payload_size
+    }
+
     /// This function attempts to deserialize a hex string into a Clarity Value.
     ///   The `expected_type` parameter determines whether or not the deserializer should expect (and enforce)
     ///   a particular type. `ClarityDB` uses this to ensure that lists, tuples, etc. loaded from the database
@@ -547,6 +572,30 @@ impl Value {
     }
 }
 
+impl TupleData {
+    // fields are written in the order of the data map, i.e., sorted by name.
+    fn serialize_fields_write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(&u32::try_from(self.data_map.len())
+                    .unwrap()
+                    .to_be_bytes())?;
+        for (key, value) in self.data_map.iter() {
+            key.serialize_write(w)?;
+            value.serialize_write(w)?;
+        }
+        Ok(())
+    }
+
+    /// The consensus serialization of this tuple as a value. The fields are serialized
+    ///   in ClarityName order, so the bytes only depend on the tuple's contents, not on
+    ///   the order in which its fields were given when it was constructed.
+    pub fn serialize_canonical(&self) -> Vec<u8> {
+        let mut byte_serialization = vec![TypePrefix::Tuple as u8];
+        self.serialize_fields_write(&mut byte_serialization)
+            .expect("IOError filling byte buffer.");
+        byte_serialization
+    }
+}
+
 impl ClaritySerializable for Value {
     fn serialize(&self) -> String {
         let mut byte_serialization = Vec::new();
@@ -566,7 +615,8 @@ impl ClarityDeserializable<Value> for Value {
 #[cfg(test)]
 mod tests {
     use std::io::Write;
-    use super::SerializationError;
+    use super::{SerializationError, TypePrefix};
+    use util::hash::to_hex;
     use vm::database::ClaritySerializable;
     use vm::errors::Error;
     use super::super::*;
@@ -785,7 +835,7 @@ mod tests {
     #[test]
     fn test_vectors() {
         let tests = [
-            ("1010", Err("Bad type prefix".into())),
+            ("1010", Err(SerializationError::UnknownTypePrefix(0x10))),
             ("0000000000000000000000000000000001", Ok(Value::Int(1))),
             ("00ffffffffffffffffffffffffffffffff", Ok(Value::Int(-1))),
             ("0100000000000000000000000000000001", Ok(Value::UInt(1))),
@@ -849,6 +899,41 @@ mod tests {
                    SerializationError::DeserializationError("Illegal tuple type".to_string()));
     }
 
+    #[test]
+    fn test_tuple_serialize_canonical() {
+        let fields = vec![("zeta".into(), Value::Int(-1)),
+                          ("alpha".into(), Value::some(Value::UInt(2)).unwrap()),
+                          ("mid".into(), Value::from(TupleData::from_data(vec![
+                              ("b".into(), Value::Bool(true)), ("a".into(), Value::none())]).unwrap()))];
+        let mut reversed = fields.clone();
+        reversed.reverse();
+
+        let tuple = TupleData::from_data(fields).unwrap();
+        let canonical = tuple.serialize_canonical();
+        assert_eq!(canonical, TupleData::from_data(reversed).unwrap().serialize_canonical());
+
+        // the canonical serialization is the consensus serialization of the tuple value
+        let value = Value::from(tuple);
+        assert_eq!(to_hex(&canonical), value.serialize());
+        assert_eq!(Value::try_deserialize_bytes_untyped(&canonical).unwrap(), value);
+    }
+
+    #[test]
+    fn try_deser_unknown_type_prefix() {
+        let buff = vec![0xf3, 0, 0, 0, 1];
+        let err = Value::try_deserialize_bytes_untyped(&buff).unwrap_err();
+        assert_eq!(err, SerializationError::UnknownTypePrefix(0xf3));
+        assert_eq!(err.to_string(),
+                   "Deserialization error: unknown value type byte 0xf3 -- the value was serialized by a newer Clarity version");
+
+        // the first byte past the registry is unknown, nested or not
+        let next_prefix = TypePrefix::ALL.len() as u8;
+        assert_eq!(Value::try_deserialize_bytes_untyped(&vec![next_prefix]).unwrap_err(),
+                   SerializationError::UnknownTypePrefix(next_prefix));
+        assert_eq!(Value::try_deserialize_bytes_untyped(&vec![TypePrefix::OptionalSome as u8, next_prefix]).unwrap_err(),
+                   SerializationError::UnknownTypePrefix(next_prefix));
+    }
+
     #[test]
     fn try_overflow_stack() {
         let input = "08080808080808080808070707080807080808080808080708080808080708080707080707080807080808080808080708080808080708080707080708070807080808080808080708080808080708080708080808080808080807070807080808080808070808070707080807070808070808080808070808070708070807080808080808080707080708070807080708080808080808070808080808070808070808080808080808080707080708080808080807080807070708080707080807080808080807080807070807080708080808080808070708070808080808080708080707070808070708080807080807070708";
@@ -857,6 +942,39 @@ mod tests {
             Value::try_deserialize_hex_untyped(input));
     }
 
+    #[test]
+    fn test_serialized_size() {
+        let issuer = PrincipalData::parse_standard_principal("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G").unwrap();
+        let contract_identifier = QualifiedContractIdentifier::new(issuer.clone(), "foo".into());
+        let tuple = Value::from(TupleData::from_data(vec![
+            ("a".into(), Value::Int(1)),
+            ("long-field-name".into(), Value::buff_from(vec![1, 2, 3]).unwrap())]).unwrap());
+
+        let values = [
+            Value::Int(-1),
+            Value::UInt(1),
+            Value::Bool(true),
+            Value::buff_from(vec![]).unwrap(),
+            Value::buff_from(vec![0; 100]).unwrap(),
+            Value::from(issuer),
+            Value::from(PrincipalData::Contract(contract_identifier)),
+            Value::none(),
+            Value::some(Value::Int(1)).unwrap(),
+            Value::okay(Value::Bool(false)).unwrap(),
+            Value::error(tuple.clone()).unwrap(),
+            Value::list_from(vec![]).unwrap(),
+            Value::list_from(vec![Value::Int(1), Value::Int(2)]).unwrap(),
+            Value::list_from(vec![tuple.clone(), tuple.clone()]).unwrap(),
+            tuple,
+        ];
+
+        for value in values.iter() {
+            let mut bytes = vec![];
+            value.serialize_write(&mut bytes).unwrap();
+            assert_eq!(value.serialized_size(), bytes.len() as u64, "{}", value);
+        }
+    }
+
     #[test]
     fn test_principals() {
         let issuer = PrincipalData::parse_standard_principal("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G").unwrap();
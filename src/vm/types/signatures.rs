@@ -1,6 +1,7 @@
 // TypeSignatures
 use std::hash::{Hash, Hasher};
 use std::{fmt, cmp};
+use std::str::FromStr;
 use std::convert::{TryFrom, TryInto};
 use std::collections::{BTreeMap, HashMap};
 
@@ -10,11 +11,12 @@ use vm::types::{Value, MAX_VALUE_SIZE, MAX_TYPE_DEPTH, WRAPPER_VALUE_SIZE,
                 QualifiedContractIdentifier, StandardPrincipalData, TraitIdentifier};
 use vm::representations::{SymbolicExpression, SymbolicExpressionType, ClarityName, ContractName, TraitDefinition};
 use vm::errors::{RuntimeErrorType, CheckErrors, IncomparableError, Error as VMError};
+use vm::ast::build_ast;
 use util::hash;
 
 type Result <R> = std::result::Result<R, CheckErrors>;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, PartialOrd, Ord)]
 pub struct AssetIdentifier {
     pub contract_identifier: QualifiedContractIdentifier,
     pub asset_name: ClarityName
@@ -117,6 +119,28 @@ pub struct FunctionArg {
     pub name: ClarityName,
 }
 
+/// Parse the `Display` form of a type signature, e.g., `(optional (list 10 int))`.
+///   Trait references are written as `<issuer.contract.trait>`. Types which cannot be
+///   written in a contract (such as the `UnknownType` of an unused response branch) are rejected.
+impl FromStr for TypeSignature {
+    type Err = CheckErrors;
+    fn from_str(type_repr: &str) -> Result<TypeSignature> {
+        let type_repr = type_repr.trim();
+        if type_repr.starts_with('<') && type_repr.ends_with('>') {
+            let trait_id = TraitIdentifier::parse_fully_qualified(&type_repr[1..type_repr.len() - 1])
+                .map_err(|_| CheckErrors::InvalidTypeDescription)?;
+            return Ok(TypeSignature::TraitReferenceType(trait_id))
+        }
+
+        let ast = build_ast(&QualifiedContractIdentifier::transient(), type_repr, &mut ())
+            .map_err(|_| CheckErrors::InvalidTypeDescription)?;
+        match ast.expressions.as_slice() {
+            [expr] => TypeSignature::parse_type_repr(expr, &mut ()),
+            _ => Err(CheckErrors::InvalidTypeDescription)
+        }
+    }
+}
+
 #[cfg(test)]
 impl From<&str> for TypeSignature {
     fn from(val: &str) -> Self {
@@ -267,7 +291,15 @@ impl TypeSignature {
         self.admits_type(&x_type)
     }
 
+    /// Does this type admit values of type `other`? `NoType` is the bottom type: it is
+    ///   the type of the missing branch of `none`, `(ok ..)` and `(err ..)` values and of
+    ///   the entries of an empty list, and it is admitted by every type, including
+    ///   when nested inside optionals, responses, lists and tuples.
     pub fn admits_type(&self, other: &TypeSignature) -> bool {
+        if other.is_no_type() {
+            return true
+        }
+
         match self {
             ListType(ref my_list_type) => {
                 if let ListType(other_list_type) = other {
@@ -285,32 +317,19 @@ impl TypeSignature {
             },
             OptionalType(ref my_inner_type) => {
                 if let OptionalType(other_inner_type) = other {
-                    // Option types will always admit a "NoType" OptionalType -- which
-                    //   can only be a None
-                    if other_inner_type.is_no_type() {
-                        true
-                    } else {
-                        my_inner_type.admits_type(other_inner_type)
-                    }
+                    // a "NoType" OptionalType -- which can only be a None --
+                    //   is admitted by the NoType rule above.
+                    my_inner_type.admits_type(other_inner_type)
                 } else {
                     false
                 }
             },
             ResponseType(ref my_inner_type) => {
                 if let ResponseType(other_inner_type) = other {
-                    // ResponseTypes admit according to the following rule:
-                    //   if other.ErrType is NoType, and other.OkType admits => admit
-                    //   if other.OkType is NoType, and other.ErrType admits => admit
-                    //   if both OkType and ErrType admit => admit
-                    //   otherwise fail.
-                    if other_inner_type.0.is_no_type() {
-                        my_inner_type.1.admits_type(&other_inner_type.1)
-                    } else if other_inner_type.1.is_no_type() {
-                        my_inner_type.0.admits_type(&other_inner_type.0)
-                    } else {
-                        my_inner_type.1.admits_type(&other_inner_type.1)
-                            && my_inner_type.0.admits_type(&other_inner_type.0)
-                    }
+                    // ResponseTypes admit if both the OkType and the ErrType admit,
+                    //   where a NoType branch (from an `ok` or `err` value) always admits.
+                    my_inner_type.0.admits_type(&other_inner_type.0)
+                        && my_inner_type.1.admits_type(&other_inner_type.1)
                 } else {
                     false
                 }
@@ -496,7 +515,7 @@ impl TypeSignature {
 
     ///
     /// This function returns the most-restrictive type that admits _both_ A and B (something like a least common supertype),
-    /// or Errors if no such type exists. On error, it throws NoSuperType(A,B), unless a constructor error'ed -- in which case,
+    /// or Errors if no such type exists. On error, it throws TypeError(A,B), unless a constructor error'ed -- in which case,
     /// it throws the constructor's error.
     ///
     ///  For two Tuples:
@@ -610,6 +629,8 @@ impl TypeSignature {
         TypeSignature::parent_list_type(&children_types)
     }
 
+    /// The type of a list of elements of the given types. The entry type is the least supertype
+    ///   of the elements' types, widening buffer and list lengths to the longest element's.
     pub fn parent_list_type(children: &[TypeSignature]) -> std::result::Result<ListTypeData, CheckErrors> {
         if let Some((first, rest)) = children.split_first() {
             let mut current_entry_type = first.clone();
@@ -623,6 +644,54 @@ impl TypeSignature {
             Ok(TypeSignature::empty_list())
         }
     }
+
+    /// Like `parent_list_type`, but fails with ListTypesMustMatch rather than widening the
+    ///   entry type to admit elements of different types, such as buffers of different lengths,
+    ///   or of incompatible types. Elements may still fill in each other's unknown parts: the
+    ///   entry type of `(list (ok 1) (err u2))` is `(response int uint)`.
+    pub fn strict_parent_list_type(children: &[TypeSignature]) -> std::result::Result<ListTypeData, CheckErrors> {
+        let list_type = Self::parent_list_type(children)
+            .map_err(|e| match e {
+                CheckErrors::TypeError(..) => CheckErrors::ListTypesMustMatch,
+                e => e
+            })?;
+        if children.iter().all(|child| only_unknowns_differ(child, list_type.get_list_item_type())) {
+            Ok(list_type)
+        } else {
+            Err(CheckErrors::ListTypesMustMatch)
+        }
+    }
+}
+
+// Whether `general` is `specific` with (some of) its unknown parts filled in.
+fn only_unknowns_differ(specific: &TypeSignature, general: &TypeSignature) -> bool {
+    match (specific, general) {
+        (NoType, _) => true,
+        // the type of an empty list is unknown, other than being a list
+        (ListType(specific_list), ListType(_)) if specific_list.get_list_item_type().is_no_type() => true,
+        (ListType(specific_list), ListType(general_list)) => {
+            specific_list.get_max_len() == general_list.get_max_len() &&
+                only_unknowns_differ(specific_list.get_list_item_type(), general_list.get_list_item_type())
+        },
+        (OptionalType(specific_inner), OptionalType(general_inner)) => {
+            only_unknowns_differ(specific_inner, general_inner)
+        },
+        (ResponseType(specific_inner), ResponseType(general_inner)) => {
+            only_unknowns_differ(&specific_inner.0, &general_inner.0) &&
+                only_unknowns_differ(&specific_inner.1, &general_inner.1)
+        },
+        (TupleType(specific_tuple), TupleType(general_tuple)) => {
+            let specific_fields = specific_tuple.get_type_map();
+            let general_fields = general_tuple.get_type_map();
+            specific_fields.len() == general_fields.len() &&
+                specific_fields.iter().all(|(name, specific_field)| {
+                    general_fields.get(name)
+                        .map(|general_field| only_unknowns_differ(specific_field, general_field))
+                        .unwrap_or(false)
+                })
+        },
+        (specific, general) => specific == general
+    }
 }
 
 
@@ -1045,6 +1114,101 @@ mod test {
         assert_eq!(TypeSignature::type_of(&value), type_descr);
     }
 
+    #[test]
+    fn no_type_is_admitted_by_everything() {
+        let optional_int: TypeSignature = "(optional int)".into();
+        assert!(optional_int.admits(&Value::none()));
+        assert!(optional_int.admits(&execute("(some 1)").unwrap().unwrap()));
+        assert!(!optional_int.admits(&execute("(some u1)").unwrap().unwrap()));
+
+        let response: TypeSignature = "(response int bool)".into();
+        assert!(response.admits(&execute("(ok 1)").unwrap().unwrap()));
+        assert!(response.admits(&execute("(err true)").unwrap().unwrap()));
+        assert!(!response.admits(&execute("(err 1)").unwrap().unwrap()));
+
+        let mixed = execute("(list none (some 1))").unwrap().unwrap();
+        assert_eq!(TypeSignature::type_of(&mixed), "(list 2 (optional int))".into());
+        assert!(TypeSignature::from("(list 2 (optional int))").admits(&mixed));
+        assert!(TypeSignature::from("(list 3 (optional int))").admits(&execute("(list none none)").unwrap().unwrap()));
+        assert!(TypeSignature::from("(list 2 (response int bool))")
+                .admits(&execute("(list (ok 1) (err false))").unwrap().unwrap()));
+        assert!(TypeSignature::from("(tuple (a (optional int)) (b (response int bool)))")
+                .admits(&execute("(tuple (a none) (b (err true)))").unwrap().unwrap()));
+
+        for type_repr in &["int", "(buff 1)", "(optional int)", "(response int bool)", "(list 1 int)"] {
+            assert!(TypeSignature::from(*type_repr).admits_type(&TypeSignature::NoType));
+        }
+    }
+
+    #[test]
+    fn strict_parent_list_type() {
+        let types_of = |values: &str| -> Vec<TypeSignature> {
+            match execute(values).unwrap().unwrap() {
+                Value::List(list) => list.data.iter().map(TypeSignature::type_of).collect(),
+                _ => panic!("expected a list")
+            }
+        };
+
+        // elements fill in each other's unknown types
+        let filled = [("(list (ok 1) (err u2))", "(list 2 (response int uint))"),
+                      ("(list (some (ok 1)) none (some (err u2)))", "(list 3 (optional (response int uint)))"),
+                      ("(list (list) (list 1 2))", "(list 2 (list 2 int))")];
+        for (values, expected) in filled.iter() {
+            let list_type = TypeSignature::strict_parent_list_type(&types_of(values)).unwrap();
+            assert_eq!(ListType(list_type), TypeSignature::from(*expected));
+        }
+
+        // incompatible types
+        let children = [IntType, BoolType];
+        assert_eq!(TypeSignature::parent_list_type(&children).unwrap_err(), TypeError(IntType, BoolType));
+        assert_eq!(TypeSignature::strict_parent_list_type(&children).unwrap_err(), ListTypesMustMatch);
+
+        // widened types
+        let children = types_of("(list 0x01 0x0102)");
+        assert_eq!(ListType(TypeSignature::parent_list_type(&children).unwrap()), TypeSignature::from("(list 2 (buff 2))"));
+        assert_eq!(TypeSignature::strict_parent_list_type(&children).unwrap_err(), ListTypesMustMatch);
+        let children = types_of("(list (list 1) (list 1 2))");
+        assert_eq!(TypeSignature::strict_parent_list_type(&children).unwrap_err(), ListTypesMustMatch);
+    }
+
+    #[test]
+    fn type_signature_from_str_round_trip() {
+        let trait_id = TraitIdentifier::parse_fully_qualified("S1G2081040G2081040G2081040G208105NK8PE5.contract.a-trait").unwrap();
+        let signatures = [
+            TypeSignature::IntType,
+            TypeSignature::UIntType,
+            TypeSignature::BoolType,
+            TypeSignature::PrincipalType,
+            TypeSignature::from("(buff 32)"),
+            TypeSignature::from("(list 10 int)"),
+            TypeSignature::from("(optional (list 10 int))"),
+            TypeSignature::from("(response (tuple (a int) (b (buff 2))) uint)"),
+            TypeSignature::from("(tuple (owner principal) (ids (list 5 (optional uint))))"),
+            TypeSignature::from("(list 3 (list 4 (response bool int)))"),
+            TypeSignature::TraitReferenceType(trait_id),
+        ];
+        for sig in signatures.iter() {
+            assert_eq!(&TypeSignature::from_str(&sig.to_string()).unwrap(), sig);
+        }
+    }
+
+    #[test]
+    fn type_signature_from_str_rejects() {
+        let malformed = ["", "integer", "(list int)", "(buff)", "(optional int uint)", "int uint",
+                         "(tuple (a))", "(response int)", "<not-a-trait>", "(optional UnknownType)"];
+        for type_repr in malformed.iter() {
+            assert!(TypeSignature::from_str(type_repr).is_err(), "{}", type_repr);
+        }
+
+        let mut too_deep = "int".to_string();
+        for _ in 0..MAX_TYPE_DEPTH {
+            too_deep = format!("(optional {})", too_deep);
+        }
+        assert_eq!(TypeSignature::from_str(&too_deep).unwrap_err(), TypeSignatureTooDeep);
+
+        assert_eq!(TypeSignature::from_str("(list 1000000 (buff 1000000))").unwrap_err(), ValueTooLarge);
+    }
+
     #[test]
     fn type_signature_way_too_big() {
         // first_tuple.type_size ~= 131
@@ -1105,4 +1269,43 @@ mod test {
             TypeSignature::from(*desc); // panics on failed types.
         }
     }
+
+    #[test]
+    fn test_least_supertype() {
+        let supertypes = [
+            ("int", "int", "int"),
+            ("(optional int)", "(optional int)", "(optional int)"),
+            ("(buff 2)", "(buff 10)", "(buff 10)"),
+            ("(list 2 (buff 2))", "(list 5 (buff 1))", "(list 5 (buff 2))"),
+            ("(tuple (a int) (b (buff 1)))", "(tuple (a int) (b (buff 3)))", "(tuple (a int) (b (buff 3)))"),
+        ];
+        for (a, b, expected) in supertypes.iter() {
+            assert_eq!(TypeSignature::least_supertype(&TypeSignature::from(*a), &TypeSignature::from(*b)).unwrap(),
+                       TypeSignature::from(*expected));
+        }
+
+        // the type of `none` and of `(ok ..)` leave a NoType to be filled in by the other type.
+        let none_type = TypeSignature::type_of(&Value::none());
+        assert_eq!(TypeSignature::least_supertype(&TypeSignature::from("(optional int)"), &none_type).unwrap(),
+                   TypeSignature::from("(optional int)"));
+        let ok_type = TypeSignature::type_of(&Value::okay(Value::Int(1)).unwrap());
+        let err_type = TypeSignature::type_of(&Value::error(Value::UInt(1)).unwrap());
+        assert_eq!(TypeSignature::least_supertype(&ok_type, &err_type).unwrap(),
+                   TypeSignature::from("(response int uint)"));
+        assert_eq!(TypeSignature::least_supertype(&TypeSignature::NoType, &TypeSignature::IntType).unwrap(),
+                   TypeSignature::IntType);
+
+        assert_eq!(TypeSignature::least_supertype(&TypeSignature::IntType, &TypeSignature::UIntType).unwrap_err(),
+                   TypeError(TypeSignature::IntType, TypeSignature::UIntType));
+
+        let no_supertypes = [
+            ("(optional int)", "(optional uint)"),
+            ("(tuple (a int))", "(tuple (b int))"),
+            ("(tuple (a int))", "(tuple (a int) (b int))"),
+            ("(tuple (a int))", "(tuple (a bool))"),
+        ];
+        for (a, b) in no_supertypes.iter() {
+            assert!(TypeSignature::least_supertype(&TypeSignature::from(*a), &TypeSignature::from(*b)).is_err());
+        }
+    }
 }